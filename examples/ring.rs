@@ -1,4 +1,4 @@
-use ring::{digest, hmac};
+use ring::hmac;
 
 fn main() {
     let key = hmac::Key::new(hmac::HMAC_SHA256, b"secret_key");
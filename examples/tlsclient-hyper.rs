@@ -1,15 +1,18 @@
 // Ref: https://github.com/rustls/hyper-rustls/blob/main/examples/client.rs
 
-//! Simple HTTPS GET client based on hyper-rustls
+//! Simple HTTPS GET client based on hyper and this crate's TLS stack
 //!
 //! First parameter is the mandatory URL to GET.
 //! Second parameter is an optional path to CA store.
 use hyper::{body::to_bytes, client, Body, Uri};
-use hyper_rustls::ConfigBuilderExt;
-use rustls::RootCertStore;
+use tls_12::client::ClientConfig;
+use tls_12::https::{HttpsConnector, ALPN_H2, ALPN_HTTP1};
+use tls_12::key_log::KeyLogFile;
+use tls_12::verify::RootCertStore;
 
 use std::str::FromStr;
-use std::{env, fs, io};
+use std::sync::Arc;
+use std::{env, io};
 
 fn main() {
     // Send GET request and inspect result, with proper error handling.
@@ -20,7 +23,7 @@ fn main() {
 }
 
 fn error(err: String) -> io::Error {
-    io::Error::new(io::ErrorKind::Other, err)
+    io::Error::other(err)
 }
 
 #[tokio::main]
@@ -35,42 +38,29 @@ async fn run_client() -> io::Result<()> {
     };
 
     // Second parameter is custom Root-CA store (optional, defaults to native cert store).
-    let mut ca = match env::args().nth(2) {
-        Some(ref path) => {
-            let f = fs::File::open(path)
-                .map_err(|e| error(format!("failed to open {}: {}", path, e)))?;
-            let rd = io::BufReader::new(f);
-            Some(rd)
+    let mut roots = RootCertStore::new();
+    match env::args().nth(2) {
+        Some(ref path) => roots
+            .load_file(path)
+            .map_err(|e| error(format!("failed to load {}: {}", path, e)))?,
+        None => {
+            for cert in rustls_native_certs::load_native_certs()? {
+                // skip roots this crate cannot parse
+                let _ = roots.add(&cert.0);
+            }
         }
-        None => None,
-    };
+    }
 
-    // Prepare the TLS client config
-    let tls = match ca {
-        Some(ref mut rd) => {
-            // Read trust roots
-            let certs = rustls_pemfile::certs(rd)
-                .map_err(|_| error("failed to load custom CA store".into()))?;
-            let mut roots = RootCertStore::empty();
-            roots.add_parsable_certificates(&certs);
-            // TLS client config using the custom CA store for lookups
-            rustls::ClientConfig::builder()
-                .with_safe_defaults()
-                .with_root_certificates(roots)
-                .with_no_client_auth()
-        }
-        // Default TLS client config with native roots
-        None => rustls::ClientConfig::builder()
-            .with_safe_defaults()
-            .with_native_roots()
-            .with_no_client_auth(),
-    };
+    // Prepare the TLS client config, the server chooses HTTP/2 or HTTP/1.1 with ALPN
+    let tls = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_alpn_protocols(vec![ALPN_H2.to_vec(), ALPN_HTTP1.to_vec()])
+        // honours SSLKEYLOGFILE, for Wireshark
+        .with_key_log(Arc::new(KeyLogFile::new()))
+        .build()
+        .map_err(|e| error(format!("{}", e)))?;
     // Prepare the HTTPS connector
-    let https = hyper_rustls::HttpsConnectorBuilder::new()
-        .with_tls_config(tls)
-        .https_or_http()
-        .enable_http1()
-        .build();
+    let https = HttpsConnector::new(Arc::new(tls));
 
     // Build the hyper client from the HTTPS connector.
     let client: client::Client<_, hyper::Body> = client::Client::builder().build(https);
@@ -84,6 +74,7 @@ async fn run_client() -> io::Result<()> {
             .await
             .map_err(|e| error(format!("Could not get: {:?}", e)))?;
         println!("Status:\n{}", res.status());
+        println!("Version:\n{:?}", res.version());
         println!("Headers:\n{:#?}", res.headers());
 
         let body: Body = res.into_body();
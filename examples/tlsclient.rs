@@ -1,67 +1,51 @@
-use rustls::internal::msgs::handshake::HandshakePayload::Certificate;
-use std::io::{Read, Write};
+use std::env;
+use std::io::{self, Read, Write};
 use std::net::TcpStream;
-use std::os::fd::AsFd;
-use tls_12::handshake::HandshakePayload;
+use std::sync::Arc;
+use tls_12::client::ClientConfig;
+use tls_12::key_log::KeyLogFile;
+use tls_12::stream::TlsStream;
+use tls_12::verify::RootCertStore;
 
-fn main() -> std::io::Result<()> {
-    let mut stream = TcpStream::connect("127.0.0.1:1337")?;
-    let mut client_hello = HandshakePayload::client_hello();
-    let mut buf = client_hello.encode();
-    println!("{:?}", buf);
-    stream.write_all(&mut buf)?;
+// usage: tlsclient [host] [port] [ca-file]
+// Without ca-file the server's certificate is checked against the system trust anchors.
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    let host = args.get(1).map_or("localhost", String::as_str);
+    let port = args.get(2).map_or("4433", String::as_str);
 
-    // let mut result = [0; 1024];
-    // stream.read(&mut result)?;
-    // println!("{:?}", result);
-
-    // ref: https://github.com/sat0ken/go-tcpip/blob/e6defa2b8b44031df4407ff228df7aa631d8d287/example/tls12_handshake_clientauth.go#L41-L50
-    let mut recv_buf: [u8; 2000] = [0; 2000];
-    stream.read(&mut recv_buf)?;
-
-    let parsed_buf = HandshakePayload::parse_packet(&recv_buf);
-    for buf in parsed_buf {
-        if buf.len() == 0 {
-            continue;
-        }
-        match buf[2] {
-            0x02 => {
-                println!("server hello");
-                let server_hello_payload = HandshakePayload::read_server_hello(buf.to_vec());
-                println!("{:?}", server_hello_payload);
-            }
-            0x0b => {
-                println!("certificate");
-                let certificate_payload = HandshakePayload::read_certificate(buf.to_vec());
-                // verify
-                if certificate_payload.verify() {
-                    println!("certificate verify success!!!!!");
-                } else {
-                    println!("certificate verify failed");
-                }
-            }
-            0x0c => {
-                println!("server key exchange");
-                let server_key_exchange_payload =
-                    HandshakePayload::read_server_key_exchange(buf.to_vec());
-                println!("{:?}", server_key_exchange_payload);
-
-                // 鍵交換
-                server_key_exchange_payload.generate_shared_key();
-            }
-            0x0e => {
-                println!("server hello done");
-                let server_hello_done_payload =
-                    HandshakePayload::read_server_hello_done(buf[6..].to_vec());
-                println!("{:?}", server_hello_done_payload);
-            }
-            _ => {
-                println!("unknown");
+    let mut root_store = RootCertStore::new();
+    match args.get(3) {
+        Some(ca_file) => root_store.load_file(ca_file)?,
+        None => {
+            for certificate in rustls_native_certs::load_native_certs()? {
+                // skip anchors this crate cannot parse
+                let _ = root_store.add(&certificate.0);
             }
         }
-        // println!("{:?}", buf);
-        // println!("{:?}", buf[4]);
     }
-    // println!("{:?}", recv_buf);
+    let config = ClientConfig::builder()
+        .with_root_certificates(root_store)
+        // honours SSLKEYLOGFILE, for Wireshark
+        .with_key_log(Arc::new(KeyLogFile::new()))
+        .build()
+        .map_err(io::Error::other)?;
+    let socket = TcpStream::connect(format!("{}:{}", host, port))?;
+    let mut stream = TlsStream::connect(Arc::new(config), host, socket);
+
+    write!(
+        stream,
+        "GET / HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        host
+    )?;
+    println!(
+        "handshake done, ALPN protocol: {:?}",
+        stream.connection().alpn_protocol()
+    );
+
+    // until the server sends close_notify, a truncated response is an error
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+    io::stdout().write_all(&response)?;
     Ok(())
 }
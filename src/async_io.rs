@@ -0,0 +1,290 @@
+use crate::client::{ClientConfig, ClientConnection};
+use crate::connection::Connection;
+use crate::server::{ServerConfig, ServerConnection};
+use std::future::poll_fn;
+use std::io::{self, Read, Write};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+// Run a client handshake over io, e.g. a tokio::net::TcpStream.
+pub async fn connect<IO: AsyncRead + AsyncWrite + Unpin>(
+    config: Arc<ClientConfig>,
+    server_name: &str,
+    io: IO,
+) -> io::Result<AsyncTlsStream<ClientConnection, IO>> {
+    let mut stream = AsyncTlsStream::new(ClientConnection::new(config, server_name), io);
+    poll_fn(|cx| stream.poll_handshake(cx)).await?;
+    Ok(stream)
+}
+
+// Run a server handshake over io, e.g. a tokio::net::TcpStream.
+pub async fn accept<IO: AsyncRead + AsyncWrite + Unpin>(
+    config: Arc<ServerConfig>,
+    io: IO,
+) -> io::Result<AsyncTlsStream<ServerConnection, IO>> {
+    let mut stream = AsyncTlsStream::new(ServerConnection::new(config), io);
+    poll_fn(|cx| stream.poll_handshake(cx)).await?;
+    Ok(stream)
+}
+
+// The async counterpart of stream::TlsStream. connect and accept return it once the handshake is
+// done, a stream from new runs the handshake on first use. poll_read is Ok with nothing read
+// after the peer's close_notify, and UnexpectedEof if the transport closes before it.
+// poll_shutdown sends close_notify, then shuts down the transport.
+pub struct AsyncTlsStream<C: Connection, IO> {
+    conn: C,
+    io: IO,
+    // close_notify was queued by poll_shutdown or in reply to the peer's
+    closed: bool,
+}
+
+impl<C: Connection + Unpin, IO: AsyncRead + AsyncWrite + Unpin> AsyncTlsStream<C, IO> {
+    pub fn new(conn: C, io: IO) -> Self {
+        AsyncTlsStream {
+            conn,
+            io,
+            closed: false,
+        }
+    }
+
+    // e.g. for alpn_protocol or peer_certificates
+    pub fn connection(&self) -> &C {
+        &self.conn
+    }
+
+    pub fn get_ref(&self) -> &IO {
+        &self.io
+    }
+
+    pub fn get_mut(&mut self) -> &mut IO {
+        &mut self.io
+    }
+
+    pub fn into_inner(self) -> (C, IO) {
+        (self.conn, self.io)
+    }
+
+    // the first handshake, renegotiation happens while reading
+    fn poll_first_handshake(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.conn.common().is_established() {
+            return Poll::Ready(Ok(()));
+        }
+        self.poll_handshake(cx)
+    }
+
+    fn poll_handshake(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            ready!(self.poll_write_all_tls(cx))?;
+            if !self.conn.is_handshaking() {
+                return Poll::Ready(Ok(()));
+            }
+            if ready!(self.poll_read_tls(cx))? == 0 {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "connection closed during the handshake",
+                )));
+            }
+        }
+    }
+
+    // read once from the transport and process what arrived
+    fn poll_read_tls(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let n = match self.conn.read_tls(&mut SyncAdapter {
+            io: &mut self.io,
+            cx,
+        }) {
+            Ok(n) => n,
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Poll::Pending,
+            Err(error) => return Poll::Ready(Err(error)),
+        };
+        if let Err(error) = self.conn.process_new_packets() {
+            // try to tell the peer why the connection is closed
+            let _ = self.poll_write_all_tls(cx);
+            return Poll::Ready(Err(error.into()));
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_write_all_tls(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.conn.wants_write() {
+            match self.conn.write_tls(&mut SyncAdapter {
+                io: &mut self.io,
+                cx,
+            }) {
+                Ok(0) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Ok(_) => {}
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Poll::Pending,
+                Err(error) => return Poll::Ready(Err(error)),
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<C: Connection + Unpin, IO: AsyncRead + AsyncWrite + Unpin> AsyncRead
+    for AsyncTlsStream<C, IO>
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_first_handshake(cx))?;
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        loop {
+            match this.conn.reader().read(buf.initialize_unfilled()) {
+                Ok(0) => {
+                    // the peer sent close_notify
+                    if !this.closed {
+                        this.closed = true;
+                        this.conn.send_close_notify();
+                        let _ = this.poll_write_all_tls(cx);
+                    }
+                    return Poll::Ready(Ok(()));
+                }
+                Ok(n) => {
+                    buf.advance(n);
+                    return Poll::Ready(Ok(()));
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {}
+                Err(error) => return Poll::Ready(Err(error)),
+            }
+            // e.g. a renegotiation handshake
+            ready!(this.poll_write_all_tls(cx))?;
+            ready!(this.poll_read_tls(cx))?;
+        }
+    }
+}
+
+impl<C: Connection + Unpin, IO: AsyncRead + AsyncWrite + Unpin> AsyncWrite
+    for AsyncTlsStream<C, IO>
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.closed {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "close_notify was already sent",
+            )));
+        }
+        ready!(this.poll_first_handshake(cx))?;
+        // the records of earlier writes go out first, so a slow peer slows down the writer
+        ready!(this.poll_write_all_tls(cx))?;
+        let n = this.conn.writer().write(buf)?;
+        // the data is consumed even if the transport is not ready, poll_flush sends the rest
+        let _ = this.poll_write_all_tls(cx);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_first_handshake(cx))?;
+        ready!(this.poll_write_all_tls(cx))?;
+        Pin::new(&mut this.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.closed {
+            this.closed = true;
+            this.conn.send_close_notify();
+        }
+        ready!(this.poll_write_all_tls(cx))?;
+        Pin::new(&mut this.io).poll_shutdown(cx)
+    }
+}
+
+// std::io over a tokio transport for read_tls and write_tls. Poll::Pending becomes WouldBlock,
+// and the waker in cx is registered for when the transport is ready.
+struct SyncAdapter<'a, 'b, IO> {
+    io: &'a mut IO,
+    cx: &'a mut Context<'b>,
+}
+
+impl<IO: AsyncRead + Unpin> Read for SyncAdapter<'_, '_, IO> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut buf = ReadBuf::new(buf);
+        match Pin::new(&mut *self.io).poll_read(self.cx, &mut buf) {
+            Poll::Ready(Ok(())) => Ok(buf.filled().len()),
+            Poll::Ready(Err(error)) => Err(error),
+            Poll::Pending => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+impl<IO: AsyncWrite + Unpin> Write for SyncAdapter<'_, '_, IO> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match Pin::new(&mut *self.io).poll_write(self.cx, buf) {
+            Poll::Ready(result) => result,
+            Poll::Pending => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match Pin::new(&mut *self.io).poll_flush(self.cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::load_private_key;
+    use crate::sign::CertifiedKey;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    const LEAF: &[u8] = include_bytes!("../testdata/ct/leaf.der");
+    const LEAF_KEY: &[u8] = include_bytes!("../testdata/ct/leaf.key");
+
+    #[tokio::test]
+    async fn echo_and_shutdown_over_tcp() {
+        let server_config = ServerConfig::builder()
+            .with_single_cert(CertifiedKey::new(
+                vec![LEAF.to_vec()],
+                load_private_key(LEAF_KEY).unwrap(),
+            ))
+            .with_alpn_protocols(vec![b"h2".to_vec()])
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let client_config = ClientConfig::builder()
+            .with_no_certificate_verification()
+            .with_alpn_protocols(vec![b"h2".to_vec()])
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (sock, _) = listener.accept().await.unwrap();
+            let mut stream = accept(server_config, sock).await.unwrap();
+            let mut received = Vec::new();
+            stream.read_to_end(&mut received).await.unwrap();
+            stream.write_all(&received).await.unwrap_err();
+            received
+        });
+
+        let sock = TcpStream::connect(addr).await.unwrap();
+        let mut stream = connect(client_config, "localhost", sock).await.unwrap();
+        assert_eq!(stream.connection().alpn_protocol(), Some(&b"h2"[..]));
+        stream.write_all(b"hello").await.unwrap();
+        stream.shutdown().await.unwrap();
+        // the server answers close_notify with close_notify
+        let mut rest = Vec::new();
+        assert_eq!(stream.read_to_end(&mut rest).await.unwrap(), 0);
+        assert_eq!(server.await.unwrap(), b"hello");
+    }
+}
@@ -1,6 +1,13 @@
+use crate::codec::{decode_all, encode_opaque, read_opaque, Codec, LengthPrefix, Reader};
 use crate::enums::HandshakeType;
-use crate::protocol_version::ProtocolVersion;
+use crate::error::{CertificateError, Error};
+use crate::handshake::HandshakePayload;
+use std::fmt;
+use std::net::IpAddr;
+use x509_parser::der_parser::oid::Oid;
+use x509_parser::objects::{oid2sn, oid_registry};
 use x509_parser::prelude::*;
+use x509_parser::public_key::PublicKey;
 
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-2--Server-Certificate
 // opaque ASN.1Cert<1..2^24-1>;
@@ -11,29 +18,500 @@ use x509_parser::prelude::*;
 #[derive(Debug)]
 pub struct Certificate {
     handshake_type: HandshakeType,
-    length: u32,
     certificate_list: Vec<u8>,
 }
 
 impl Certificate {
-    pub fn read(buffer: Vec<u8>, len: u32) -> Certificate {
-        let buffer = buffer;
+    // certificates are DER encoded, the sender's certificate first
+    pub fn new(certificates: &[Vec<u8>]) -> Certificate {
+        let mut certificate_list = Vec::new();
+        for certificate in certificates {
+            encode_opaque(&mut certificate_list, LengthPrefix::U24, certificate);
+        }
         Certificate {
             handshake_type: HandshakeType::Certificate,
-            length: len,
-            certificate_list: buffer,
+            certificate_list,
+        }
+    }
+
+    // every ASN.1Cert must fit exactly into certificate_list
+    pub fn read(buf: &[u8]) -> Result<Certificate, Error> {
+        decode_all(buf).ok_or(Error::Decode("Certificate"))
+    }
+
+    // split certificate_list into DER encoded certificates. The sender's certificate comes first.
+    pub fn certificates(&self) -> Vec<Vec<u8>> {
+        let mut certificates = Vec::new();
+        let mut list = Reader::new(&self.certificate_list);
+        while let Some(certificate) = read_opaque(&mut list, LengthPrefix::U24) {
+            certificates.push(certificate.to_vec());
         }
+        certificates
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        HandshakePayload::encode_message(self.handshake_type, &self.get_encoding())
+    }
+
+    // subjectPublicKey of the leaf certificate, used to verify the ServerKeyExchange signature
+    pub fn public_key(&self) -> Option<Vec<u8>> {
+        let certificates = self.certificates();
+        let (_, cert) = parse_x509_certificate(certificates.first()?).ok()?;
+        Some(cert.public_key().subject_public_key.data.to_vec())
+    }
+
+    // DER encoded issuer names of every certificate in the list
+    pub fn issuers(&self) -> Vec<Vec<u8>> {
+        self.certificates()
+            .iter()
+            .filter_map(|certificate| {
+                let (_, cert) = parse_x509_certificate(certificate).ok()?;
+                Some(cert.issuer().as_raw().to_vec())
+            })
+            .collect()
     }
 
-    pub fn verify(&self) -> bool {
-        let mut buffer = &self.certificate_list;
-        let res = parse_x509_certificate(buffer);
-        match res {
-            Ok((rem, cert)) => true,
-            Err(e) => {
-                println!("error: {:?}", e);
-                false
+    // the leaf is present and parses as an X.509 certificate
+    pub fn verify(&self) -> Result<(), CertificateError> {
+        let certificates = self.certificates();
+        let Some(leaf) = certificates.first() else {
+            return Err(CertificateError::Bad(String::from("no certificate")));
+        };
+        match parse_x509_certificate(leaf) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(CertificateError::Bad(format!("invalid certificate: {}", e))),
+        }
+    }
+
+    // parsed details of every certificate in the list, the sender's certificate first.
+    // None if any of them is not a valid X.509 certificate.
+    pub fn chain_info(&self) -> Option<Vec<CertificateInfo>> {
+        chain_info(&self.certificates())
+    }
+}
+
+impl Codec for Certificate {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        encode_opaque(bytes, LengthPrefix::U24, &self.certificate_list);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let certificate_list = read_opaque(r, LengthPrefix::U24)?.to_vec();
+        let mut list = Reader::new(&certificate_list);
+        while list.any_left() {
+            if read_opaque(&mut list, LengthPrefix::U24)?.is_empty() {
+                return None;
             }
         }
+        Some(Certificate {
+            handshake_type: HandshakeType::Certificate,
+            certificate_list,
+        })
+    }
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5280.html#4-1--Basic-Certificate-Fields
+// Details of an X.509 certificate, e.g. for logging the certificates a server presented.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CertificateInfo {
+    // distinguished names in the RFC 4514 string form, e.g. "CN=localhost"
+    pub subject: String,
+    pub issuer: String,
+    // hex encoded, colon separated
+    pub serial: String,
+    // seconds since the UNIX epoch
+    pub not_before: i64,
+    pub not_after: i64,
+    pub subject_alt_names: Vec<SubjectAltName>,
+    pub public_key_algorithm: PublicKeyAlgorithm,
+    // RSA modulus size or EC curve size, 0 if unknown
+    pub public_key_bits: usize,
+    // short name such as "ecdsa-with-SHA256", the dotted OID if unknown
+    pub signature_algorithm: String,
+    // key usage bits such as "digitalSignature", empty without the extension
+    pub key_usage: Vec<String>,
+    // extended key usages such as "serverAuth", unknown ones as dotted OIDs
+    pub extended_key_usage: Vec<String>,
+    // basicConstraints cA
+    pub is_ca: bool,
+    // hashes of the DER encoded certificate
+    pub sha1_fingerprint: [u8; 20],
+    pub sha256_fingerprint: [u8; 32],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubjectAltName {
+    Dns(String),
+    Ip(IpAddr),
+    Email(String),
+    Uri(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PublicKeyAlgorithm {
+    Rsa,
+    // curve short name, e.g. "prime256v1"
+    Ec(String),
+    Ed25519,
+    // dotted OID
+    Other(String),
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5280.html#4-2-1-3--Key-Usage
+const KEY_USAGE_NAMES: [&str; 9] = [
+    "digitalSignature",
+    "nonRepudiation",
+    "keyEncipherment",
+    "dataEncipherment",
+    "keyAgreement",
+    "keyCertSign",
+    "cRLSign",
+    "encipherOnly",
+    "decipherOnly",
+];
+
+impl CertificateInfo {
+    // certificate is DER encoded
+    pub fn parse(certificate: &[u8]) -> Option<CertificateInfo> {
+        let (_, cert) = parse_x509_certificate(certificate).ok()?;
+
+        let mut subject_alt_names = Vec::new();
+        if let Ok(Some(san)) = cert.subject_alternative_name() {
+            for name in &san.value.general_names {
+                match name {
+                    GeneralName::DNSName(name) => {
+                        subject_alt_names.push(SubjectAltName::Dns(name.to_string()))
+                    }
+                    GeneralName::RFC822Name(name) => {
+                        subject_alt_names.push(SubjectAltName::Email(name.to_string()))
+                    }
+                    GeneralName::URI(uri) => {
+                        subject_alt_names.push(SubjectAltName::Uri(uri.to_string()))
+                    }
+                    GeneralName::IPAddress(address) => {
+                        if let Some(address) = ip_address(address) {
+                            subject_alt_names.push(SubjectAltName::Ip(address));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let key_usage = match cert.key_usage() {
+            Ok(Some(key_usage)) => KEY_USAGE_NAMES
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| key_usage.value.flags >> bit & 1 == 1)
+                .map(|(_, name)| name.to_string())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let mut extended_key_usage = Vec::new();
+        if let Ok(Some(eku)) = cert.extended_key_usage() {
+            let eku = eku.value;
+            for (present, name) in [
+                (eku.any, "anyExtendedKeyUsage"),
+                (eku.server_auth, "serverAuth"),
+                (eku.client_auth, "clientAuth"),
+                (eku.code_signing, "codeSigning"),
+                (eku.email_protection, "emailProtection"),
+                (eku.time_stamping, "timeStamping"),
+                (eku.ocsp_signing, "OCSPSigning"),
+            ] {
+                if present {
+                    extended_key_usage.push(name.to_string());
+                }
+            }
+            extended_key_usage.extend(eku.other.iter().map(|oid| oid.to_id_string()));
+        }
+
+        let (public_key_algorithm, public_key_bits) = public_key_info(cert.public_key());
+
+        Some(CertificateInfo {
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            serial: cert.raw_serial_as_string(),
+            not_before: cert.validity().not_before.timestamp(),
+            not_after: cert.validity().not_after.timestamp(),
+            subject_alt_names,
+            public_key_algorithm,
+            public_key_bits,
+            signature_algorithm: oid_name(&cert.signature_algorithm.algorithm),
+            key_usage,
+            extended_key_usage,
+            is_ca: matches!(cert.basic_constraints(), Ok(Some(constraints)) if constraints.value.ca),
+            sha1_fingerprint: ring::digest::digest(
+                &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+                certificate,
+            )
+            .as_ref()
+            .try_into()
+            .ok()?,
+            sha256_fingerprint: ring::digest::digest(&ring::digest::SHA256, certificate)
+                .as_ref()
+                .try_into()
+                .ok()?,
+        })
+    }
+}
+
+// one line per certificate, for logs
+impl fmt::Display for CertificateInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "subject=\"{}\" issuer=\"{}\" serial={} not_before={} not_after={}",
+            self.subject, self.issuer, self.serial, self.not_before, self.not_after
+        )?;
+        if !self.subject_alt_names.is_empty() {
+            let names: Vec<String> = self
+                .subject_alt_names
+                .iter()
+                .map(|name| match name {
+                    SubjectAltName::Dns(name) => format!("DNS:{}", name),
+                    SubjectAltName::Ip(address) => format!("IP:{}", address),
+                    SubjectAltName::Email(name) => format!("email:{}", name),
+                    SubjectAltName::Uri(uri) => format!("URI:{}", uri),
+                })
+                .collect();
+            write!(f, " san={}", names.join(","))?;
+        }
+        let public_key_algorithm = match &self.public_key_algorithm {
+            PublicKeyAlgorithm::Rsa => "rsa",
+            PublicKeyAlgorithm::Ec(curve) => curve,
+            PublicKeyAlgorithm::Ed25519 => "ed25519",
+            PublicKeyAlgorithm::Other(oid) => oid,
+        };
+        write!(
+            f,
+            " key={}/{} signature={}",
+            public_key_algorithm, self.public_key_bits, self.signature_algorithm
+        )?;
+        if !self.key_usage.is_empty() {
+            write!(f, " key_usage={}", self.key_usage.join(","))?;
+        }
+        if !self.extended_key_usage.is_empty() {
+            write!(
+                f,
+                " extended_key_usage={}",
+                self.extended_key_usage.join(",")
+            )?;
+        }
+        if self.is_ca {
+            write!(f, " ca")?;
+        }
+        write!(f, " sha256={}", hex(&self.sha256_fingerprint))
+    }
+}
+
+// parse DER encoded certificates, leaf first. None if any of them is invalid.
+pub fn chain_info(certificates: &[Vec<u8>]) -> Option<Vec<CertificateInfo>> {
+    certificates
+        .iter()
+        .map(|certificate| CertificateInfo::parse(certificate))
+        .collect()
+}
+
+fn public_key_info(spki: &SubjectPublicKeyInfo) -> (PublicKeyAlgorithm, usize) {
+    let algorithm = &spki.algorithm.algorithm;
+    match spki.parsed() {
+        Ok(PublicKey::RSA(rsa)) => {
+            // the modulus is a positive INTEGER and may start with zero bytes
+            let modulus: &[u8] = match rsa.modulus.iter().position(|byte| *byte != 0) {
+                Some(start) => &rsa.modulus[start..],
+                None => &[],
+            };
+            let bits = match modulus.first() {
+                Some(first) => modulus.len() * 8 - first.leading_zeros() as usize,
+                None => 0,
+            };
+            (PublicKeyAlgorithm::Rsa, bits)
+        }
+        Ok(PublicKey::EC(point)) => {
+            let curve = spki
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|parameters| parameters.as_oid().ok())
+                .map(|curve| oid_name(&curve))
+                .unwrap_or_default();
+            // P-521 points are encoded in 66 bytes
+            let bits = match curve.as_str() {
+                "secp521r1" => 521,
+                _ => point.key_size(),
+            };
+            (PublicKeyAlgorithm::Ec(curve), bits)
+        }
+        // id-Ed25519 1.3.101.112
+        _ if algorithm.as_bytes() == [0x2b, 0x65, 0x70] => (PublicKeyAlgorithm::Ed25519, 256),
+        _ => (PublicKeyAlgorithm::Other(algorithm.to_id_string()), 0),
+    }
+}
+
+fn oid_name(oid: &Oid) -> String {
+    match oid2sn(oid, oid_registry()) {
+        Ok(name) => name.to_string(),
+        Err(_) => oid.to_id_string(),
+    }
+}
+
+fn ip_address(address: &[u8]) -> Option<IpAddr> {
+    match address.len() {
+        4 => Some(IpAddr::from(<[u8; 4]>::try_from(address).ok()?)),
+        16 => Some(IpAddr::from(<[u8; 16]>::try_from(address).ok()?)),
+        _ => None,
+    }
+}
+
+// colon separated upper case hex, like openssl x509 -fingerprint
+pub fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<String>>()
+        .join(":")
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc7469.html#2-4--Semantics-of-Pins
+// SHA-256 hash of the DER encoded SubjectPublicKeyInfo of a certificate
+pub fn spki_sha256(certificate: &[u8]) -> Option<[u8; 32]> {
+    let (_, cert) = parse_x509_certificate(certificate).ok()?;
+    ring::digest::digest(&ring::digest::SHA256, cert.public_key().raw)
+        .as_ref()
+        .try_into()
+        .ok()
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5280.html#4-1-1-2--signatureAlgorithm
+// Verify a signature made by the key of the signer certificate over an X.509 style structure
+// (certificate, CRL or OCSP response). algorithm is the contents of the signatureAlgorithm OID.
+pub fn verify_signed_data(
+    signer: &[u8],
+    algorithm: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> bool {
+    let Ok((_, signer)) = parse_x509_certificate(signer) else {
+        return false;
+    };
+    let public_key = &signer.public_key().subject_public_key.data;
+    // ECDSA OIDs only name the hash, so try both curves
+    signed_data_algorithms(algorithm).iter().any(|algorithm| {
+        ring::signature::UnparsedPublicKey::new(*algorithm, public_key)
+            .verify(message, signature)
+            .is_ok()
+    })
+}
+
+// cert is signed by issuer and names it as its issuer
+pub fn is_issued_by(cert: &[u8], issuer: &[u8]) -> bool {
+    let (Ok((_, parsed)), Ok((_, parsed_issuer))) =
+        (parse_x509_certificate(cert), parse_x509_certificate(issuer))
+    else {
+        return false;
+    };
+    parsed.issuer().as_raw() == parsed_issuer.subject().as_raw()
+        && verify_signed_data(
+            issuer,
+            parsed.signature_algorithm.algorithm.as_bytes(),
+            parsed.tbs_certificate.as_ref(),
+            &parsed.signature_value.data,
+        )
+}
+
+fn signed_data_algorithms(
+    algorithm: &[u8],
+) -> Vec<&'static dyn ring::signature::VerificationAlgorithm> {
+    use ring::signature::*;
+    match algorithm {
+        // sha1WithRSAEncryption 1.2.840.113549.1.1.5
+        [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x05] => {
+            vec![&RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY]
+        }
+        // sha256WithRSAEncryption 1.2.840.113549.1.1.11
+        [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b] => {
+            vec![&RSA_PKCS1_2048_8192_SHA256]
+        }
+        // sha384WithRSAEncryption 1.2.840.113549.1.1.12
+        [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0c] => {
+            vec![&RSA_PKCS1_2048_8192_SHA384]
+        }
+        // sha512WithRSAEncryption 1.2.840.113549.1.1.13
+        [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0d] => {
+            vec![&RSA_PKCS1_2048_8192_SHA512]
+        }
+        // ecdsa-with-SHA256 1.2.840.10045.4.3.2
+        [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02] => {
+            vec![&ECDSA_P256_SHA256_ASN1, &ECDSA_P384_SHA256_ASN1]
+        }
+        // ecdsa-with-SHA384 1.2.840.10045.4.3.3
+        [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03] => {
+            vec![&ECDSA_P384_SHA384_ASN1, &ECDSA_P256_SHA384_ASN1]
+        }
+        // id-Ed25519 1.3.101.112
+        [0x2b, 0x65, 0x70] => vec![&ED25519],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA: &[u8] = include_bytes!("../testdata/ocsp/ca.der");
+    const LEAF: &[u8] = include_bytes!("../testdata/ct/leaf.der");
+    const RESPONDER: &[u8] = include_bytes!("../testdata/ocsp/responder.der");
+
+    #[test]
+    fn parse_certificate_info() {
+        let certificate = Certificate::new(&[LEAF.to_vec(), CA.to_vec()]);
+        let chain = certificate.chain_info().unwrap();
+        let (leaf, ca) = (&chain[0], &chain[1]);
+
+        assert_eq!(leaf.subject, "CN=localhost");
+        assert_eq!(leaf.issuer, "CN=OCSP Test CA");
+        assert_eq!(leaf.serial, "12:34:56:78:90");
+        // 2026-10-19 and 2027-10-19 00:00:00 UTC
+        assert_eq!(leaf.not_before, 1792368000);
+        assert_eq!(leaf.not_after, 1823904000);
+        assert_eq!(
+            leaf.subject_alt_names,
+            vec![SubjectAltName::Dns(String::from("localhost"))]
+        );
+        assert_eq!(
+            leaf.public_key_algorithm,
+            PublicKeyAlgorithm::Ec(String::from("prime256v1"))
+        );
+        assert_eq!(leaf.public_key_bits, 256);
+        assert_eq!(leaf.signature_algorithm, "ecdsa-with-SHA256");
+        assert!(!leaf.is_ca);
+        assert_eq!(
+            hex(&leaf.sha256_fingerprint),
+            "EF:30:3D:80:27:FA:84:70:D7:57:D7:B5:E9:66:11:2B:B0:AC:5C:40:5E:7E:EB:9A:94:E4:09:CD:55:E1:D7:AC"
+        );
+
+        assert!(ca.is_ca);
+        assert_eq!(ca.key_usage, vec!["keyCertSign", "cRLSign"]);
+        let responder = CertificateInfo::parse(RESPONDER).unwrap();
+        assert_eq!(responder.extended_key_usage, vec!["OCSPSigning"]);
+
+        assert!(CertificateInfo::parse(&LEAF[1..]).is_none());
+    }
+
+    #[test]
+    fn read_rejects_malformed_list() {
+        let encoded = Certificate::new(&[LEAF.to_vec(), CA.to_vec()]).encode();
+        let body = encoded[4..].to_vec();
+        let certificate = Certificate::read(&body).unwrap();
+        assert_eq!(certificate.certificates(), vec![LEAF.to_vec(), CA.to_vec()]);
+
+        for len in 0..body.len() {
+            assert!(Certificate::read(&body[..len]).is_err());
+        }
+        let mut trailing = body.clone();
+        trailing.push(0);
+        assert!(Certificate::read(&trailing).is_err());
     }
 }
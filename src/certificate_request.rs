@@ -0,0 +1,118 @@
+use crate::codec::{
+    decode_all, encode_opaque, encode_opaque_vec, encode_vec, read_opaque, read_opaque_vec,
+    read_vec, Codec, LengthPrefix, Reader,
+};
+use crate::enums::{HandshakeType, SignatureScheme};
+use crate::error::Error;
+use crate::handshake::HandshakePayload;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-4--Certificate-Request
+// struct {
+//     ClientCertificateType certificate_types<1..2^8-1>;
+//     SignatureAndHashAlgorithm
+//       supported_signature_algorithms<2^16-1>;
+//     DistinguishedName certificate_authorities<0..2^16-1>;
+// } CertificateRequest;
+//
+// opaque DistinguishedName<1..2^16-1>;
+#[derive(Debug)]
+pub struct CertificateRequest {
+    handshake_type: HandshakeType,
+    certificate_types: Vec<u8>,
+    supported_signature_algorithms: Vec<SignatureScheme>,
+    // DER encoded distinguished names of acceptable CAs
+    certificate_authorities: Vec<Vec<u8>>,
+}
+
+impl CertificateRequest {
+    pub fn new(
+        certificate_types: Vec<u8>,
+        supported_signature_algorithms: Vec<SignatureScheme>,
+        certificate_authorities: Vec<Vec<u8>>,
+    ) -> Self {
+        CertificateRequest {
+            handshake_type: HandshakeType::CertificateRequest,
+            certificate_types,
+            supported_signature_algorithms,
+            certificate_authorities,
+        }
+    }
+
+    pub fn read(buf: &[u8]) -> Result<Self, Error> {
+        decode_all(buf).ok_or(Error::Decode("CertificateRequest"))
+    }
+
+    pub fn certificate_types(&self) -> &[u8] {
+        &self.certificate_types
+    }
+
+    pub fn supported_signature_algorithms(&self) -> &[SignatureScheme] {
+        &self.supported_signature_algorithms
+    }
+
+    pub fn certificate_authorities(&self) -> &[Vec<u8>] {
+        &self.certificate_authorities
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        HandshakePayload::encode_message(self.handshake_type, &self.get_encoding())
+    }
+}
+
+impl Codec for CertificateRequest {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        encode_opaque(bytes, LengthPrefix::U8, &self.certificate_types);
+        encode_vec(
+            bytes,
+            LengthPrefix::U16,
+            &self.supported_signature_algorithms,
+        );
+        encode_opaque_vec(
+            bytes,
+            LengthPrefix::U16,
+            LengthPrefix::U16,
+            &self.certificate_authorities,
+        );
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(CertificateRequest {
+            handshake_type: HandshakeType::CertificateRequest,
+            certificate_types: read_opaque(r, LengthPrefix::U8)?.to_vec(),
+            supported_signature_algorithms: read_vec(r, LengthPrefix::U16)?,
+            certificate_authorities: read_opaque_vec(r, LengthPrefix::U16, LengthPrefix::U16)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_certificate_request() {
+        let buf = vec![
+            // certificate_types: rsa_sign, ecdsa_sign
+            0x02, 0x01, 0x40, //
+            // supported_signature_algorithms: rsa_pkcs1_sha256, ecdsa_secp256r1_sha256
+            0x00, 0x04, 0x04, 0x01, 0x04, 0x03, //
+            // certificate_authorities: one 3 byte name
+            0x00, 0x05, 0x00, 0x03, 0x30, 0x01, 0x00,
+        ];
+        let request = CertificateRequest::read(&buf).unwrap();
+        assert_eq!(request.certificate_types(), &[0x01, 0x40]);
+        assert_eq!(
+            request.supported_signature_algorithms(),
+            &[
+                SignatureScheme::rsa_pkcs1_sha256,
+                SignatureScheme::ecdsa_secp256r1_sha256
+            ]
+        );
+        assert_eq!(request.certificate_authorities(), &[vec![0x30, 0x01, 0x00]]);
+
+        // every truncation is a decode error, not a panic
+        for len in 0..buf.len() {
+            assert!(CertificateRequest::read(&buf[..len]).is_err());
+        }
+    }
+}
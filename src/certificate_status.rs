@@ -0,0 +1,62 @@
+use crate::codec::{decode_all, encode_opaque, read_opaque, Codec, LengthPrefix, Reader};
+use crate::enums::HandshakeType;
+use crate::error::Error;
+use crate::handshake::HandshakePayload;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#8--Certificate-Status-Request
+// struct {
+//     CertificateStatusType status_type;
+//     select (status_type) {
+//         case ocsp: OCSPResponse;
+//     } response;
+// } CertificateStatus;
+//
+// opaque OCSPResponse<1..2^24-1>;
+const STATUS_TYPE_OCSP: u8 = 0x01;
+
+#[derive(Debug)]
+pub struct CertificateStatus {
+    handshake_type: HandshakeType,
+    // DER encoded OCSPResponse
+    ocsp_response: Vec<u8>,
+}
+
+impl CertificateStatus {
+    pub fn new(ocsp_response: Vec<u8>) -> Self {
+        CertificateStatus {
+            handshake_type: HandshakeType::CertificateStatus,
+            ocsp_response,
+        }
+    }
+
+    // DecodeError if status_type is not ocsp or the length does not match the body
+    pub fn read(buf: &[u8]) -> Result<Self, Error> {
+        decode_all(buf).ok_or(Error::Decode("CertificateStatus"))
+    }
+
+    pub fn ocsp_response(&self) -> &[u8] {
+        &self.ocsp_response
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        HandshakePayload::encode_message(self.handshake_type, &self.get_encoding())
+    }
+}
+
+impl Codec for CertificateStatus {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.push(STATUS_TYPE_OCSP);
+        encode_opaque(bytes, LengthPrefix::U24, &self.ocsp_response);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        if u8::decode(r)? != STATUS_TYPE_OCSP {
+            return None;
+        }
+        let ocsp_response = read_opaque(r, LengthPrefix::U24)?;
+        if ocsp_response.is_empty() {
+            return None;
+        }
+        Some(CertificateStatus::new(ocsp_response.to_vec()))
+    }
+}
@@ -0,0 +1,62 @@
+use crate::codec::{decode_all, encode_opaque, read_opaque, Codec, LengthPrefix, Reader};
+use crate::enums::{HandshakeType, SignatureScheme};
+use crate::error::Error;
+use crate::handshake::HandshakePayload;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-8--Certificate-Verify
+// struct {
+//      digitally-signed struct {
+//          opaque handshake_messages[handshake_messages_length];
+//      }
+// } CertificateVerify;
+//
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#4-7--Cryptographic-Attributes
+// struct {
+//     SignatureAndHashAlgorithm algorithm;
+//     opaque signature<0..2^16-1>;
+// } DigitallySigned;
+#[derive(Debug)]
+pub struct CertificateVerify {
+    handshake_type: HandshakeType,
+    algorithm: SignatureScheme,
+    signature: Vec<u8>,
+}
+
+impl CertificateVerify {
+    pub fn new(algorithm: SignatureScheme, signature: Vec<u8>) -> Self {
+        CertificateVerify {
+            handshake_type: HandshakeType::CertificateVerify,
+            algorithm,
+            signature,
+        }
+    }
+
+    pub fn read(buf: &[u8]) -> Result<Self, Error> {
+        decode_all(buf).ok_or(Error::Decode("CertificateVerify"))
+    }
+
+    pub fn algorithm(&self) -> SignatureScheme {
+        self.algorithm
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        HandshakePayload::encode_message(self.handshake_type, &self.get_encoding())
+    }
+}
+
+impl Codec for CertificateVerify {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        self.algorithm.encode_into(bytes);
+        encode_opaque(bytes, LengthPrefix::U16, &self.signature);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let algorithm = SignatureScheme::decode(r)?;
+        let signature = read_opaque(r, LengthPrefix::U16)?.to_vec();
+        Some(CertificateVerify::new(algorithm, signature))
+    }
+}
@@ -0,0 +1,723 @@
+use crate::codec::{Codec, Reader};
+use ring::{aead, digest, hmac};
+
+// Ref: https://www.iana.org/assignments/tls-parameters/tls-parameters.xhtml#tls-parameters-4
+// The TLS Cipher Suites registry, limited to the suites that can be negotiated in TLS 1.2.
+// TLS 1.3 suites (TLS_AES_128_GCM_SHA256 etc.), the GOST suites of RFC 9189 and unassigned
+// values are CipherSuite::Unknown.
+//
+// Each suite is named TLS_<key exchange>_WITH_<bulk cipher>_<MAC>. The table below spells out
+// the parts so that negotiation, key expansion and record protection read them from one place.
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#A-5--The-Cipher-Suite
+// How the pre_master_secret is agreed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyExchange {
+    Null,
+    // the client encrypts the pre_master_secret with the server's RSA key
+    Rsa,
+    // static Diffie-Hellman parameters in the server certificate
+    Dh,
+    Dhe,
+    Ecdh,
+    Ecdhe,
+    Psk,
+    DhePsk,
+    RsaPsk,
+    EcdhePsk,
+    Krb5,
+    Srp,
+    EcCpwd,
+}
+
+// How the server is authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authentication {
+    Anonymous,
+    Rsa,
+    Dss,
+    Ecdsa,
+    Psk,
+    Krb5,
+    Srp,
+    Password,
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#6-2-3--Record-Payload-Protection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherType {
+    Stream,
+    Block,
+    Aead,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkCipher {
+    Null,
+    Rc4_40,
+    Rc4_128,
+    Rc2Cbc40,
+    IdeaCbc,
+    Des40Cbc,
+    DesCbc,
+    TripleDesEdeCbc,
+    Aes128Cbc,
+    Aes256Cbc,
+    Camellia128Cbc,
+    Camellia256Cbc,
+    SeedCbc,
+    Aria128Cbc,
+    Aria256Cbc,
+    Aes128Gcm,
+    Aes256Gcm,
+    Camellia128Gcm,
+    Camellia256Gcm,
+    Aria128Gcm,
+    Aria256Gcm,
+    Aes128Ccm,
+    Aes256Ccm,
+    Aes128Ccm8,
+    Aes256Ccm8,
+    ChaCha20Poly1305,
+}
+
+impl BulkCipher {
+    pub fn cipher_type(self) -> CipherType {
+        match self {
+            BulkCipher::Null | BulkCipher::Rc4_40 | BulkCipher::Rc4_128 => CipherType::Stream,
+            BulkCipher::Rc2Cbc40
+            | BulkCipher::IdeaCbc
+            | BulkCipher::Des40Cbc
+            | BulkCipher::DesCbc
+            | BulkCipher::TripleDesEdeCbc
+            | BulkCipher::Aes128Cbc
+            | BulkCipher::Aes256Cbc
+            | BulkCipher::Camellia128Cbc
+            | BulkCipher::Camellia256Cbc
+            | BulkCipher::SeedCbc
+            | BulkCipher::Aria128Cbc
+            | BulkCipher::Aria256Cbc => CipherType::Block,
+            _ => CipherType::Aead,
+        }
+    }
+
+    // enc_key_length. Export ciphers derive their key from 5 bytes of key material.
+    pub fn key_len(self) -> usize {
+        match self {
+            BulkCipher::Null => 0,
+            BulkCipher::Rc4_40 | BulkCipher::Rc2Cbc40 | BulkCipher::Des40Cbc => 5,
+            BulkCipher::DesCbc => 8,
+            BulkCipher::TripleDesEdeCbc => 24,
+            BulkCipher::Aes256Cbc
+            | BulkCipher::Camellia256Cbc
+            | BulkCipher::Aria256Cbc
+            | BulkCipher::Aes256Gcm
+            | BulkCipher::Camellia256Gcm
+            | BulkCipher::Aria256Gcm
+            | BulkCipher::Aes256Ccm
+            | BulkCipher::Aes256Ccm8
+            | BulkCipher::ChaCha20Poly1305 => 32,
+            _ => 16,
+        }
+    }
+
+    // fixed_iv_length: the part of the nonce taken from the key block.
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5288.html#3--AES-GCM-Cipher-Suites
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc7905.html#2--ChaCha20-Cipher-Suites
+    pub fn fixed_iv_len(self) -> usize {
+        match self.cipher_type() {
+            CipherType::Stream | CipherType::Block => 0,
+            CipherType::Aead if self == BulkCipher::ChaCha20Poly1305 => 12,
+            CipherType::Aead => 4,
+        }
+    }
+
+    // record_iv_length: the IV or nonce_explicit sent in each record
+    pub fn record_iv_len(self) -> usize {
+        match self {
+            BulkCipher::Null | BulkCipher::Rc4_40 | BulkCipher::Rc4_128 => 0,
+            BulkCipher::ChaCha20Poly1305 => 0,
+            BulkCipher::Rc2Cbc40
+            | BulkCipher::IdeaCbc
+            | BulkCipher::Des40Cbc
+            | BulkCipher::DesCbc
+            | BulkCipher::TripleDesEdeCbc => 8,
+            BulkCipher::Aes128Cbc
+            | BulkCipher::Aes256Cbc
+            | BulkCipher::Camellia128Cbc
+            | BulkCipher::Camellia256Cbc
+            | BulkCipher::SeedCbc
+            | BulkCipher::Aria128Cbc
+            | BulkCipher::Aria256Cbc => 16,
+            _ => 8,
+        }
+    }
+
+    // the authentication tag of an AEAD cipher, 0 otherwise
+    pub fn tag_len(self) -> usize {
+        match self.cipher_type() {
+            CipherType::Stream | CipherType::Block => 0,
+            CipherType::Aead if matches!(self, BulkCipher::Aes128Ccm8 | BulkCipher::Aes256Ccm8) => {
+                8
+            }
+            CipherType::Aead => 16,
+        }
+    }
+
+    // the ring implementation, None if ring does not provide the cipher
+    pub fn aead_algorithm(self) -> Option<&'static aead::Algorithm> {
+        match self {
+            BulkCipher::Aes128Gcm => Some(&aead::AES_128_GCM),
+            BulkCipher::Aes256Gcm => Some(&aead::AES_256_GCM),
+            BulkCipher::ChaCha20Poly1305 => Some(&aead::CHACHA20_POLY1305),
+            _ => None,
+        }
+    }
+}
+
+// The record MAC. AEAD ciphers authenticate the record themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacAlgorithm {
+    Null,
+    Md5,
+    Sha1,
+    Sha256,
+    Sha384,
+    Aead,
+}
+
+impl MacAlgorithm {
+    // mac_length, which is also mac_key_length
+    pub fn mac_len(self) -> usize {
+        match self {
+            MacAlgorithm::Null | MacAlgorithm::Aead => 0,
+            MacAlgorithm::Md5 => 16,
+            MacAlgorithm::Sha1 => 20,
+            MacAlgorithm::Sha256 => 32,
+            MacAlgorithm::Sha384 => 48,
+        }
+    }
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#5--HMAC-and-the-Pseudorandom-Function
+// The hash of the PRF and of the Finished handshake hash. SHA-256 unless the suite says otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrfHash {
+    Sha256,
+    Sha384,
+}
+
+impl PrfHash {
+    pub fn hmac_algorithm(self) -> hmac::Algorithm {
+        match self {
+            PrfHash::Sha256 => hmac::HMAC_SHA256,
+            PrfHash::Sha384 => hmac::HMAC_SHA384,
+        }
+    }
+
+    pub fn digest_algorithm(self) -> &'static digest::Algorithm {
+        match self {
+            PrfHash::Sha256 => &digest::SHA256,
+            PrfHash::Sha384 => &digest::SHA384,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CipherSuiteParams {
+    pub suite: CipherSuite,
+    pub key_exchange: KeyExchange,
+    pub authentication: Authentication,
+    pub bulk_cipher: BulkCipher,
+    pub mac: MacAlgorithm,
+    pub prf_hash: PrfHash,
+    // the crate can negotiate the suite
+    pub implemented: bool,
+}
+
+impl CipherSuiteParams {
+    pub fn key_len(&self) -> usize {
+        self.bulk_cipher.key_len()
+    }
+
+    pub fn fixed_iv_len(&self) -> usize {
+        self.bulk_cipher.fixed_iv_len()
+    }
+
+    pub fn record_iv_len(&self) -> usize {
+        self.bulk_cipher.record_iv_len()
+    }
+
+    pub fn mac_len(&self) -> usize {
+        self.mac.mac_len()
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#6-3--Key-Calculation
+    // client_write_MAC_key[SecurityParameters.mac_key_length]
+    // server_write_MAC_key[SecurityParameters.mac_key_length]
+    // client_write_key[SecurityParameters.enc_key_length]
+    // server_write_key[SecurityParameters.enc_key_length]
+    // client_write_IV[SecurityParameters.fixed_iv_length]
+    // server_write_IV[SecurityParameters.fixed_iv_length]
+    pub fn key_block_len(&self) -> usize {
+        2 * (self.mac_len() + self.key_len() + self.fixed_iv_len())
+    }
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5746.html#3-3--Renegotiation-Protection-Request-Signaling-Cipher-Suite-Value
+const TLS_EMPTY_RENEGOTIATION_INFO_SCSV: u16 = 0x00ff;
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc7507.html#2--Protocol-Values
+const TLS_FALLBACK_SCSV: u16 = 0x5600;
+
+macro_rules! cipher_suites {
+    ($($value:literal => $name:ident: $key_exchange:ident, $authentication:ident, $bulk_cipher:ident, $mac:ident, $prf_hash:ident, $implemented:literal;)*) => {
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum CipherSuite {
+            $($name,)*
+            // signaling values, they do not name a suite
+            TLS_EMPTY_RENEGOTIATION_INFO_SCSV,
+            TLS_FALLBACK_SCSV,
+            Unknown(u16),
+        }
+
+        impl CipherSuite {
+            pub fn get_u16(self) -> u16 {
+                match self {
+                    $(CipherSuite::$name => $value,)*
+                    CipherSuite::TLS_EMPTY_RENEGOTIATION_INFO_SCSV => TLS_EMPTY_RENEGOTIATION_INFO_SCSV,
+                    CipherSuite::TLS_FALLBACK_SCSV => TLS_FALLBACK_SCSV,
+                    CipherSuite::Unknown(value) => value,
+                }
+            }
+
+            pub fn from_u16(value: u16) -> Self {
+                match value {
+                    $($value => CipherSuite::$name,)*
+                    TLS_EMPTY_RENEGOTIATION_INFO_SCSV => CipherSuite::TLS_EMPTY_RENEGOTIATION_INFO_SCSV,
+                    TLS_FALLBACK_SCSV => CipherSuite::TLS_FALLBACK_SCSV,
+                    _ => CipherSuite::Unknown(value),
+                }
+            }
+        }
+
+        static REGISTRY: &[CipherSuiteParams] = &[
+            $(CipherSuiteParams {
+                suite: CipherSuite::$name,
+                key_exchange: KeyExchange::$key_exchange,
+                authentication: Authentication::$authentication,
+                bulk_cipher: BulkCipher::$bulk_cipher,
+                mac: MacAlgorithm::$mac,
+                prf_hash: PrfHash::$prf_hash,
+                implemented: $implemented,
+            },)*
+        ];
+    };
+}
+
+// value => name: key exchange, authentication, bulk cipher, MAC, PRF hash, implemented;
+cipher_suites! {
+    0x0000 => TLS_NULL_WITH_NULL_NULL: Null, Anonymous, Null, Null, Sha256, false;
+    0x0001 => TLS_RSA_WITH_NULL_MD5: Rsa, Rsa, Null, Md5, Sha256, false;
+    0x0002 => TLS_RSA_WITH_NULL_SHA: Rsa, Rsa, Null, Sha1, Sha256, false;
+    0x0003 => TLS_RSA_EXPORT_WITH_RC4_40_MD5: Rsa, Rsa, Rc4_40, Md5, Sha256, false;
+    0x0004 => TLS_RSA_WITH_RC4_128_MD5: Rsa, Rsa, Rc4_128, Md5, Sha256, false;
+    0x0005 => TLS_RSA_WITH_RC4_128_SHA: Rsa, Rsa, Rc4_128, Sha1, Sha256, false;
+    0x0006 => TLS_RSA_EXPORT_WITH_RC2_CBC_40_MD5: Rsa, Rsa, Rc2Cbc40, Md5, Sha256, false;
+    0x0007 => TLS_RSA_WITH_IDEA_CBC_SHA: Rsa, Rsa, IdeaCbc, Sha1, Sha256, false;
+    0x0008 => TLS_RSA_EXPORT_WITH_DES40_CBC_SHA: Rsa, Rsa, Des40Cbc, Sha1, Sha256, false;
+    0x0009 => TLS_RSA_WITH_DES_CBC_SHA: Rsa, Rsa, DesCbc, Sha1, Sha256, false;
+    0x000a => TLS_RSA_WITH_3DES_EDE_CBC_SHA: Rsa, Rsa, TripleDesEdeCbc, Sha1, Sha256, false;
+    0x000b => TLS_DH_DSS_EXPORT_WITH_DES40_CBC_SHA: Dh, Dss, Des40Cbc, Sha1, Sha256, false;
+    0x000c => TLS_DH_DSS_WITH_DES_CBC_SHA: Dh, Dss, DesCbc, Sha1, Sha256, false;
+    0x000d => TLS_DH_DSS_WITH_3DES_EDE_CBC_SHA: Dh, Dss, TripleDesEdeCbc, Sha1, Sha256, false;
+    0x000e => TLS_DH_RSA_EXPORT_WITH_DES40_CBC_SHA: Dh, Rsa, Des40Cbc, Sha1, Sha256, false;
+    0x000f => TLS_DH_RSA_WITH_DES_CBC_SHA: Dh, Rsa, DesCbc, Sha1, Sha256, false;
+    0x0010 => TLS_DH_RSA_WITH_3DES_EDE_CBC_SHA: Dh, Rsa, TripleDesEdeCbc, Sha1, Sha256, false;
+    0x0011 => TLS_DHE_DSS_EXPORT_WITH_DES40_CBC_SHA: Dhe, Dss, Des40Cbc, Sha1, Sha256, false;
+    0x0012 => TLS_DHE_DSS_WITH_DES_CBC_SHA: Dhe, Dss, DesCbc, Sha1, Sha256, false;
+    0x0013 => TLS_DHE_DSS_WITH_3DES_EDE_CBC_SHA: Dhe, Dss, TripleDesEdeCbc, Sha1, Sha256, false;
+    0x0014 => TLS_DHE_RSA_EXPORT_WITH_DES40_CBC_SHA: Dhe, Rsa, Des40Cbc, Sha1, Sha256, false;
+    0x0015 => TLS_DHE_RSA_WITH_DES_CBC_SHA: Dhe, Rsa, DesCbc, Sha1, Sha256, false;
+    0x0016 => TLS_DHE_RSA_WITH_3DES_EDE_CBC_SHA: Dhe, Rsa, TripleDesEdeCbc, Sha1, Sha256, false;
+    0x0017 => TLS_DH_anon_EXPORT_WITH_RC4_40_MD5: Dhe, Anonymous, Rc4_40, Md5, Sha256, false;
+    0x0018 => TLS_DH_anon_WITH_RC4_128_MD5: Dhe, Anonymous, Rc4_128, Md5, Sha256, false;
+    0x0019 => TLS_DH_anon_EXPORT_WITH_DES40_CBC_SHA: Dhe, Anonymous, Des40Cbc, Sha1, Sha256, false;
+    0x001a => TLS_DH_anon_WITH_DES_CBC_SHA: Dhe, Anonymous, DesCbc, Sha1, Sha256, false;
+    0x001b => TLS_DH_anon_WITH_3DES_EDE_CBC_SHA: Dhe, Anonymous, TripleDesEdeCbc, Sha1, Sha256, false;
+    0x001e => TLS_KRB5_WITH_DES_CBC_SHA: Krb5, Krb5, DesCbc, Sha1, Sha256, false;
+    0x001f => TLS_KRB5_WITH_3DES_EDE_CBC_SHA: Krb5, Krb5, TripleDesEdeCbc, Sha1, Sha256, false;
+    0x0020 => TLS_KRB5_WITH_RC4_128_SHA: Krb5, Krb5, Rc4_128, Sha1, Sha256, false;
+    0x0021 => TLS_KRB5_WITH_IDEA_CBC_SHA: Krb5, Krb5, IdeaCbc, Sha1, Sha256, false;
+    0x0022 => TLS_KRB5_WITH_DES_CBC_MD5: Krb5, Krb5, DesCbc, Md5, Sha256, false;
+    0x0023 => TLS_KRB5_WITH_3DES_EDE_CBC_MD5: Krb5, Krb5, TripleDesEdeCbc, Md5, Sha256, false;
+    0x0024 => TLS_KRB5_WITH_RC4_128_MD5: Krb5, Krb5, Rc4_128, Md5, Sha256, false;
+    0x0025 => TLS_KRB5_WITH_IDEA_CBC_MD5: Krb5, Krb5, IdeaCbc, Md5, Sha256, false;
+    0x0026 => TLS_KRB5_EXPORT_WITH_DES_CBC_40_SHA: Krb5, Krb5, Des40Cbc, Sha1, Sha256, false;
+    0x0027 => TLS_KRB5_EXPORT_WITH_RC2_CBC_40_SHA: Krb5, Krb5, Rc2Cbc40, Sha1, Sha256, false;
+    0x0028 => TLS_KRB5_EXPORT_WITH_RC4_40_SHA: Krb5, Krb5, Rc4_40, Sha1, Sha256, false;
+    0x0029 => TLS_KRB5_EXPORT_WITH_DES_CBC_40_MD5: Krb5, Krb5, Des40Cbc, Md5, Sha256, false;
+    0x002a => TLS_KRB5_EXPORT_WITH_RC2_CBC_40_MD5: Krb5, Krb5, Rc2Cbc40, Md5, Sha256, false;
+    0x002b => TLS_KRB5_EXPORT_WITH_RC4_40_MD5: Krb5, Krb5, Rc4_40, Md5, Sha256, false;
+    0x002c => TLS_PSK_WITH_NULL_SHA: Psk, Psk, Null, Sha1, Sha256, false;
+    0x002d => TLS_DHE_PSK_WITH_NULL_SHA: DhePsk, Psk, Null, Sha1, Sha256, false;
+    0x002e => TLS_RSA_PSK_WITH_NULL_SHA: RsaPsk, Rsa, Null, Sha1, Sha256, false;
+    0x002f => TLS_RSA_WITH_AES_128_CBC_SHA: Rsa, Rsa, Aes128Cbc, Sha1, Sha256, false;
+    0x0030 => TLS_DH_DSS_WITH_AES_128_CBC_SHA: Dh, Dss, Aes128Cbc, Sha1, Sha256, false;
+    0x0031 => TLS_DH_RSA_WITH_AES_128_CBC_SHA: Dh, Rsa, Aes128Cbc, Sha1, Sha256, false;
+    0x0032 => TLS_DHE_DSS_WITH_AES_128_CBC_SHA: Dhe, Dss, Aes128Cbc, Sha1, Sha256, false;
+    0x0033 => TLS_DHE_RSA_WITH_AES_128_CBC_SHA: Dhe, Rsa, Aes128Cbc, Sha1, Sha256, false;
+    0x0034 => TLS_DH_anon_WITH_AES_128_CBC_SHA: Dhe, Anonymous, Aes128Cbc, Sha1, Sha256, false;
+    0x0035 => TLS_RSA_WITH_AES_256_CBC_SHA: Rsa, Rsa, Aes256Cbc, Sha1, Sha256, false;
+    0x0036 => TLS_DH_DSS_WITH_AES_256_CBC_SHA: Dh, Dss, Aes256Cbc, Sha1, Sha256, false;
+    0x0037 => TLS_DH_RSA_WITH_AES_256_CBC_SHA: Dh, Rsa, Aes256Cbc, Sha1, Sha256, false;
+    0x0038 => TLS_DHE_DSS_WITH_AES_256_CBC_SHA: Dhe, Dss, Aes256Cbc, Sha1, Sha256, false;
+    0x0039 => TLS_DHE_RSA_WITH_AES_256_CBC_SHA: Dhe, Rsa, Aes256Cbc, Sha1, Sha256, false;
+    0x003a => TLS_DH_anon_WITH_AES_256_CBC_SHA: Dhe, Anonymous, Aes256Cbc, Sha1, Sha256, false;
+    0x003b => TLS_RSA_WITH_NULL_SHA256: Rsa, Rsa, Null, Sha256, Sha256, false;
+    0x003c => TLS_RSA_WITH_AES_128_CBC_SHA256: Rsa, Rsa, Aes128Cbc, Sha256, Sha256, false;
+    0x003d => TLS_RSA_WITH_AES_256_CBC_SHA256: Rsa, Rsa, Aes256Cbc, Sha256, Sha256, false;
+    0x003e => TLS_DH_DSS_WITH_AES_128_CBC_SHA256: Dh, Dss, Aes128Cbc, Sha256, Sha256, false;
+    0x003f => TLS_DH_RSA_WITH_AES_128_CBC_SHA256: Dh, Rsa, Aes128Cbc, Sha256, Sha256, false;
+    0x0040 => TLS_DHE_DSS_WITH_AES_128_CBC_SHA256: Dhe, Dss, Aes128Cbc, Sha256, Sha256, false;
+    0x0041 => TLS_RSA_WITH_CAMELLIA_128_CBC_SHA: Rsa, Rsa, Camellia128Cbc, Sha1, Sha256, false;
+    0x0042 => TLS_DH_DSS_WITH_CAMELLIA_128_CBC_SHA: Dh, Dss, Camellia128Cbc, Sha1, Sha256, false;
+    0x0043 => TLS_DH_RSA_WITH_CAMELLIA_128_CBC_SHA: Dh, Rsa, Camellia128Cbc, Sha1, Sha256, false;
+    0x0044 => TLS_DHE_DSS_WITH_CAMELLIA_128_CBC_SHA: Dhe, Dss, Camellia128Cbc, Sha1, Sha256, false;
+    0x0045 => TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA: Dhe, Rsa, Camellia128Cbc, Sha1, Sha256, false;
+    0x0046 => TLS_DH_anon_WITH_CAMELLIA_128_CBC_SHA: Dhe, Anonymous, Camellia128Cbc, Sha1, Sha256, false;
+    0x0067 => TLS_DHE_RSA_WITH_AES_128_CBC_SHA256: Dhe, Rsa, Aes128Cbc, Sha256, Sha256, false;
+    0x0068 => TLS_DH_DSS_WITH_AES_256_CBC_SHA256: Dh, Dss, Aes256Cbc, Sha256, Sha256, false;
+    0x0069 => TLS_DH_RSA_WITH_AES_256_CBC_SHA256: Dh, Rsa, Aes256Cbc, Sha256, Sha256, false;
+    0x006a => TLS_DHE_DSS_WITH_AES_256_CBC_SHA256: Dhe, Dss, Aes256Cbc, Sha256, Sha256, false;
+    0x006b => TLS_DHE_RSA_WITH_AES_256_CBC_SHA256: Dhe, Rsa, Aes256Cbc, Sha256, Sha256, false;
+    0x006c => TLS_DH_anon_WITH_AES_128_CBC_SHA256: Dhe, Anonymous, Aes128Cbc, Sha256, Sha256, false;
+    0x006d => TLS_DH_anon_WITH_AES_256_CBC_SHA256: Dhe, Anonymous, Aes256Cbc, Sha256, Sha256, false;
+    0x0084 => TLS_RSA_WITH_CAMELLIA_256_CBC_SHA: Rsa, Rsa, Camellia256Cbc, Sha1, Sha256, false;
+    0x0085 => TLS_DH_DSS_WITH_CAMELLIA_256_CBC_SHA: Dh, Dss, Camellia256Cbc, Sha1, Sha256, false;
+    0x0086 => TLS_DH_RSA_WITH_CAMELLIA_256_CBC_SHA: Dh, Rsa, Camellia256Cbc, Sha1, Sha256, false;
+    0x0087 => TLS_DHE_DSS_WITH_CAMELLIA_256_CBC_SHA: Dhe, Dss, Camellia256Cbc, Sha1, Sha256, false;
+    0x0088 => TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA: Dhe, Rsa, Camellia256Cbc, Sha1, Sha256, false;
+    0x0089 => TLS_DH_anon_WITH_CAMELLIA_256_CBC_SHA: Dhe, Anonymous, Camellia256Cbc, Sha1, Sha256, false;
+    0x008a => TLS_PSK_WITH_RC4_128_SHA: Psk, Psk, Rc4_128, Sha1, Sha256, false;
+    0x008b => TLS_PSK_WITH_3DES_EDE_CBC_SHA: Psk, Psk, TripleDesEdeCbc, Sha1, Sha256, false;
+    0x008c => TLS_PSK_WITH_AES_128_CBC_SHA: Psk, Psk, Aes128Cbc, Sha1, Sha256, false;
+    0x008d => TLS_PSK_WITH_AES_256_CBC_SHA: Psk, Psk, Aes256Cbc, Sha1, Sha256, false;
+    0x008e => TLS_DHE_PSK_WITH_RC4_128_SHA: DhePsk, Psk, Rc4_128, Sha1, Sha256, false;
+    0x008f => TLS_DHE_PSK_WITH_3DES_EDE_CBC_SHA: DhePsk, Psk, TripleDesEdeCbc, Sha1, Sha256, false;
+    0x0090 => TLS_DHE_PSK_WITH_AES_128_CBC_SHA: DhePsk, Psk, Aes128Cbc, Sha1, Sha256, false;
+    0x0091 => TLS_DHE_PSK_WITH_AES_256_CBC_SHA: DhePsk, Psk, Aes256Cbc, Sha1, Sha256, false;
+    0x0092 => TLS_RSA_PSK_WITH_RC4_128_SHA: RsaPsk, Rsa, Rc4_128, Sha1, Sha256, false;
+    0x0093 => TLS_RSA_PSK_WITH_3DES_EDE_CBC_SHA: RsaPsk, Rsa, TripleDesEdeCbc, Sha1, Sha256, false;
+    0x0094 => TLS_RSA_PSK_WITH_AES_128_CBC_SHA: RsaPsk, Rsa, Aes128Cbc, Sha1, Sha256, false;
+    0x0095 => TLS_RSA_PSK_WITH_AES_256_CBC_SHA: RsaPsk, Rsa, Aes256Cbc, Sha1, Sha256, false;
+    0x0096 => TLS_RSA_WITH_SEED_CBC_SHA: Rsa, Rsa, SeedCbc, Sha1, Sha256, false;
+    0x0097 => TLS_DH_DSS_WITH_SEED_CBC_SHA: Dh, Dss, SeedCbc, Sha1, Sha256, false;
+    0x0098 => TLS_DH_RSA_WITH_SEED_CBC_SHA: Dh, Rsa, SeedCbc, Sha1, Sha256, false;
+    0x0099 => TLS_DHE_DSS_WITH_SEED_CBC_SHA: Dhe, Dss, SeedCbc, Sha1, Sha256, false;
+    0x009a => TLS_DHE_RSA_WITH_SEED_CBC_SHA: Dhe, Rsa, SeedCbc, Sha1, Sha256, false;
+    0x009b => TLS_DH_anon_WITH_SEED_CBC_SHA: Dhe, Anonymous, SeedCbc, Sha1, Sha256, false;
+    0x009c => TLS_RSA_WITH_AES_128_GCM_SHA256: Rsa, Rsa, Aes128Gcm, Aead, Sha256, false;
+    0x009d => TLS_RSA_WITH_AES_256_GCM_SHA384: Rsa, Rsa, Aes256Gcm, Aead, Sha384, false;
+    0x009e => TLS_DHE_RSA_WITH_AES_128_GCM_SHA256: Dhe, Rsa, Aes128Gcm, Aead, Sha256, false;
+    0x009f => TLS_DHE_RSA_WITH_AES_256_GCM_SHA384: Dhe, Rsa, Aes256Gcm, Aead, Sha384, false;
+    0x00a0 => TLS_DH_RSA_WITH_AES_128_GCM_SHA256: Dh, Rsa, Aes128Gcm, Aead, Sha256, false;
+    0x00a1 => TLS_DH_RSA_WITH_AES_256_GCM_SHA384: Dh, Rsa, Aes256Gcm, Aead, Sha384, false;
+    0x00a2 => TLS_DHE_DSS_WITH_AES_128_GCM_SHA256: Dhe, Dss, Aes128Gcm, Aead, Sha256, false;
+    0x00a3 => TLS_DHE_DSS_WITH_AES_256_GCM_SHA384: Dhe, Dss, Aes256Gcm, Aead, Sha384, false;
+    0x00a4 => TLS_DH_DSS_WITH_AES_128_GCM_SHA256: Dh, Dss, Aes128Gcm, Aead, Sha256, false;
+    0x00a5 => TLS_DH_DSS_WITH_AES_256_GCM_SHA384: Dh, Dss, Aes256Gcm, Aead, Sha384, false;
+    0x00a6 => TLS_DH_anon_WITH_AES_128_GCM_SHA256: Dhe, Anonymous, Aes128Gcm, Aead, Sha256, false;
+    0x00a7 => TLS_DH_anon_WITH_AES_256_GCM_SHA384: Dhe, Anonymous, Aes256Gcm, Aead, Sha384, false;
+    0x00a8 => TLS_PSK_WITH_AES_128_GCM_SHA256: Psk, Psk, Aes128Gcm, Aead, Sha256, false;
+    0x00a9 => TLS_PSK_WITH_AES_256_GCM_SHA384: Psk, Psk, Aes256Gcm, Aead, Sha384, false;
+    0x00aa => TLS_DHE_PSK_WITH_AES_128_GCM_SHA256: DhePsk, Psk, Aes128Gcm, Aead, Sha256, false;
+    0x00ab => TLS_DHE_PSK_WITH_AES_256_GCM_SHA384: DhePsk, Psk, Aes256Gcm, Aead, Sha384, false;
+    0x00ac => TLS_RSA_PSK_WITH_AES_128_GCM_SHA256: RsaPsk, Rsa, Aes128Gcm, Aead, Sha256, false;
+    0x00ad => TLS_RSA_PSK_WITH_AES_256_GCM_SHA384: RsaPsk, Rsa, Aes256Gcm, Aead, Sha384, false;
+    0x00ae => TLS_PSK_WITH_AES_128_CBC_SHA256: Psk, Psk, Aes128Cbc, Sha256, Sha256, false;
+    0x00af => TLS_PSK_WITH_AES_256_CBC_SHA384: Psk, Psk, Aes256Cbc, Sha384, Sha384, false;
+    0x00b0 => TLS_PSK_WITH_NULL_SHA256: Psk, Psk, Null, Sha256, Sha256, false;
+    0x00b1 => TLS_PSK_WITH_NULL_SHA384: Psk, Psk, Null, Sha384, Sha384, false;
+    0x00b2 => TLS_DHE_PSK_WITH_AES_128_CBC_SHA256: DhePsk, Psk, Aes128Cbc, Sha256, Sha256, false;
+    0x00b3 => TLS_DHE_PSK_WITH_AES_256_CBC_SHA384: DhePsk, Psk, Aes256Cbc, Sha384, Sha384, false;
+    0x00b4 => TLS_DHE_PSK_WITH_NULL_SHA256: DhePsk, Psk, Null, Sha256, Sha256, false;
+    0x00b5 => TLS_DHE_PSK_WITH_NULL_SHA384: DhePsk, Psk, Null, Sha384, Sha384, false;
+    0x00b6 => TLS_RSA_PSK_WITH_AES_128_CBC_SHA256: RsaPsk, Rsa, Aes128Cbc, Sha256, Sha256, false;
+    0x00b7 => TLS_RSA_PSK_WITH_AES_256_CBC_SHA384: RsaPsk, Rsa, Aes256Cbc, Sha384, Sha384, false;
+    0x00b8 => TLS_RSA_PSK_WITH_NULL_SHA256: RsaPsk, Rsa, Null, Sha256, Sha256, false;
+    0x00b9 => TLS_RSA_PSK_WITH_NULL_SHA384: RsaPsk, Rsa, Null, Sha384, Sha384, false;
+    0x00ba => TLS_RSA_WITH_CAMELLIA_128_CBC_SHA256: Rsa, Rsa, Camellia128Cbc, Sha256, Sha256, false;
+    0x00bb => TLS_DH_DSS_WITH_CAMELLIA_128_CBC_SHA256: Dh, Dss, Camellia128Cbc, Sha256, Sha256, false;
+    0x00bc => TLS_DH_RSA_WITH_CAMELLIA_128_CBC_SHA256: Dh, Rsa, Camellia128Cbc, Sha256, Sha256, false;
+    0x00bd => TLS_DHE_DSS_WITH_CAMELLIA_128_CBC_SHA256: Dhe, Dss, Camellia128Cbc, Sha256, Sha256, false;
+    0x00be => TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA256: Dhe, Rsa, Camellia128Cbc, Sha256, Sha256, false;
+    0x00bf => TLS_DH_anon_WITH_CAMELLIA_128_CBC_SHA256: Dhe, Anonymous, Camellia128Cbc, Sha256, Sha256, false;
+    0x00c0 => TLS_RSA_WITH_CAMELLIA_256_CBC_SHA256: Rsa, Rsa, Camellia256Cbc, Sha256, Sha256, false;
+    0x00c1 => TLS_DH_DSS_WITH_CAMELLIA_256_CBC_SHA256: Dh, Dss, Camellia256Cbc, Sha256, Sha256, false;
+    0x00c2 => TLS_DH_RSA_WITH_CAMELLIA_256_CBC_SHA256: Dh, Rsa, Camellia256Cbc, Sha256, Sha256, false;
+    0x00c3 => TLS_DHE_DSS_WITH_CAMELLIA_256_CBC_SHA256: Dhe, Dss, Camellia256Cbc, Sha256, Sha256, false;
+    0x00c4 => TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA256: Dhe, Rsa, Camellia256Cbc, Sha256, Sha256, false;
+    0x00c5 => TLS_DH_anon_WITH_CAMELLIA_256_CBC_SHA256: Dhe, Anonymous, Camellia256Cbc, Sha256, Sha256, false;
+    0xc001 => TLS_ECDH_ECDSA_WITH_NULL_SHA: Ecdh, Ecdsa, Null, Sha1, Sha256, false;
+    0xc002 => TLS_ECDH_ECDSA_WITH_RC4_128_SHA: Ecdh, Ecdsa, Rc4_128, Sha1, Sha256, false;
+    0xc003 => TLS_ECDH_ECDSA_WITH_3DES_EDE_CBC_SHA: Ecdh, Ecdsa, TripleDesEdeCbc, Sha1, Sha256, false;
+    0xc004 => TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA: Ecdh, Ecdsa, Aes128Cbc, Sha1, Sha256, false;
+    0xc005 => TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA: Ecdh, Ecdsa, Aes256Cbc, Sha1, Sha256, false;
+    0xc006 => TLS_ECDHE_ECDSA_WITH_NULL_SHA: Ecdhe, Ecdsa, Null, Sha1, Sha256, false;
+    0xc007 => TLS_ECDHE_ECDSA_WITH_RC4_128_SHA: Ecdhe, Ecdsa, Rc4_128, Sha1, Sha256, false;
+    0xc008 => TLS_ECDHE_ECDSA_WITH_3DES_EDE_CBC_SHA: Ecdhe, Ecdsa, TripleDesEdeCbc, Sha1, Sha256, false;
+    0xc009 => TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA: Ecdhe, Ecdsa, Aes128Cbc, Sha1, Sha256, false;
+    0xc00a => TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA: Ecdhe, Ecdsa, Aes256Cbc, Sha1, Sha256, false;
+    0xc00b => TLS_ECDH_RSA_WITH_NULL_SHA: Ecdh, Rsa, Null, Sha1, Sha256, false;
+    0xc00c => TLS_ECDH_RSA_WITH_RC4_128_SHA: Ecdh, Rsa, Rc4_128, Sha1, Sha256, false;
+    0xc00d => TLS_ECDH_RSA_WITH_3DES_EDE_CBC_SHA: Ecdh, Rsa, TripleDesEdeCbc, Sha1, Sha256, false;
+    0xc00e => TLS_ECDH_RSA_WITH_AES_128_CBC_SHA: Ecdh, Rsa, Aes128Cbc, Sha1, Sha256, false;
+    0xc00f => TLS_ECDH_RSA_WITH_AES_256_CBC_SHA: Ecdh, Rsa, Aes256Cbc, Sha1, Sha256, false;
+    0xc010 => TLS_ECDHE_RSA_WITH_NULL_SHA: Ecdhe, Rsa, Null, Sha1, Sha256, false;
+    0xc011 => TLS_ECDHE_RSA_WITH_RC4_128_SHA: Ecdhe, Rsa, Rc4_128, Sha1, Sha256, false;
+    0xc012 => TLS_ECDHE_RSA_WITH_3DES_EDE_CBC_SHA: Ecdhe, Rsa, TripleDesEdeCbc, Sha1, Sha256, false;
+    0xc013 => TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA: Ecdhe, Rsa, Aes128Cbc, Sha1, Sha256, false;
+    0xc014 => TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA: Ecdhe, Rsa, Aes256Cbc, Sha1, Sha256, false;
+    0xc015 => TLS_ECDH_anon_WITH_NULL_SHA: Ecdhe, Anonymous, Null, Sha1, Sha256, false;
+    0xc016 => TLS_ECDH_anon_WITH_RC4_128_SHA: Ecdhe, Anonymous, Rc4_128, Sha1, Sha256, false;
+    0xc017 => TLS_ECDH_anon_WITH_3DES_EDE_CBC_SHA: Ecdhe, Anonymous, TripleDesEdeCbc, Sha1, Sha256, false;
+    0xc018 => TLS_ECDH_anon_WITH_AES_128_CBC_SHA: Ecdhe, Anonymous, Aes128Cbc, Sha1, Sha256, false;
+    0xc019 => TLS_ECDH_anon_WITH_AES_256_CBC_SHA: Ecdhe, Anonymous, Aes256Cbc, Sha1, Sha256, false;
+    0xc01a => TLS_SRP_SHA_WITH_3DES_EDE_CBC_SHA: Srp, Srp, TripleDesEdeCbc, Sha1, Sha256, false;
+    0xc01b => TLS_SRP_SHA_RSA_WITH_3DES_EDE_CBC_SHA: Srp, Rsa, TripleDesEdeCbc, Sha1, Sha256, false;
+    0xc01c => TLS_SRP_SHA_DSS_WITH_3DES_EDE_CBC_SHA: Srp, Dss, TripleDesEdeCbc, Sha1, Sha256, false;
+    0xc01d => TLS_SRP_SHA_WITH_AES_128_CBC_SHA: Srp, Srp, Aes128Cbc, Sha1, Sha256, false;
+    0xc01e => TLS_SRP_SHA_RSA_WITH_AES_128_CBC_SHA: Srp, Rsa, Aes128Cbc, Sha1, Sha256, false;
+    0xc01f => TLS_SRP_SHA_DSS_WITH_AES_128_CBC_SHA: Srp, Dss, Aes128Cbc, Sha1, Sha256, false;
+    0xc020 => TLS_SRP_SHA_WITH_AES_256_CBC_SHA: Srp, Srp, Aes256Cbc, Sha1, Sha256, false;
+    0xc021 => TLS_SRP_SHA_RSA_WITH_AES_256_CBC_SHA: Srp, Rsa, Aes256Cbc, Sha1, Sha256, false;
+    0xc022 => TLS_SRP_SHA_DSS_WITH_AES_256_CBC_SHA: Srp, Dss, Aes256Cbc, Sha1, Sha256, false;
+    0xc023 => TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256: Ecdhe, Ecdsa, Aes128Cbc, Sha256, Sha256, false;
+    0xc024 => TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384: Ecdhe, Ecdsa, Aes256Cbc, Sha384, Sha384, false;
+    0xc025 => TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA256: Ecdh, Ecdsa, Aes128Cbc, Sha256, Sha256, false;
+    0xc026 => TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA384: Ecdh, Ecdsa, Aes256Cbc, Sha384, Sha384, false;
+    0xc027 => TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256: Ecdhe, Rsa, Aes128Cbc, Sha256, Sha256, false;
+    0xc028 => TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384: Ecdhe, Rsa, Aes256Cbc, Sha384, Sha384, false;
+    0xc029 => TLS_ECDH_RSA_WITH_AES_128_CBC_SHA256: Ecdh, Rsa, Aes128Cbc, Sha256, Sha256, false;
+    0xc02a => TLS_ECDH_RSA_WITH_AES_256_CBC_SHA384: Ecdh, Rsa, Aes256Cbc, Sha384, Sha384, false;
+    0xc02b => TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256: Ecdhe, Ecdsa, Aes128Gcm, Aead, Sha256, true;
+    0xc02c => TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384: Ecdhe, Ecdsa, Aes256Gcm, Aead, Sha384, true;
+    0xc02d => TLS_ECDH_ECDSA_WITH_AES_128_GCM_SHA256: Ecdh, Ecdsa, Aes128Gcm, Aead, Sha256, false;
+    0xc02e => TLS_ECDH_ECDSA_WITH_AES_256_GCM_SHA384: Ecdh, Ecdsa, Aes256Gcm, Aead, Sha384, false;
+    0xc02f => TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256: Ecdhe, Rsa, Aes128Gcm, Aead, Sha256, true;
+    0xc030 => TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384: Ecdhe, Rsa, Aes256Gcm, Aead, Sha384, true;
+    0xc031 => TLS_ECDH_RSA_WITH_AES_128_GCM_SHA256: Ecdh, Rsa, Aes128Gcm, Aead, Sha256, false;
+    0xc032 => TLS_ECDH_RSA_WITH_AES_256_GCM_SHA384: Ecdh, Rsa, Aes256Gcm, Aead, Sha384, false;
+    0xc033 => TLS_ECDHE_PSK_WITH_RC4_128_SHA: EcdhePsk, Psk, Rc4_128, Sha1, Sha256, false;
+    0xc034 => TLS_ECDHE_PSK_WITH_3DES_EDE_CBC_SHA: EcdhePsk, Psk, TripleDesEdeCbc, Sha1, Sha256, false;
+    0xc035 => TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA: EcdhePsk, Psk, Aes128Cbc, Sha1, Sha256, false;
+    0xc036 => TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA: EcdhePsk, Psk, Aes256Cbc, Sha1, Sha256, false;
+    0xc037 => TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA256: EcdhePsk, Psk, Aes128Cbc, Sha256, Sha256, false;
+    0xc038 => TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA384: EcdhePsk, Psk, Aes256Cbc, Sha384, Sha384, false;
+    0xc039 => TLS_ECDHE_PSK_WITH_NULL_SHA: EcdhePsk, Psk, Null, Sha1, Sha256, false;
+    0xc03a => TLS_ECDHE_PSK_WITH_NULL_SHA256: EcdhePsk, Psk, Null, Sha256, Sha256, false;
+    0xc03b => TLS_ECDHE_PSK_WITH_NULL_SHA384: EcdhePsk, Psk, Null, Sha384, Sha384, false;
+    0xc03c => TLS_RSA_WITH_ARIA_128_CBC_SHA256: Rsa, Rsa, Aria128Cbc, Sha256, Sha256, false;
+    0xc03d => TLS_RSA_WITH_ARIA_256_CBC_SHA384: Rsa, Rsa, Aria256Cbc, Sha384, Sha384, false;
+    0xc03e => TLS_DH_DSS_WITH_ARIA_128_CBC_SHA256: Dh, Dss, Aria128Cbc, Sha256, Sha256, false;
+    0xc03f => TLS_DH_DSS_WITH_ARIA_256_CBC_SHA384: Dh, Dss, Aria256Cbc, Sha384, Sha384, false;
+    0xc040 => TLS_DH_RSA_WITH_ARIA_128_CBC_SHA256: Dh, Rsa, Aria128Cbc, Sha256, Sha256, false;
+    0xc041 => TLS_DH_RSA_WITH_ARIA_256_CBC_SHA384: Dh, Rsa, Aria256Cbc, Sha384, Sha384, false;
+    0xc042 => TLS_DHE_DSS_WITH_ARIA_128_CBC_SHA256: Dhe, Dss, Aria128Cbc, Sha256, Sha256, false;
+    0xc043 => TLS_DHE_DSS_WITH_ARIA_256_CBC_SHA384: Dhe, Dss, Aria256Cbc, Sha384, Sha384, false;
+    0xc044 => TLS_DHE_RSA_WITH_ARIA_128_CBC_SHA256: Dhe, Rsa, Aria128Cbc, Sha256, Sha256, false;
+    0xc045 => TLS_DHE_RSA_WITH_ARIA_256_CBC_SHA384: Dhe, Rsa, Aria256Cbc, Sha384, Sha384, false;
+    0xc046 => TLS_DH_anon_WITH_ARIA_128_CBC_SHA256: Dhe, Anonymous, Aria128Cbc, Sha256, Sha256, false;
+    0xc047 => TLS_DH_anon_WITH_ARIA_256_CBC_SHA384: Dhe, Anonymous, Aria256Cbc, Sha384, Sha384, false;
+    0xc048 => TLS_ECDHE_ECDSA_WITH_ARIA_128_CBC_SHA256: Ecdhe, Ecdsa, Aria128Cbc, Sha256, Sha256, false;
+    0xc049 => TLS_ECDHE_ECDSA_WITH_ARIA_256_CBC_SHA384: Ecdhe, Ecdsa, Aria256Cbc, Sha384, Sha384, false;
+    0xc04a => TLS_ECDH_ECDSA_WITH_ARIA_128_CBC_SHA256: Ecdh, Ecdsa, Aria128Cbc, Sha256, Sha256, false;
+    0xc04b => TLS_ECDH_ECDSA_WITH_ARIA_256_CBC_SHA384: Ecdh, Ecdsa, Aria256Cbc, Sha384, Sha384, false;
+    0xc04c => TLS_ECDHE_RSA_WITH_ARIA_128_CBC_SHA256: Ecdhe, Rsa, Aria128Cbc, Sha256, Sha256, false;
+    0xc04d => TLS_ECDHE_RSA_WITH_ARIA_256_CBC_SHA384: Ecdhe, Rsa, Aria256Cbc, Sha384, Sha384, false;
+    0xc04e => TLS_ECDH_RSA_WITH_ARIA_128_CBC_SHA256: Ecdh, Rsa, Aria128Cbc, Sha256, Sha256, false;
+    0xc04f => TLS_ECDH_RSA_WITH_ARIA_256_CBC_SHA384: Ecdh, Rsa, Aria256Cbc, Sha384, Sha384, false;
+    0xc050 => TLS_RSA_WITH_ARIA_128_GCM_SHA256: Rsa, Rsa, Aria128Gcm, Aead, Sha256, false;
+    0xc051 => TLS_RSA_WITH_ARIA_256_GCM_SHA384: Rsa, Rsa, Aria256Gcm, Aead, Sha384, false;
+    0xc052 => TLS_DHE_RSA_WITH_ARIA_128_GCM_SHA256: Dhe, Rsa, Aria128Gcm, Aead, Sha256, false;
+    0xc053 => TLS_DHE_RSA_WITH_ARIA_256_GCM_SHA384: Dhe, Rsa, Aria256Gcm, Aead, Sha384, false;
+    0xc054 => TLS_DH_RSA_WITH_ARIA_128_GCM_SHA256: Dh, Rsa, Aria128Gcm, Aead, Sha256, false;
+    0xc055 => TLS_DH_RSA_WITH_ARIA_256_GCM_SHA384: Dh, Rsa, Aria256Gcm, Aead, Sha384, false;
+    0xc056 => TLS_DHE_DSS_WITH_ARIA_128_GCM_SHA256: Dhe, Dss, Aria128Gcm, Aead, Sha256, false;
+    0xc057 => TLS_DHE_DSS_WITH_ARIA_256_GCM_SHA384: Dhe, Dss, Aria256Gcm, Aead, Sha384, false;
+    0xc058 => TLS_DH_DSS_WITH_ARIA_128_GCM_SHA256: Dh, Dss, Aria128Gcm, Aead, Sha256, false;
+    0xc059 => TLS_DH_DSS_WITH_ARIA_256_GCM_SHA384: Dh, Dss, Aria256Gcm, Aead, Sha384, false;
+    0xc05a => TLS_DH_anon_WITH_ARIA_128_GCM_SHA256: Dhe, Anonymous, Aria128Gcm, Aead, Sha256, false;
+    0xc05b => TLS_DH_anon_WITH_ARIA_256_GCM_SHA384: Dhe, Anonymous, Aria256Gcm, Aead, Sha384, false;
+    0xc05c => TLS_ECDHE_ECDSA_WITH_ARIA_128_GCM_SHA256: Ecdhe, Ecdsa, Aria128Gcm, Aead, Sha256, false;
+    0xc05d => TLS_ECDHE_ECDSA_WITH_ARIA_256_GCM_SHA384: Ecdhe, Ecdsa, Aria256Gcm, Aead, Sha384, false;
+    0xc05e => TLS_ECDH_ECDSA_WITH_ARIA_128_GCM_SHA256: Ecdh, Ecdsa, Aria128Gcm, Aead, Sha256, false;
+    0xc05f => TLS_ECDH_ECDSA_WITH_ARIA_256_GCM_SHA384: Ecdh, Ecdsa, Aria256Gcm, Aead, Sha384, false;
+    0xc060 => TLS_ECDHE_RSA_WITH_ARIA_128_GCM_SHA256: Ecdhe, Rsa, Aria128Gcm, Aead, Sha256, false;
+    0xc061 => TLS_ECDHE_RSA_WITH_ARIA_256_GCM_SHA384: Ecdhe, Rsa, Aria256Gcm, Aead, Sha384, false;
+    0xc062 => TLS_ECDH_RSA_WITH_ARIA_128_GCM_SHA256: Ecdh, Rsa, Aria128Gcm, Aead, Sha256, false;
+    0xc063 => TLS_ECDH_RSA_WITH_ARIA_256_GCM_SHA384: Ecdh, Rsa, Aria256Gcm, Aead, Sha384, false;
+    0xc064 => TLS_PSK_WITH_ARIA_128_CBC_SHA256: Psk, Psk, Aria128Cbc, Sha256, Sha256, false;
+    0xc065 => TLS_PSK_WITH_ARIA_256_CBC_SHA384: Psk, Psk, Aria256Cbc, Sha384, Sha384, false;
+    0xc066 => TLS_DHE_PSK_WITH_ARIA_128_CBC_SHA256: DhePsk, Psk, Aria128Cbc, Sha256, Sha256, false;
+    0xc067 => TLS_DHE_PSK_WITH_ARIA_256_CBC_SHA384: DhePsk, Psk, Aria256Cbc, Sha384, Sha384, false;
+    0xc068 => TLS_RSA_PSK_WITH_ARIA_128_CBC_SHA256: RsaPsk, Rsa, Aria128Cbc, Sha256, Sha256, false;
+    0xc069 => TLS_RSA_PSK_WITH_ARIA_256_CBC_SHA384: RsaPsk, Rsa, Aria256Cbc, Sha384, Sha384, false;
+    0xc06a => TLS_PSK_WITH_ARIA_128_GCM_SHA256: Psk, Psk, Aria128Gcm, Aead, Sha256, false;
+    0xc06b => TLS_PSK_WITH_ARIA_256_GCM_SHA384: Psk, Psk, Aria256Gcm, Aead, Sha384, false;
+    0xc06c => TLS_DHE_PSK_WITH_ARIA_128_GCM_SHA256: DhePsk, Psk, Aria128Gcm, Aead, Sha256, false;
+    0xc06d => TLS_DHE_PSK_WITH_ARIA_256_GCM_SHA384: DhePsk, Psk, Aria256Gcm, Aead, Sha384, false;
+    0xc06e => TLS_RSA_PSK_WITH_ARIA_128_GCM_SHA256: RsaPsk, Rsa, Aria128Gcm, Aead, Sha256, false;
+    0xc06f => TLS_RSA_PSK_WITH_ARIA_256_GCM_SHA384: RsaPsk, Rsa, Aria256Gcm, Aead, Sha384, false;
+    0xc070 => TLS_ECDHE_PSK_WITH_ARIA_128_CBC_SHA256: EcdhePsk, Psk, Aria128Cbc, Sha256, Sha256, false;
+    0xc071 => TLS_ECDHE_PSK_WITH_ARIA_256_CBC_SHA384: EcdhePsk, Psk, Aria256Cbc, Sha384, Sha384, false;
+    0xc072 => TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_CBC_SHA256: Ecdhe, Ecdsa, Camellia128Cbc, Sha256, Sha256, false;
+    0xc073 => TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_CBC_SHA384: Ecdhe, Ecdsa, Camellia256Cbc, Sha384, Sha384, false;
+    0xc074 => TLS_ECDH_ECDSA_WITH_CAMELLIA_128_CBC_SHA256: Ecdh, Ecdsa, Camellia128Cbc, Sha256, Sha256, false;
+    0xc075 => TLS_ECDH_ECDSA_WITH_CAMELLIA_256_CBC_SHA384: Ecdh, Ecdsa, Camellia256Cbc, Sha384, Sha384, false;
+    0xc076 => TLS_ECDHE_RSA_WITH_CAMELLIA_128_CBC_SHA256: Ecdhe, Rsa, Camellia128Cbc, Sha256, Sha256, false;
+    0xc077 => TLS_ECDHE_RSA_WITH_CAMELLIA_256_CBC_SHA384: Ecdhe, Rsa, Camellia256Cbc, Sha384, Sha384, false;
+    0xc078 => TLS_ECDH_RSA_WITH_CAMELLIA_128_CBC_SHA256: Ecdh, Rsa, Camellia128Cbc, Sha256, Sha256, false;
+    0xc079 => TLS_ECDH_RSA_WITH_CAMELLIA_256_CBC_SHA384: Ecdh, Rsa, Camellia256Cbc, Sha384, Sha384, false;
+    0xc07a => TLS_RSA_WITH_CAMELLIA_128_GCM_SHA256: Rsa, Rsa, Camellia128Gcm, Aead, Sha256, false;
+    0xc07b => TLS_RSA_WITH_CAMELLIA_256_GCM_SHA384: Rsa, Rsa, Camellia256Gcm, Aead, Sha384, false;
+    0xc07c => TLS_DHE_RSA_WITH_CAMELLIA_128_GCM_SHA256: Dhe, Rsa, Camellia128Gcm, Aead, Sha256, false;
+    0xc07d => TLS_DHE_RSA_WITH_CAMELLIA_256_GCM_SHA384: Dhe, Rsa, Camellia256Gcm, Aead, Sha384, false;
+    0xc07e => TLS_DH_RSA_WITH_CAMELLIA_128_GCM_SHA256: Dh, Rsa, Camellia128Gcm, Aead, Sha256, false;
+    0xc07f => TLS_DH_RSA_WITH_CAMELLIA_256_GCM_SHA384: Dh, Rsa, Camellia256Gcm, Aead, Sha384, false;
+    0xc080 => TLS_DHE_DSS_WITH_CAMELLIA_128_GCM_SHA256: Dhe, Dss, Camellia128Gcm, Aead, Sha256, false;
+    0xc081 => TLS_DHE_DSS_WITH_CAMELLIA_256_GCM_SHA384: Dhe, Dss, Camellia256Gcm, Aead, Sha384, false;
+    0xc082 => TLS_DH_DSS_WITH_CAMELLIA_128_GCM_SHA256: Dh, Dss, Camellia128Gcm, Aead, Sha256, false;
+    0xc083 => TLS_DH_DSS_WITH_CAMELLIA_256_GCM_SHA384: Dh, Dss, Camellia256Gcm, Aead, Sha384, false;
+    0xc084 => TLS_DH_anon_WITH_CAMELLIA_128_GCM_SHA256: Dhe, Anonymous, Camellia128Gcm, Aead, Sha256, false;
+    0xc085 => TLS_DH_anon_WITH_CAMELLIA_256_GCM_SHA384: Dhe, Anonymous, Camellia256Gcm, Aead, Sha384, false;
+    0xc086 => TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_GCM_SHA256: Ecdhe, Ecdsa, Camellia128Gcm, Aead, Sha256, false;
+    0xc087 => TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_GCM_SHA384: Ecdhe, Ecdsa, Camellia256Gcm, Aead, Sha384, false;
+    0xc088 => TLS_ECDH_ECDSA_WITH_CAMELLIA_128_GCM_SHA256: Ecdh, Ecdsa, Camellia128Gcm, Aead, Sha256, false;
+    0xc089 => TLS_ECDH_ECDSA_WITH_CAMELLIA_256_GCM_SHA384: Ecdh, Ecdsa, Camellia256Gcm, Aead, Sha384, false;
+    0xc08a => TLS_ECDHE_RSA_WITH_CAMELLIA_128_GCM_SHA256: Ecdhe, Rsa, Camellia128Gcm, Aead, Sha256, false;
+    0xc08b => TLS_ECDHE_RSA_WITH_CAMELLIA_256_GCM_SHA384: Ecdhe, Rsa, Camellia256Gcm, Aead, Sha384, false;
+    0xc08c => TLS_ECDH_RSA_WITH_CAMELLIA_128_GCM_SHA256: Ecdh, Rsa, Camellia128Gcm, Aead, Sha256, false;
+    0xc08d => TLS_ECDH_RSA_WITH_CAMELLIA_256_GCM_SHA384: Ecdh, Rsa, Camellia256Gcm, Aead, Sha384, false;
+    0xc08e => TLS_PSK_WITH_CAMELLIA_128_GCM_SHA256: Psk, Psk, Camellia128Gcm, Aead, Sha256, false;
+    0xc08f => TLS_PSK_WITH_CAMELLIA_256_GCM_SHA384: Psk, Psk, Camellia256Gcm, Aead, Sha384, false;
+    0xc090 => TLS_DHE_PSK_WITH_CAMELLIA_128_GCM_SHA256: DhePsk, Psk, Camellia128Gcm, Aead, Sha256, false;
+    0xc091 => TLS_DHE_PSK_WITH_CAMELLIA_256_GCM_SHA384: DhePsk, Psk, Camellia256Gcm, Aead, Sha384, false;
+    0xc092 => TLS_RSA_PSK_WITH_CAMELLIA_128_GCM_SHA256: RsaPsk, Rsa, Camellia128Gcm, Aead, Sha256, false;
+    0xc093 => TLS_RSA_PSK_WITH_CAMELLIA_256_GCM_SHA384: RsaPsk, Rsa, Camellia256Gcm, Aead, Sha384, false;
+    0xc094 => TLS_PSK_WITH_CAMELLIA_128_CBC_SHA256: Psk, Psk, Camellia128Cbc, Sha256, Sha256, false;
+    0xc095 => TLS_PSK_WITH_CAMELLIA_256_CBC_SHA384: Psk, Psk, Camellia256Cbc, Sha384, Sha384, false;
+    0xc096 => TLS_DHE_PSK_WITH_CAMELLIA_128_CBC_SHA256: DhePsk, Psk, Camellia128Cbc, Sha256, Sha256, false;
+    0xc097 => TLS_DHE_PSK_WITH_CAMELLIA_256_CBC_SHA384: DhePsk, Psk, Camellia256Cbc, Sha384, Sha384, false;
+    0xc098 => TLS_RSA_PSK_WITH_CAMELLIA_128_CBC_SHA256: RsaPsk, Rsa, Camellia128Cbc, Sha256, Sha256, false;
+    0xc099 => TLS_RSA_PSK_WITH_CAMELLIA_256_CBC_SHA384: RsaPsk, Rsa, Camellia256Cbc, Sha384, Sha384, false;
+    0xc09a => TLS_ECDHE_PSK_WITH_CAMELLIA_128_CBC_SHA256: EcdhePsk, Psk, Camellia128Cbc, Sha256, Sha256, false;
+    0xc09b => TLS_ECDHE_PSK_WITH_CAMELLIA_256_CBC_SHA384: EcdhePsk, Psk, Camellia256Cbc, Sha384, Sha384, false;
+    0xc09c => TLS_RSA_WITH_AES_128_CCM: Rsa, Rsa, Aes128Ccm, Aead, Sha256, false;
+    0xc09d => TLS_RSA_WITH_AES_256_CCM: Rsa, Rsa, Aes256Ccm, Aead, Sha256, false;
+    0xc09e => TLS_DHE_RSA_WITH_AES_128_CCM: Dhe, Rsa, Aes128Ccm, Aead, Sha256, false;
+    0xc09f => TLS_DHE_RSA_WITH_AES_256_CCM: Dhe, Rsa, Aes256Ccm, Aead, Sha256, false;
+    0xc0a0 => TLS_RSA_WITH_AES_128_CCM_8: Rsa, Rsa, Aes128Ccm8, Aead, Sha256, false;
+    0xc0a1 => TLS_RSA_WITH_AES_256_CCM_8: Rsa, Rsa, Aes256Ccm8, Aead, Sha256, false;
+    0xc0a2 => TLS_DHE_RSA_WITH_AES_128_CCM_8: Dhe, Rsa, Aes128Ccm8, Aead, Sha256, false;
+    0xc0a3 => TLS_DHE_RSA_WITH_AES_256_CCM_8: Dhe, Rsa, Aes256Ccm8, Aead, Sha256, false;
+    0xc0a4 => TLS_PSK_WITH_AES_128_CCM: Psk, Psk, Aes128Ccm, Aead, Sha256, false;
+    0xc0a5 => TLS_PSK_WITH_AES_256_CCM: Psk, Psk, Aes256Ccm, Aead, Sha256, false;
+    0xc0a6 => TLS_DHE_PSK_WITH_AES_128_CCM: DhePsk, Psk, Aes128Ccm, Aead, Sha256, false;
+    0xc0a7 => TLS_DHE_PSK_WITH_AES_256_CCM: DhePsk, Psk, Aes256Ccm, Aead, Sha256, false;
+    0xc0a8 => TLS_PSK_WITH_AES_128_CCM_8: Psk, Psk, Aes128Ccm8, Aead, Sha256, false;
+    0xc0a9 => TLS_PSK_WITH_AES_256_CCM_8: Psk, Psk, Aes256Ccm8, Aead, Sha256, false;
+    0xc0aa => TLS_PSK_DHE_WITH_AES_128_CCM_8: DhePsk, Psk, Aes128Ccm8, Aead, Sha256, false;
+    0xc0ab => TLS_PSK_DHE_WITH_AES_256_CCM_8: DhePsk, Psk, Aes256Ccm8, Aead, Sha256, false;
+    0xc0ac => TLS_ECDHE_ECDSA_WITH_AES_128_CCM: Ecdhe, Ecdsa, Aes128Ccm, Aead, Sha256, false;
+    0xc0ad => TLS_ECDHE_ECDSA_WITH_AES_256_CCM: Ecdhe, Ecdsa, Aes256Ccm, Aead, Sha256, false;
+    0xc0ae => TLS_ECDHE_ECDSA_WITH_AES_128_CCM_8: Ecdhe, Ecdsa, Aes128Ccm8, Aead, Sha256, false;
+    0xc0af => TLS_ECDHE_ECDSA_WITH_AES_256_CCM_8: Ecdhe, Ecdsa, Aes256Ccm8, Aead, Sha256, false;
+    0xc0b0 => TLS_ECCPWD_WITH_AES_128_GCM_SHA256: EcCpwd, Password, Aes128Gcm, Aead, Sha256, false;
+    0xc0b1 => TLS_ECCPWD_WITH_AES_256_GCM_SHA384: EcCpwd, Password, Aes256Gcm, Aead, Sha384, false;
+    0xc0b2 => TLS_ECCPWD_WITH_AES_128_CCM_SHA256: EcCpwd, Password, Aes128Ccm, Aead, Sha256, false;
+    0xc0b3 => TLS_ECCPWD_WITH_AES_256_CCM_SHA384: EcCpwd, Password, Aes256Ccm, Aead, Sha384, false;
+    0xcca8 => TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256: Ecdhe, Rsa, ChaCha20Poly1305, Aead, Sha256, false;
+    0xcca9 => TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256: Ecdhe, Ecdsa, ChaCha20Poly1305, Aead, Sha256, false;
+    0xccaa => TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256: Dhe, Rsa, ChaCha20Poly1305, Aead, Sha256, false;
+    0xccab => TLS_PSK_WITH_CHACHA20_POLY1305_SHA256: Psk, Psk, ChaCha20Poly1305, Aead, Sha256, false;
+    0xccac => TLS_ECDHE_PSK_WITH_CHACHA20_POLY1305_SHA256: EcdhePsk, Psk, ChaCha20Poly1305, Aead, Sha256, false;
+    0xccad => TLS_DHE_PSK_WITH_CHACHA20_POLY1305_SHA256: DhePsk, Psk, ChaCha20Poly1305, Aead, Sha256, false;
+    0xccae => TLS_RSA_PSK_WITH_CHACHA20_POLY1305_SHA256: RsaPsk, Rsa, ChaCha20Poly1305, Aead, Sha256, false;
+    0xd001 => TLS_ECDHE_PSK_WITH_AES_128_GCM_SHA256: EcdhePsk, Psk, Aes128Gcm, Aead, Sha256, false;
+    0xd002 => TLS_ECDHE_PSK_WITH_AES_256_GCM_SHA384: EcdhePsk, Psk, Aes256Gcm, Aead, Sha384, false;
+    0xd003 => TLS_ECDHE_PSK_WITH_AES_128_CCM_8_SHA256: EcdhePsk, Psk, Aes128Ccm8, Aead, Sha256, false;
+    0xd005 => TLS_ECDHE_PSK_WITH_AES_128_CCM_SHA256: EcdhePsk, Psk, Aes128Ccm, Aead, Sha256, false;
+}
+
+// The suites this crate offers and accepts, most preferred first.
+pub const DEFAULT_CIPHER_SUITES: [CipherSuite; 4] = [
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+    CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+    CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+];
+
+impl CipherSuite {
+    // None for signaling values and unknown suites
+    pub fn params(self) -> Option<&'static CipherSuiteParams> {
+        REGISTRY.iter().find(|params| params.suite == self)
+    }
+
+    pub fn is_implemented(self) -> bool {
+        self.params().is_some_and(|params| params.implemented)
+    }
+}
+
+impl Codec for CipherSuite {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        self.get_u16().encode_into(bytes);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(CipherSuite::from_u16(u16::decode(r)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        for params in REGISTRY {
+            assert_eq!(CipherSuite::from_u16(params.suite.get_u16()), params.suite);
+            // the implemented suites are exactly the ones that can be offered
+            assert_eq!(
+                params.implemented,
+                DEFAULT_CIPHER_SUITES.contains(&params.suite)
+            );
+        }
+        assert_eq!(CipherSuite::from_u16(0x1301), CipherSuite::Unknown(0x1301));
+        assert!(CipherSuite::TLS_EMPTY_RENEGOTIATION_INFO_SCSV
+            .params()
+            .is_none());
+
+        let params = CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384
+            .params()
+            .unwrap();
+        assert_eq!(params.key_exchange, KeyExchange::Ecdhe);
+        assert_eq!(params.authentication, Authentication::Rsa);
+        assert_eq!(params.prf_hash, PrfHash::Sha384);
+        assert_eq!(params.key_block_len(), 2 * (32 + 4));
+
+        // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#C--Cipher-Suite-Definitions
+        let params = CipherSuite::TLS_RSA_WITH_AES_128_CBC_SHA256
+            .params()
+            .unwrap();
+        assert_eq!(params.bulk_cipher.cipher_type(), CipherType::Block);
+        assert_eq!((params.key_len(), params.record_iv_len()), (16, 16));
+        assert_eq!(params.mac_len(), 32);
+        assert_eq!(params.prf_hash, PrfHash::Sha256);
+        let params = CipherSuite::TLS_RSA_WITH_3DES_EDE_CBC_SHA.params().unwrap();
+        assert_eq!((params.key_len(), params.record_iv_len()), (24, 8));
+        assert_eq!(params.mac, MacAlgorithm::Sha1);
+    }
+}
@@ -0,0 +1,1205 @@
+use crate::certificate::{chain_info, Certificate, CertificateInfo};
+use crate::certificate_request::CertificateRequest;
+use crate::certificate_status::CertificateStatus;
+use crate::certificate_verify::CertificateVerify;
+use crate::cipher_suite::{CipherSuite, CipherSuiteParams, DEFAULT_CIPHER_SUITES};
+use crate::client_hello::ClientHelloPayload;
+use crate::client_key_exchange::ClientKeyExchange;
+use crate::codec::Codec;
+use crate::config;
+use crate::connection::{key_expansion, Connection, ConnectionCommon, Message};
+use crate::crl::{CrlChecker, RevocationError};
+use crate::ct::{CtPolicy, SignedCertificateTimestamp, VerifiedSct};
+use crate::enums::{
+    AlertDescription, AlertLevel, HandshakeType, MaxFragmentLength, NamedGroup, SignatureScheme,
+};
+use crate::error::{CertificateError, ConfigError, Error};
+use crate::extensions::{ClientExtension, ServerExtension};
+use crate::handshake::{HandshakePayload, MasterSecretInfo, SessionId};
+use crate::key_exchange::SUPPORTED_GROUPS;
+use crate::key_log::{KeyLog, NoKeyLog};
+use crate::new_session_ticket::NewSessionTicket;
+use crate::ocsp::{CertStatus, OcspResponse};
+use crate::pcap::PcapRecorder;
+use crate::pinning::PinSet;
+use crate::server_hello::ServerHelloPayload;
+use crate::server_hello_done::ServerHelloDone;
+use crate::server_key_exchange::ServerKeyExchange;
+use crate::session::{unix_time, ClientSessionCache, ClientSessionValue};
+use crate::sign::{CertifiedKey, SUPPORTED_SIGNATURE_SCHEMES};
+use crate::tls_plaintext::MAX_FRAGMENT_LEN;
+use crate::verify::{verify_chain, verify_server_name, RootCertStore};
+use std::mem;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+// TLS 1.2 client side of a connection. It does no I/O itself, see Connection.
+//
+// Full handshake:
+// ClientHello -> ServerHello, Certificate, CertificateStatus**, ServerKeyExchange,
+// CertificateRequest*, ServerHelloDone
+// -> Certificate*, ClientKeyExchange, CertificateVerify*, [ChangeCipherSpec], Finished
+// -> [ChangeCipherSpec], Finished
+//
+// * only when the server requests client authentication
+// ** only when the server acknowledges status_request. The stapled OCSP response must be
+// valid and the certificate not revoked.
+//
+// Abbreviated handshake, when the server echoes the cached session_id:
+// ClientHello -> ServerHello, [ChangeCipherSpec], Finished -> [ChangeCipherSpec], Finished
+//
+// If the server acknowledges the session_ticket extension it sends NewSessionTicket
+// right before its ChangeCipherSpec in either handshake (RFC 5077).
+//
+// Renegotiation is only done with servers that support RFC 5746 secure renegotiation.
+pub struct ClientConnection {
+    common: ConnectionCommon,
+    config: Arc<ClientConfig>,
+    server_name: String,
+    state: ClientState,
+    peer_certificates: Vec<Vec<u8>>,
+    // OCSP response stapled in the last full handshake
+    ocsp_response: Option<Vec<u8>>,
+    resumed: bool,
+    // RFC 5746: the server sent renegotiation_info in the initial ServerHello
+    secure_renegotiation: bool,
+    // verify_data of the Finished messages of the last handshake
+    client_verify_data: Vec<u8>,
+    server_verify_data: Vec<u8>,
+    // SCTs that satisfied the CT policy in the last full handshake
+    verified_scts: Vec<VerifiedSct>,
+    // protocol the server selected with ALPN
+    alpn_protocol: Option<Vec<u8>>,
+}
+
+// The message the client waits for. The states after ServerHello carry what was negotiated.
+enum ClientState {
+    ExpectServerHello(Box<ClientHello>),
+    ExpectCertificate(Box<ClientHandshake>),
+    // CertificateStatus, or ServerKeyExchange if the server omits it
+    ExpectCertificateStatus(Box<ClientHandshake>),
+    ExpectServerKeyExchange(Box<ClientHandshake>),
+    // CertificateRequest or ServerHelloDone
+    ExpectCertificateRequest(Box<ClientHandshake>, ServerKeyExchange),
+    ExpectServerHelloDone(Box<ClientHandshake>, ServerKeyExchange, CertificateRequest),
+    ExpectNewSessionTicket(Box<ClientHandshake>, MasterSecretInfo),
+    ExpectChangeCipherSpec(Box<ClientHandshake>, MasterSecretInfo),
+    ExpectFinished(Box<ClientHandshake>, MasterSecretInfo),
+    Connected,
+}
+
+impl ClientState {
+    fn expected(&self) -> &'static str {
+        match self {
+            ClientState::ExpectServerHello(_) => "ServerHello",
+            ClientState::ExpectCertificate(_) => "Certificate",
+            ClientState::ExpectCertificateStatus(..) => "CertificateStatus or ServerKeyExchange",
+            ClientState::ExpectServerKeyExchange(_) => "ServerKeyExchange",
+            ClientState::ExpectCertificateRequest(..) => "CertificateRequest or ServerHelloDone",
+            ClientState::ExpectServerHelloDone(..) => "ServerHelloDone",
+            ClientState::ExpectNewSessionTicket(..) => "NewSessionTicket",
+            ClientState::ExpectChangeCipherSpec(..) => "ChangeCipherSpec",
+            ClientState::ExpectFinished(..) => "Finished",
+            ClientState::Connected => "no handshake message",
+        }
+    }
+}
+
+// The ClientHello that was sent and the session it offers.
+struct ClientHello {
+    client_hello: ClientHelloPayload,
+    cached: Option<ClientSessionValue>,
+}
+
+// What ServerHello negotiated, completed by the following messages.
+struct ClientHandshake {
+    suite: &'static CipherSuiteParams,
+    // session_id from ServerHello
+    session_id: SessionId,
+    client_random: Vec<u8>,
+    server_random: Vec<u8>,
+    expect_ticket: bool,
+    expect_status: bool,
+    extended_master_secret: bool,
+    // SCTs from the signed_certificate_timestamp extension
+    tls_scts: Vec<SignedCertificateTimestamp>,
+    // subjectPublicKey of the server's certificate
+    public_key: Vec<u8>,
+    // the verified path to a trust anchor, leaf first, or the certificates as presented when
+    // they are not verified
+    path: Vec<Vec<u8>>,
+    // from NewSessionTicket, or the ticket of the resumed session
+    ticket: Option<Vec<u8>>,
+    // from NewSessionTicket, None if the server did not send one in this handshake
+    ticket_lifetime_hint: Option<u32>,
+}
+
+// What to do when the server sends HelloRequest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenegotiationPolicy {
+    // reply with a no_renegotiation warning alert
+    Refuse,
+    // run a new handshake, if the server supports secure renegotiation
+    Renegotiate,
+}
+
+// Settings of client connections, validated by ClientConfigBuilder::build.
+// It is immutable, so one Arc<ClientConfig> can be shared by many connections.
+// Connections that share it also share its session cache.
+pub struct ClientConfig {
+    // in order of preference
+    cipher_suites: Vec<CipherSuite>,
+    named_groups: Vec<NamedGroup>,
+    signature_schemes: Vec<SignatureScheme>,
+    // None only if certificate verification was turned off explicitly
+    root_store: Option<RootCertStore>,
+    // candidates for client authentication
+    client_certificates: Vec<CertifiedKey>,
+    // in order of preference, empty to not offer ALPN
+    alpn_protocols: Vec<Vec<u8>>,
+    session_cache: Arc<ClientSessionCache>,
+    max_fragment_length: Option<MaxFragmentLength>,
+    // abort handshakes without the extended master secret (RFC 7627)
+    require_ems: bool,
+    // send the server_name extension for host names
+    enable_sni: bool,
+    renegotiation_policy: RenegotiationPolicy,
+    // CRL based revocation checking of the server's chain
+    crl_checker: Option<CrlChecker>,
+    // SubjectPublicKeyInfo pins, checked after the revocation check
+    pins: Option<PinSet>,
+    // Certificate Transparency policy for the server's leaf
+    ct_policy: Option<CtPolicy>,
+    key_log: Arc<dyn KeyLog>,
+    // seconds since the UNIX epoch for the certificate, CRL, OCSP and CT checks
+    time_provider: fn() -> u64,
+}
+
+impl ClientConfig {
+    pub fn builder() -> ClientConfigBuilder {
+        ClientConfigBuilder::new()
+    }
+
+    pub fn cipher_suites(&self) -> &[CipherSuite] {
+        &self.cipher_suites
+    }
+
+    pub fn alpn_protocols(&self) -> &[Vec<u8>] {
+        &self.alpn_protocols
+    }
+
+    pub fn session_cache(&self) -> &Arc<ClientSessionCache> {
+        &self.session_cache
+    }
+}
+
+pub struct ClientConfigBuilder {
+    config: ClientConfig,
+    no_certificate_verification: bool,
+}
+
+impl ClientConfigBuilder {
+    // the crate's defaults, without trust anchors
+    pub fn new() -> Self {
+        ClientConfigBuilder {
+            config: ClientConfig {
+                cipher_suites: DEFAULT_CIPHER_SUITES.to_vec(),
+                named_groups: SUPPORTED_GROUPS.to_vec(),
+                signature_schemes: SUPPORTED_SIGNATURE_SCHEMES.to_vec(),
+                root_store: None,
+                client_certificates: Vec::new(),
+                alpn_protocols: Vec::new(),
+                session_cache: Arc::new(ClientSessionCache::new()),
+                max_fragment_length: None,
+                require_ems: false,
+                enable_sni: true,
+                renegotiation_policy: RenegotiationPolicy::Refuse,
+                crl_checker: None,
+                pins: None,
+                ct_policy: None,
+                key_log: Arc::new(NoKeyLog),
+                time_provider: unix_time,
+            },
+            no_certificate_verification: false,
+        }
+    }
+
+    // cipher suites to offer, most preferred first
+    pub fn with_cipher_suites(mut self, cipher_suites: Vec<CipherSuite>) -> Self {
+        self.config.cipher_suites = cipher_suites;
+        self
+    }
+
+    // supported_groups to offer, most preferred first
+    pub fn with_named_groups(mut self, named_groups: Vec<NamedGroup>) -> Self {
+        self.config.named_groups = named_groups;
+        self
+    }
+
+    // signature_algorithms to offer, most preferred first
+    pub fn with_signature_schemes(mut self, signature_schemes: Vec<SignatureScheme>) -> Self {
+        self.config.signature_schemes = signature_schemes;
+        self
+    }
+
+    // the server's chain must lead to one of these and its leaf must match the server name
+    pub fn with_root_certificates(mut self, root_store: RootCertStore) -> Self {
+        self.config.root_store = Some(root_store);
+        self.no_certificate_verification = false;
+        self
+    }
+
+    // accept any server certificate. Only for tests, or when the server is authenticated
+    // by other means such as pins.
+    pub fn with_no_certificate_verification(mut self) -> Self {
+        self.config.root_store = None;
+        self.no_certificate_verification = true;
+        self
+    }
+
+    // offer this certificate when the server sends CertificateRequest.
+    // The first one that matches the request is used.
+    pub fn with_client_certificate(mut self, certified_key: CertifiedKey) -> Self {
+        self.config.client_certificates.push(certified_key);
+        self
+    }
+
+    // ALPN protocol names, most preferred first
+    pub fn with_alpn_protocols(mut self, alpn_protocols: Vec<Vec<u8>>) -> Self {
+        self.config.alpn_protocols = alpn_protocols;
+        self
+    }
+
+    // share sessions with other configs
+    pub fn with_session_cache(mut self, session_cache: Arc<ClientSessionCache>) -> Self {
+        self.config.session_cache = session_cache;
+        self
+    }
+
+    // ask the server for smaller records (RFC 6066)
+    pub fn with_max_fragment_length(mut self, max_fragment_length: MaxFragmentLength) -> Self {
+        self.config.max_fragment_length = Some(max_fragment_length);
+        self
+    }
+
+    // refuse servers that do not support the extended master secret (RFC 7627)
+    pub fn with_require_ems(mut self, require_ems: bool) -> Self {
+        self.config.require_ems = require_ems;
+        self
+    }
+
+    // the server_name extension is sent by default
+    pub fn with_sni(mut self, enable_sni: bool) -> Self {
+        self.config.enable_sni = enable_sni;
+        self
+    }
+
+    pub fn with_renegotiation_policy(mut self, policy: RenegotiationPolicy) -> Self {
+        self.config.renegotiation_policy = policy;
+        self
+    }
+
+    // check the verified path of the server's chain against CRLs in full handshakes.
+    // Needs root certificates, there is no path to check without them.
+    pub fn with_crl_checker(mut self, crl_checker: CrlChecker) -> Self {
+        self.config.crl_checker = Some(crl_checker);
+        self
+    }
+
+    // require one of the pinned keys on the verified path of the server's chain, if the server
+    // name has pins. Without certificate verification only the leaf key can match.
+    pub fn with_pins(mut self, pins: PinSet) -> Self {
+        self.config.pins = Some(pins);
+        self
+    }
+
+    // require SCTs from the TLS extension, the stapled OCSP response or the certificate
+    pub fn with_ct_policy(mut self, ct_policy: CtPolicy) -> Self {
+        self.config.ct_policy = Some(ct_policy);
+        self
+    }
+
+    // receives the master secret of every handshake, e.g. a KeyLogFile
+    pub fn with_key_log(mut self, key_log: Arc<dyn KeyLog>) -> Self {
+        self.config.key_log = key_log;
+        self
+    }
+
+    // the current time for the certificate, CRL, OCSP and CT checks, session::unix_time by
+    // default. Tests set the time their fixtures are valid at.
+    pub fn with_time_provider(mut self, time_provider: fn() -> u64) -> Self {
+        self.config.time_provider = time_provider;
+        self
+    }
+
+    pub fn build(self) -> Result<ClientConfig, ConfigError> {
+        let config = self.config;
+        config::check_signature_schemes(&config.signature_schemes)?;
+        config::check_cipher_suites(&config.cipher_suites, &config.signature_schemes)?;
+        config::check_named_groups(&config.named_groups)?;
+        config::check_alpn_protocols(&config.alpn_protocols)?;
+        config::check_max_fragment_length(config.max_fragment_length)?;
+        match &config.root_store {
+            Some(root_store) if root_store.is_empty() => {
+                return Err(ConfigError::new("the root certificate store is empty"))
+            }
+            None if !self.no_certificate_verification => {
+                return Err(ConfigError::new(
+                    "no root certificates, certificate verification must be turned off explicitly",
+                ))
+            }
+            _ => {}
+        }
+        if config.crl_checker.is_some() && config.root_store.is_none() {
+            return Err(ConfigError::new(
+                "revocation checking needs root certificates",
+            ));
+        }
+        Ok(config)
+    }
+}
+
+impl Default for ClientConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientConnection {
+    // server_name is verified against the server's certificate and sent with SNI,
+    // and it is the key of the session cache. The ClientHello is queued right away.
+    pub fn new(config: Arc<ClientConfig>, server_name: &str) -> Self {
+        let mut connection = ClientConnection {
+            common: ConnectionCommon::new(),
+            config,
+            server_name: server_name.to_string(),
+            state: ClientState::Connected,
+            peer_certificates: Vec::new(),
+            ocsp_response: None,
+            resumed: false,
+            secure_renegotiation: false,
+            client_verify_data: Vec::new(),
+            server_verify_data: Vec::new(),
+            verified_scts: Vec::new(),
+            alpn_protocol: None,
+        };
+        connection.state = connection.send_client_hello();
+        connection
+    }
+
+    // true if the last handshake resumed a cached session
+    pub fn is_resumed(&self) -> bool {
+        self.resumed
+    }
+
+    // DER encoded certificates of the server, the leaf comes first
+    pub fn peer_certificates(&self) -> &[Vec<u8>] {
+        &self.peer_certificates
+    }
+
+    // parsed details of the server's certificates, the leaf comes first
+    pub fn peer_certificate_info(&self) -> Option<Vec<CertificateInfo>> {
+        chain_info(&self.peer_certificates)
+    }
+
+    // DER encoded OCSP response the server stapled, already verified
+    pub fn ocsp_response(&self) -> Option<&[u8]> {
+        self.ocsp_response.as_deref()
+    }
+
+    // SCTs verified against the CT policy, empty without a policy
+    pub fn verified_scts(&self) -> &[VerifiedSct] {
+        &self.verified_scts
+    }
+
+    // the protocol the server selected with ALPN
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    // capture the connection to a pcap file, see PcapRecorder
+    pub fn set_recorder(&mut self, recorder: PcapRecorder) {
+        self.common.set_recorder(recorder, true);
+    }
+
+    fn send_client_hello(&mut self) -> ClientState {
+        self.common.start_handshake();
+        let config = Arc::clone(&self.config);
+        // a session without the extended master secret cannot be resumed if it is required
+        let cached = config
+            .session_cache
+            .get(&self.server_name)
+            .filter(|session| session.extended_master_secret || !config.require_ems);
+        let mut client_hello = ClientHelloPayload::new()
+            .with_cipher_suites(config.cipher_suites.clone())
+            .with_extension(ClientExtension::NamedGroups(config.named_groups.clone()))
+            .with_extension(ClientExtension::SignatureAlgorithms(
+                config.signature_schemes.clone(),
+            ))
+            .with_renegotiation_info(self.client_verify_data.clone());
+        if config.enable_sni && self.server_name.parse::<IpAddr>().is_err() {
+            client_hello =
+                client_hello.with_extension(ClientExtension::ServerName(self.server_name.clone()));
+        }
+        if !config.alpn_protocols.is_empty() {
+            client_hello = client_hello
+                .with_extension(ClientExtension::Protocols(config.alpn_protocols.clone()));
+        }
+        if let Some(max_fragment_length) = config.max_fragment_length {
+            client_hello = client_hello
+                .with_extension(ClientExtension::MaxFragmentLength(max_fragment_length));
+        }
+        if let Some(session) = &cached {
+            client_hello = if session.ticket.is_empty() {
+                client_hello.with_session_id(session.session_id.clone())
+            } else {
+                client_hello
+                    .with_session_id(SessionId::random())
+                    .with_session_ticket(session.ticket.clone())
+            };
+        }
+        self.common.send_handshake(HandshakePayload::encode_message(
+            HandshakeType::ClientHello,
+            &client_hello.encode(),
+        ));
+        ClientState::ExpectServerHello(Box::new(ClientHello {
+            client_hello,
+            cached,
+        }))
+    }
+
+    fn process_messages(&mut self) -> Result<(), Error> {
+        while let Some(message) = self.common.next_message()? {
+            let state = mem::replace(&mut self.state, ClientState::Connected);
+            self.state = match message {
+                Message::Handshake(msg_type, body) => {
+                    self.handle_handshake(state, msg_type, body)?
+                }
+                Message::ChangeCipherSpec => self.handle_change_cipher_spec(state)?,
+            };
+        }
+        Ok(())
+    }
+
+    fn handle_handshake(
+        &mut self,
+        state: ClientState,
+        msg_type: HandshakeType,
+        body: Vec<u8>,
+    ) -> Result<ClientState, Error> {
+        match (state, msg_type) {
+            (state, HandshakeType::HelloRequest) => self.handle_hello_request(state, body),
+            (ClientState::ExpectServerHello(hello), HandshakeType::ServerHello) => {
+                self.handle_server_hello(*hello, body)
+            }
+            (ClientState::ExpectCertificate(handshake), HandshakeType::Certificate) => {
+                self.handle_certificate(handshake, body)
+            }
+            (ClientState::ExpectCertificateStatus(handshake), HandshakeType::CertificateStatus) => {
+                self.check_certificate_status(&handshake, body)?;
+                self.check_ct(&handshake)?;
+                Ok(ClientState::ExpectServerKeyExchange(handshake))
+            }
+            // CertificateStatus may be omitted even if the server acknowledged status_request
+            (ClientState::ExpectCertificateStatus(handshake), HandshakeType::ServerKeyExchange) => {
+                self.check_ct(&handshake)?;
+                self.handle_server_key_exchange(handshake, body)
+            }
+            (ClientState::ExpectServerKeyExchange(handshake), HandshakeType::ServerKeyExchange) => {
+                self.handle_server_key_exchange(handshake, body)
+            }
+            (
+                ClientState::ExpectCertificateRequest(handshake, server_key_exchange),
+                HandshakeType::CertificateRequest,
+            ) => {
+                let request = CertificateRequest::read(&body)?;
+                Ok(ClientState::ExpectServerHelloDone(
+                    handshake,
+                    server_key_exchange,
+                    request,
+                ))
+            }
+            (
+                ClientState::ExpectCertificateRequest(handshake, server_key_exchange),
+                HandshakeType::ServerHelloDone,
+            ) => self.handle_server_hello_done(handshake, server_key_exchange, None, body),
+            (
+                ClientState::ExpectServerHelloDone(handshake, server_key_exchange, request),
+                HandshakeType::ServerHelloDone,
+            ) => self.handle_server_hello_done(handshake, server_key_exchange, Some(request), body),
+            (
+                ClientState::ExpectNewSessionTicket(mut handshake, secrets),
+                HandshakeType::NewSessionTicket,
+            ) => {
+                let new_session_ticket = NewSessionTicket::read(&body)?;
+                handshake.ticket = Some(new_session_ticket.ticket().to_vec());
+                handshake.ticket_lifetime_hint = Some(new_session_ticket.ticket_lifetime_hint());
+                Ok(ClientState::ExpectChangeCipherSpec(handshake, secrets))
+            }
+            (ClientState::ExpectFinished(handshake, secrets), HandshakeType::Finished) => {
+                self.handle_finished(*handshake, secrets, body)
+            }
+            (state, msg_type) => Err(Error::UnexpectedMessage(format!(
+                "expected {}, got {:?}",
+                state.expected(),
+                msg_type
+            ))),
+        }
+    }
+
+    fn handle_change_cipher_spec(&mut self, state: ClientState) -> Result<ClientState, Error> {
+        match state {
+            ClientState::ExpectChangeCipherSpec(handshake, secrets) => {
+                let (_, server_cipher) = key_expansion(&secrets);
+                self.common.set_decrypter(server_cipher);
+                Ok(ClientState::ExpectFinished(handshake, secrets))
+            }
+            state => Err(Error::UnexpectedMessage(format!(
+                "expected {}, got ChangeCipherSpec",
+                state.expected()
+            ))),
+        }
+    }
+
+    fn handle_server_hello(
+        &mut self,
+        hello: ClientHello,
+        body: Vec<u8>,
+    ) -> Result<ClientState, Error> {
+        let ClientHello {
+            client_hello,
+            cached,
+        } = hello;
+        let config = Arc::clone(&self.config);
+        let server_hello = ServerHelloPayload::read(&body)?;
+        let version = server_hello.protocol_version();
+        if (version.major, version.minor) != (0x03, 0x03) {
+            return Err(Error::ProtocolVersion(String::from(
+                "server is not TLS 1.2",
+            )));
+        }
+        if server_hello.compression_method() != [0x00] {
+            return Err(Error::IllegalParameter(String::from(
+                "server selected a compression method",
+            )));
+        }
+        let cipher_suite = server_hello.cipher_suite();
+        let suite = match cipher_suite.params() {
+            Some(params) if client_hello.cipher_suites().contains(&cipher_suite) => params,
+            _ => {
+                return Err(Error::HandshakeFailure(String::from(
+                    "server selected a cipher suite that was not offered",
+                )))
+            }
+        };
+        self.check_renegotiation_info(&server_hello)?;
+        self.check_extensions(&server_hello)?;
+        let extended_master_secret = server_hello
+            .extensions()
+            .iter()
+            .any(|extension| matches!(extension, ServerExtension::ExtendedMasterSecretAck));
+        if config.require_ems && !extended_master_secret {
+            return Err(Error::HandshakeFailure(String::from(
+                "server does not support the extended master secret",
+            )));
+        }
+
+        let expect_ticket = server_hello
+            .extensions()
+            .iter()
+            .any(|extension| matches!(extension, ServerExtension::SessionTicketAck));
+        let expect_status = server_hello
+            .extensions()
+            .iter()
+            .any(|extension| matches!(extension, ServerExtension::CertificateStatusAck));
+        let tls_scts: Vec<SignedCertificateTimestamp> = server_hello
+            .extensions()
+            .iter()
+            .find_map(|extension| match extension {
+                ServerExtension::SignedCertificateTimestamp(scts) => Some(
+                    scts.iter()
+                        .filter_map(|sct| SignedCertificateTimestamp::read(sct))
+                        .collect(),
+                ),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let mut handshake = Box::new(ClientHandshake {
+            suite,
+            session_id: server_hello.session_id().clone(),
+            client_random: client_hello.random().encode(),
+            server_random: server_hello.random().encode(),
+            expect_ticket,
+            expect_status,
+            extended_master_secret,
+            tls_scts,
+            public_key: Vec::new(),
+            path: Vec::new(),
+            ticket: None,
+            ticket_lifetime_hint: None,
+        });
+
+        match cached {
+            // The server echoes the offered session_id when it agrees to resume the session.
+            Some(session)
+                if !server_hello.session_id().is_empty()
+                    && server_hello.session_id() == client_hello.session_id() =>
+            {
+                if session.cipher_suite != cipher_suite.get_encoding() {
+                    return Err(Error::IllegalParameter(String::from(
+                        "resumed session with a different cipher suite",
+                    )));
+                }
+                // Ref: https://tex2e.github.io/rfc-translater/html/rfc7627.html#5-3--Client-and-Server-Behavior--Abbreviated-Handshake
+                if session.extended_master_secret != extended_master_secret {
+                    return Err(Error::HandshakeFailure(String::from(
+                        "resumed session with a different extended_master_secret",
+                    )));
+                }
+                let secrets = MasterSecretInfo::resume(
+                    suite,
+                    session.master_secret,
+                    session.extended_master_secret,
+                    handshake.client_random.clone(),
+                    handshake.server_random.clone(),
+                );
+                self.log_master_secret(&secrets);
+                self.resumed = true;
+                self.peer_certificates = session.peer_certificates;
+                // keep using the old ticket unless the server issues a new one
+                handshake.ticket = Some(session.ticket);
+                Ok(expect_server_finished(handshake, secrets))
+            }
+            cached => {
+                if cached.is_some() {
+                    config.session_cache.remove(&self.server_name);
+                }
+                self.resumed = false;
+                Ok(ClientState::ExpectCertificate(handshake))
+            }
+        }
+    }
+
+    // ALPN and max_fragment_length in ServerHello must answer what the client offered
+    fn check_extensions(&mut self, server_hello: &ServerHelloPayload) -> Result<(), Error> {
+        self.alpn_protocol = None;
+        let mut max_fragment_len = MAX_FRAGMENT_LEN;
+        for extension in server_hello.extensions() {
+            match extension {
+                // Ref: https://tex2e.github.io/rfc-translater/html/rfc7301.html#3-2--Protocol-Selection
+                ServerExtension::Protocol(protocol) => {
+                    if !self.config.alpn_protocols.contains(protocol) {
+                        return Err(Error::IllegalParameter(String::from(
+                            "server selected an ALPN protocol that was not offered",
+                        )));
+                    }
+                    self.alpn_protocol = Some(protocol.clone());
+                }
+                // Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#4--Maximum-Fragment-Length-Negotiation
+                ServerExtension::MaxFragmentLength(max_fragment_length) => {
+                    match max_fragment_length.fragment_len() {
+                        Some(len)
+                            if self.config.max_fragment_length == Some(*max_fragment_length) =>
+                        {
+                            max_fragment_len = len
+                        }
+                        _ => {
+                            return Err(Error::IllegalParameter(String::from(
+                                "max_fragment_length differs from the requested length",
+                            )))
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.common.set_max_fragment_len(max_fragment_len);
+        Ok(())
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5746.html#3-4--Client-Behavior--Initial-Handshake
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5746.html#3-5--Client-Behavior--Secure-Renegotiation
+    fn check_renegotiation_info(&mut self, server_hello: &ServerHelloPayload) -> Result<(), Error> {
+        let renegotiated_connection =
+            server_hello
+                .extensions()
+                .iter()
+                .find_map(|extension| match extension {
+                    ServerExtension::RenegotiationInfo(info) => Some(info.clone()),
+                    _ => None,
+                });
+
+        if !self.common.is_established() {
+            match renegotiated_connection {
+                Some(info) if !info.is_empty() => Err(Error::HandshakeFailure(String::from(
+                    "renegotiated_connection must be empty on the initial handshake",
+                ))),
+                info => {
+                    self.secure_renegotiation = info.is_some();
+                    Ok(())
+                }
+            }
+        } else {
+            let expected = [
+                self.client_verify_data.as_slice(),
+                self.server_verify_data.as_slice(),
+            ]
+            .concat();
+            if renegotiated_connection != Some(expected) {
+                return Err(Error::HandshakeFailure(String::from(
+                    "renegotiation_info does not match the previous handshake",
+                )));
+            }
+            Ok(())
+        }
+    }
+
+    // Start a new handshake on the established connection. The ClientHello is queued,
+    // the handshake runs as the caller does the I/O.
+    pub fn renegotiate(&mut self) -> Result<(), Error> {
+        if !self.secure_renegotiation {
+            return Err(Error::Renegotiation(String::from(
+                "server does not support secure renegotiation",
+            )));
+        }
+        if self.common.is_handshaking() {
+            return Err(Error::Renegotiation(String::from(
+                "a handshake is in progress",
+            )));
+        }
+        self.state = self.send_client_hello();
+        Ok(())
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-1-1--Hello-Request
+    // HelloRequest is ignored while a handshake is in progress.
+    fn handle_hello_request(
+        &mut self,
+        state: ClientState,
+        body: Vec<u8>,
+    ) -> Result<ClientState, Error> {
+        if !body.is_empty() {
+            return Err(Error::Decode("HelloRequest"));
+        }
+        if !matches!(state, ClientState::Connected) {
+            return Ok(state);
+        }
+        if self.config.renegotiation_policy == RenegotiationPolicy::Renegotiate
+            && self.secure_renegotiation
+        {
+            return Ok(self.send_client_hello());
+        }
+        self.common
+            .send_alert(AlertLevel::Warning, AlertDescription::NoRenegotiation);
+        Ok(state)
+    }
+
+    fn handle_certificate(
+        &mut self,
+        mut handshake: Box<ClientHandshake>,
+        body: Vec<u8>,
+    ) -> Result<ClientState, Error> {
+        let config = Arc::clone(&self.config);
+        let certificate = Certificate::read(&body)?;
+        certificate.verify()?;
+        self.peer_certificates = certificate.certificates();
+        handshake.path = match &config.root_store {
+            Some(root_store) => {
+                let path = verify_chain(
+                    &self.peer_certificates,
+                    root_store,
+                    (config.time_provider)(),
+                )?;
+                verify_server_name(&self.peer_certificates[0], &self.server_name)?;
+                path
+            }
+            None => self.peer_certificates.clone(),
+        };
+        // build() requires root certificates for the CRL checker, so the path is verified
+        if let Some(crl_checker) = &config.crl_checker {
+            match crl_checker.check(&handshake.path, (config.time_provider)()) {
+                Ok(()) => {}
+                Err(e @ RevocationError::Revoked(_)) => {
+                    return Err(Error::Certificate(CertificateError::Revoked(e.to_string())))
+                }
+                Err(e @ RevocationError::UnknownStatus(_)) => {
+                    return Err(Error::Certificate(CertificateError::Unknown(e.to_string())))
+                }
+            }
+        }
+        if let Some(pins) = &config.pins {
+            // unverified certificates other than the leaf prove nothing
+            let pinned = match config.root_store {
+                Some(_) => &handshake.path[..],
+                None => &handshake.path[..1],
+            };
+            if let Err(e) = pins.check(&self.server_name, pinned) {
+                return Err(Error::Certificate(CertificateError::Bad(e.to_string())));
+            }
+        }
+        handshake.public_key = match certificate.public_key() {
+            Some(public_key) => public_key,
+            None => {
+                return Err(Error::Certificate(CertificateError::Unsupported(
+                    String::from("cannot read the certificate public key"),
+                )))
+            }
+        };
+
+        self.ocsp_response = None;
+        if handshake.expect_status {
+            return Ok(ClientState::ExpectCertificateStatus(handshake));
+        }
+        self.check_ct(&handshake)?;
+        Ok(ClientState::ExpectServerKeyExchange(handshake))
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#8--Certificate-Status-Request
+    // The issuer is the certificate after the leaf in the path built in handle_certificate.
+    fn check_certificate_status(
+        &mut self,
+        handshake: &ClientHandshake,
+        body: Vec<u8>,
+    ) -> Result<(), Error> {
+        let status = CertificateStatus::read(&body)?;
+        let Some(response) = OcspResponse::read(status.ocsp_response()) else {
+            return Err(Error::Certificate(CertificateError::BadStatusResponse(
+                String::from("malformed OCSP response"),
+            )));
+        };
+        let (Some(leaf), Some(issuer)) = (handshake.path.first(), handshake.path.get(1)) else {
+            return Err(Error::Certificate(CertificateError::BadStatusResponse(
+                String::from("no issuer certificate to check the OCSP response"),
+            )));
+        };
+        match response.verify(leaf, issuer, (self.config.time_provider)()) {
+            Ok(CertStatus::Revoked { .. }) => Err(Error::Certificate(CertificateError::Revoked(
+                String::from("the server certificate is revoked"),
+            ))),
+            // unknown only means the responder has no information about the certificate
+            Ok(CertStatus::Good | CertStatus::Unknown) => {
+                self.ocsp_response = Some(status.ocsp_response().to_vec());
+                Ok(())
+            }
+            Err(message) => Err(Error::Certificate(CertificateError::BadStatusResponse(
+                message.to_string(),
+            ))),
+        }
+    }
+
+    // SCTs may come from the TLS extension, the stapled OCSP response or the certificate
+    fn check_ct(&mut self, handshake: &ClientHandshake) -> Result<(), Error> {
+        self.verified_scts = Vec::new();
+        if let Some(ct_policy) = &self.config.ct_policy {
+            let ocsp_response = self.ocsp_response.as_deref().and_then(OcspResponse::read);
+            match ct_policy.check(
+                &handshake.path,
+                &handshake.tls_scts,
+                ocsp_response.as_ref(),
+                (self.config.time_provider)(),
+            ) {
+                Ok(verified_scts) => self.verified_scts = verified_scts,
+                Err(e) => return Err(Error::Certificate(CertificateError::Unknown(e.to_string()))),
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_server_key_exchange(
+        &mut self,
+        handshake: Box<ClientHandshake>,
+        body: Vec<u8>,
+    ) -> Result<ClientState, Error> {
+        let server_key_exchange = ServerKeyExchange::read(&body)?;
+        if !self
+            .config
+            .named_groups
+            .contains(&server_key_exchange.named_curve())
+            || !self
+                .config
+                .signature_schemes
+                .contains(&server_key_exchange.signature_algorithm())
+        {
+            return Err(Error::IllegalParameter(String::from(
+                "server used a named group or signature scheme that was not offered",
+            )));
+        }
+        if !server_key_exchange.verify(
+            &handshake.client_random,
+            &handshake.server_random,
+            &handshake.public_key,
+        ) {
+            return Err(Error::Crypto(String::from(
+                "invalid ServerKeyExchange signature",
+            )));
+        }
+        Ok(ClientState::ExpectCertificateRequest(
+            handshake,
+            server_key_exchange,
+        ))
+    }
+
+    // sends the client's flight of the full handshake
+    fn handle_server_hello_done(
+        &mut self,
+        handshake: Box<ClientHandshake>,
+        server_key_exchange: ServerKeyExchange,
+        certificate_request: Option<CertificateRequest>,
+        body: Vec<u8>,
+    ) -> Result<ClientState, Error> {
+        ServerHelloDone::read(&body)?;
+        let (client_public_key, pre_master_secret) = match server_key_exchange.generate_shared_key()
+        {
+            Some(shared_key) => shared_key,
+            None => {
+                return Err(Error::HandshakeFailure(String::from(
+                    "unsupported named curve or invalid server public key",
+                )))
+            }
+        };
+
+        // An empty Certificate tells the server we have no suitable certificate.
+        let client_auth = certificate_request
+            .as_ref()
+            .and_then(|request| self.choose_client_certificate(request));
+        if certificate_request.is_some() {
+            let chain = match &client_auth {
+                Some((certified_key, _)) => certified_key.cert_chain.clone(),
+                None => vec![],
+            };
+            self.common
+                .send_handshake(Certificate::new(&chain).encode());
+        }
+
+        self.common
+            .send_handshake(ClientKeyExchange::encode(client_public_key));
+        let suite = handshake.suite;
+        let client_random = handshake.client_random.clone();
+        let server_random = handshake.server_random.clone();
+        let secrets = if handshake.extended_master_secret {
+            MasterSecretInfo::extended(
+                suite,
+                pre_master_secret,
+                self.common.handshake_messages(),
+                client_random,
+                server_random,
+            )
+        } else {
+            MasterSecretInfo::new(suite, pre_master_secret, client_random, server_random)
+        };
+        self.log_master_secret(&secrets);
+
+        if let Some((certified_key, scheme)) = client_auth {
+            let signature = match certified_key
+                .key
+                .sign(scheme, self.common.handshake_messages())
+            {
+                Some(signature) => signature,
+                None => {
+                    return Err(Error::Internal(String::from(
+                        "failed to sign CertificateVerify",
+                    )))
+                }
+            };
+            self.common
+                .send_handshake(CertificateVerify::new(scheme, signature).encode());
+        }
+        let (client_cipher, _) = key_expansion(&secrets);
+
+        self.common.send_change_cipher_spec(client_cipher);
+        self.client_verify_data = self.common.send_finished(&secrets, b"client finished");
+        Ok(expect_server_finished(handshake, secrets))
+    }
+
+    // a certificate whose key type and signature scheme the server accepts,
+    // issued by one of the acceptable CAs if the server named any
+    fn choose_client_certificate(
+        &self,
+        request: &CertificateRequest,
+    ) -> Option<(CertifiedKey, SignatureScheme)> {
+        let schemes: Vec<SignatureScheme> = request
+            .supported_signature_algorithms()
+            .iter()
+            .copied()
+            .filter(|scheme| self.config.signature_schemes.contains(scheme))
+            .collect();
+        self.config
+            .client_certificates
+            .iter()
+            .find_map(|certified_key| {
+                if !request
+                    .certificate_types()
+                    .contains(&certified_key.key.certificate_type())
+                {
+                    return None;
+                }
+                let authorities = request.certificate_authorities();
+                if !authorities.is_empty()
+                    && !Certificate::new(&certified_key.cert_chain)
+                        .issuers()
+                        .iter()
+                        .any(|issuer| authorities.contains(issuer))
+                {
+                    return None;
+                }
+                let scheme = certified_key.key.choose_scheme(&schemes)?;
+                Some((certified_key.clone(), scheme))
+            })
+    }
+
+    fn log_master_secret(&self, secrets: &MasterSecretInfo) {
+        self.config.key_log.log(
+            "CLIENT_RANDOM",
+            &secrets.client_random,
+            &secrets.master_secret,
+        );
+    }
+
+    // The server's Finished completes a full handshake. In an abbreviated handshake
+    // the client's ChangeCipherSpec and Finished follow.
+    fn handle_finished(
+        &mut self,
+        handshake: ClientHandshake,
+        secrets: MasterSecretInfo,
+        body: Vec<u8>,
+    ) -> Result<ClientState, Error> {
+        self.server_verify_data = self
+            .common
+            .check_finished(&secrets, b"server finished", body)?;
+        if self.resumed {
+            let (client_cipher, _) = key_expansion(&secrets);
+            self.common.send_change_cipher_spec(client_cipher);
+            self.client_verify_data = self.common.send_finished(&secrets, b"client finished");
+        }
+
+        // A server that cannot issue a ticket sends an empty one. A resumed session keeps
+        // its expiry unless the server issued a new ticket.
+        let ticket = handshake.ticket.unwrap_or_default();
+        let new_ticket = handshake.ticket_lifetime_hint.is_some();
+        if (!self.resumed || new_ticket) && (!handshake.session_id.is_empty() || !ticket.is_empty())
+        {
+            self.config.session_cache.put(
+                &self.server_name,
+                ClientSessionValue {
+                    session_id: handshake.session_id,
+                    master_secret: secrets.master_secret.clone(),
+                    cipher_suite: handshake.suite.suite.get_encoding(),
+                    peer_certificates: self.peer_certificates.clone(),
+                    ticket,
+                    extended_master_secret: secrets.is_extended(),
+                },
+                handshake.ticket_lifetime_hint.unwrap_or(0),
+            );
+        }
+        self.common.handshake_done();
+        Ok(ClientState::Connected)
+    }
+}
+
+// NewSessionTicket comes before the server's ChangeCipherSpec if it was announced
+fn expect_server_finished(
+    handshake: Box<ClientHandshake>,
+    secrets: MasterSecretInfo,
+) -> ClientState {
+    if handshake.expect_ticket {
+        ClientState::ExpectNewSessionTicket(handshake, secrets)
+    } else {
+        ClientState::ExpectChangeCipherSpec(handshake, secrets)
+    }
+}
+
+impl Connection for ClientConnection {
+    fn common(&self) -> &ConnectionCommon {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut ConnectionCommon {
+        &mut self.common
+    }
+
+    fn process_new_packets(&mut self) -> Result<(), Error> {
+        self.common.check_failed()?;
+        let result = self.process_messages();
+        self.common.abort_on_error(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::load_private_key;
+    use crate::server::{ServerConfig, ServerConnection};
+    use crate::sign::CertifiedKey;
+
+    const LEAF: &[u8] = include_bytes!("../testdata/ct/leaf.der");
+    const LEAF_KEY: &[u8] = include_bytes!("../testdata/ct/leaf.key");
+
+    fn transfer(from: &mut dyn Connection, to: &mut dyn Connection) -> Result<(), Error> {
+        let mut buf = Vec::new();
+        while from.wants_write() {
+            from.write_tls(&mut buf)?;
+        }
+        let mut rd = &buf[..];
+        while !rd.is_empty() {
+            to.read_tls(&mut rd)?;
+            to.process_new_packets()?;
+        }
+        Ok(())
+    }
+
+    // after a handshake with secure renegotiation on both sides
+    fn connected() -> (ClientConnection, ServerConnection) {
+        let server_config = ServerConfig::builder()
+            .with_single_cert(CertifiedKey::new(
+                vec![LEAF.to_vec()],
+                load_private_key(LEAF_KEY).unwrap(),
+            ))
+            .with_secure_renegotiation(true)
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let client_config = ClientConfig::builder()
+            .with_no_certificate_verification()
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let mut client = ClientConnection::new(client_config, "localhost");
+        let mut server = ServerConnection::new(server_config);
+        while client.is_handshaking() || server.is_handshaking() {
+            transfer(&mut client, &mut server).unwrap();
+            transfer(&mut server, &mut client).unwrap();
+        }
+        assert!(client.secure_renegotiation);
+        (client, server)
+    }
+
+    #[test]
+    fn renegotiation_without_the_right_binding_fails() {
+        // the server's renegotiation_info does not match the verify_data the client has
+        let (mut client, mut server) = connected();
+        client.server_verify_data[0] ^= 0x01;
+        client.renegotiate().unwrap();
+        transfer(&mut client, &mut server).unwrap();
+        assert!(matches!(
+            transfer(&mut server, &mut client),
+            Err(Error::HandshakeFailure(_))
+        ));
+        assert!(matches!(
+            transfer(&mut client, &mut server),
+            Err(Error::AlertReceived(AlertDescription::HandshakeFailure))
+        ));
+
+        // the ClientHello lacks the binding, renegotiated_connection is empty
+        let (mut client, mut server) = connected();
+        client.client_verify_data.clear();
+        client.renegotiate().unwrap();
+        assert!(matches!(
+            transfer(&mut client, &mut server),
+            Err(Error::HandshakeFailure(_))
+        ));
+        assert!(matches!(
+            transfer(&mut server, &mut client),
+            Err(Error::AlertReceived(AlertDescription::HandshakeFailure))
+        ));
+    }
+}
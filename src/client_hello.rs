@@ -1,11 +1,15 @@
+use crate::cipher_suite::{CipherSuite, DEFAULT_CIPHER_SUITES};
+use crate::codec::{
+    decode_all, encode_opaque, encode_vec, read_opaque, read_vec, Codec, LengthPrefix, Reader,
+};
+use crate::enums::{ECPointFormat, MaxFragmentLength, NamedGroup, SignatureScheme};
+use crate::error::Error;
+use crate::extensions::{CertificateStatusRequest, ClientExtension};
 use crate::handshake::{Random, SessionId};
+use crate::key_exchange::SUPPORTED_GROUPS;
 use crate::protocol_version::ProtocolVersion;
-use byteorder::{BigEndian, ByteOrder};
-use chrono::{DateTime, Utc};
-use rustls::internal::msgs::codec::Codec;
-use rustls::internal::msgs::enums::ECPointFormat::{ANSIX962CompressedPrime, Uncompressed};
-use rustls::internal::msgs::handshake::ClientExtension;
-use rustls::{NamedGroup, SignatureScheme};
+use crate::sign::SUPPORTED_SIGNATURE_SCHEMES;
+use std::net::IpAddr;
 
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-1-2--Client-Hello
 // struct {
@@ -21,11 +25,12 @@ use rustls::{NamedGroup, SignatureScheme};
 //             Extension extensions<0..2^16-1>;
 //     };
 // } ClientHello;
+
 pub struct ClientHelloPayload {
     client_hello: ProtocolVersion,
     random: Random,
     session_id: SessionId,
-    cipher_suites: Vec<u8>,
+    cipher_suites: Vec<CipherSuite>,
     compression_methods: Vec<u8>,
     extensions: Vec<ClientExtension>,
 }
@@ -38,68 +43,323 @@ impl ClientHelloPayload {
                 major: 0x03,
                 minor: 0x03,
             },
-            random: Random {
-                gmt_unix_time: Utc::now(),
-                random_bytes: vec![0; 28],
-            },
-            session_id: SessionId {
-                len: 0,
-                data: [0; 32],
-            },
-            // TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384
-            cipher_suites: vec![0xc0, 0x30],
+            random: Random::new(),
+            session_id: SessionId::empty(),
+            cipher_suites: DEFAULT_CIPHER_SUITES.to_vec(),
             compression_methods: vec![0; 1],
             // I referred to the extension when connecting with openssl
             // done command is `openssl s_client -connect 127.0.0.1:1337 -tls1_2 < /dev/null`
             extensions: vec![
-                // ec_point_formats
-                ClientExtension::ECPointFormats(vec![Uncompressed, ANSIX962CompressedPrime]),
+                // ec_point_formats: uncompressed, ansiX962_compressed_prime
+                ClientExtension::ECPointFormats(vec![
+                    ECPointFormat::uncompressed,
+                    ECPointFormat::ansiX962_compressed_prime,
+                ]),
                 // signature_algorithms
-                ClientExtension::SignatureAlgorithms(
-                    // Ref: https://github.com/rustls/rustls/blob/main/rustls/src/verify.rs#L420
-                    vec![
-                        SignatureScheme::ECDSA_NISTP384_SHA384,
-                        SignatureScheme::ECDSA_NISTP256_SHA256,
-                        SignatureScheme::ED25519,
-                        SignatureScheme::RSA_PSS_SHA512,
-                        SignatureScheme::RSA_PSS_SHA384,
-                        SignatureScheme::RSA_PSS_SHA256,
-                        SignatureScheme::RSA_PKCS1_SHA512,
-                        SignatureScheme::RSA_PKCS1_SHA384,
-                        SignatureScheme::RSA_PKCS1_SHA256,
-                    ],
-                ),
+                ClientExtension::SignatureAlgorithms(SUPPORTED_SIGNATURE_SCHEMES.to_vec()),
                 // supported_groups(elliptic_curves)
-                ClientExtension::NamedGroups(vec![NamedGroup::X25519, NamedGroup::secp521r1]),
+                ClientExtension::NamedGroups(SUPPORTED_GROUPS.to_vec()),
+                // SessionTicket TLS (RFC 5077), empty to request a new ticket
+                ClientExtension::SessionTicket(vec![]),
+                // status_request (RFC 6066), ask the server to staple an OCSP response
+                ClientExtension::StatusRequest(CertificateStatusRequest::ocsp()),
+                // signed_certificate_timestamp (RFC 6962), empty in ClientHello
+                ClientExtension::SignedCertificateTimestampRequest,
+                // renegotiation_info (RFC 5746), empty on the initial handshake
+                ClientExtension::RenegotiationInfo(vec![]),
+                // extended_master_secret (RFC 7627)
+                ClientExtension::ExtendedMasterSecretRequest,
             ],
         }
     }
 
+    // offer a cached session to the server for an abbreviated handshake
+    pub fn with_session_id(mut self, session_id: SessionId) -> Self {
+        self.session_id = session_id;
+        self
+    }
+
+    // offer a session ticket. The session_id should be set too, the server echoes it on resumption.
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5077.html#3-4--Interaction-with-TLS-Session-ID
+    pub fn with_session_ticket(self, ticket: Vec<u8>) -> Self {
+        self.with_extension(ClientExtension::SessionTicket(ticket))
+    }
+
+    // on renegotiation renegotiated_connection is the client_verify_data of the previous handshake
+    pub fn with_renegotiation_info(self, renegotiated_connection: Vec<u8>) -> Self {
+        self.with_extension(ClientExtension::RenegotiationInfo(renegotiated_connection))
+    }
+
+    // cipher suites in order of preference, replacing DEFAULT_CIPHER_SUITES
+    pub fn with_cipher_suites(mut self, cipher_suites: Vec<CipherSuite>) -> Self {
+        self.cipher_suites = cipher_suites;
+        self
+    }
+
+    // replace the extension of the same type, or add it
+    pub fn with_extension(mut self, extension: ClientExtension) -> Self {
+        let extension_type = extension.extension_type();
+        match self
+            .extensions
+            .iter_mut()
+            .find(|existing| existing.extension_type() == extension_type)
+        {
+            Some(existing) => *existing = extension,
+            None => self.extensions.push(extension),
+        }
+        self
+    }
+
+    // Every length is checked against the enclosing structure.
+    // Returns DecodeError if the message is truncated, has trailing bytes, contains
+    // an extension more than once or an extension body that does not parse.
+    pub fn read(buf: &[u8]) -> Result<Self, Error> {
+        decode_all(buf).ok_or(Error::Decode("ClientHello"))
+    }
+
+    pub fn client_version(&self) -> &ProtocolVersion {
+        &self.client_hello
+    }
+
+    pub fn random(&self) -> &Random {
+        &self.random
+    }
+
+    pub fn session_id(&self) -> &SessionId {
+        &self.session_id
+    }
+
+    pub fn cipher_suites(&self) -> &[CipherSuite] {
+        &self.cipher_suites
+    }
+
+    pub fn compression_methods(&self) -> &[u8] {
+        &self.compression_methods
+    }
+
+    // supported_groups, empty if the extension is absent
+    pub fn named_groups(&self) -> Vec<NamedGroup> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                ClientExtension::NamedGroups(groups) => Some(groups.clone()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    // signature_algorithms. Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-1-4-1--Signature-Algorithms
+    // If the client does not send the extension the server assumes {sha1,rsa}, which this crate does not support.
+    pub fn signature_algorithms(&self) -> Vec<SignatureScheme> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                ClientExtension::SignatureAlgorithms(schemes) => Some(schemes.clone()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    // None if the client did not send the SessionTicket extension, an empty ticket requests a new one
+    pub fn session_ticket(&self) -> Option<&[u8]> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                ClientExtension::SessionTicket(ticket) => Some(&ticket[..]),
+                _ => None,
+            })
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#3--Server-Name-Indication
+    // the host_name of the server_name extension, None for IP addresses
+    pub fn server_name(&self) -> Option<String> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                ClientExtension::ServerName(host_name) => Some(host_name.clone()),
+                _ => None,
+            })
+            .filter(|host_name| host_name.parse::<IpAddr>().is_err())
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc7301.html#3-1--The-Application-Layer-Protocol-Negotiation-Extension
+    // ProtocolName protocol_name_list<2..2^16-1>, in the client's order of preference
+    pub fn alpn_protocols(&self) -> Vec<Vec<u8>> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                ClientExtension::Protocols(protocols) => Some(protocols.clone()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc7627.html#5-1--Extension-Definition
+    pub fn extended_master_secret(&self) -> bool {
+        self.extensions
+            .iter()
+            .any(|extension| matches!(extension, ClientExtension::ExtendedMasterSecretRequest))
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#4--Maximum-Fragment-Length-Negotiation
+    pub fn max_fragment_length(&self) -> Option<MaxFragmentLength> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                ClientExtension::MaxFragmentLength(max_fragment_length) => {
+                    Some(*max_fragment_length)
+                }
+                _ => None,
+            })
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#8--Certificate-Status-Request
+    // true if the client asked for a stapled OCSP response
+    pub fn status_request(&self) -> bool {
+        self.extensions.iter().any(|extension| {
+            matches!(
+                extension,
+                ClientExtension::StatusRequest(CertificateStatusRequest::Ocsp { .. })
+            )
+        })
+    }
+
+    pub fn extensions(&self) -> &[ClientExtension] {
+        &self.extensions
+    }
+
+    // renegotiated_connection of the renegotiation_info extension.
+    // TLS_EMPTY_RENEGOTIATION_INFO_SCSV counts as an empty renegotiation_info.
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5746.html#3-6--Server-Behavior--Initial-Handshake
+    pub fn renegotiation_info(&self) -> Option<Vec<u8>> {
+        let renegotiation_info = self
+            .extensions
+            .iter()
+            .find_map(|extension| match extension {
+                ClientExtension::RenegotiationInfo(renegotiated_connection) => {
+                    Some(renegotiated_connection.clone())
+                }
+                _ => None,
+            });
+        if renegotiation_info.is_none()
+            && self
+                .cipher_suites()
+                .contains(&CipherSuite::TLS_EMPTY_RENEGOTIATION_INFO_SCSV)
+        {
+            return Some(vec![]);
+        }
+        renegotiation_info
+    }
+
     // encode ClientHello type to bytes
     pub fn encode(&self) -> Vec<u8> {
-        let mut buf = Vec::new();
-        buf.push(self.client_hello.major);
-        buf.push(self.client_hello.minor);
-        let gmt_unix_time = &self.random.gmt_unix_time;
-        let mut gmt_unix_time_buf = [0; 4];
-        BigEndian::write_u32(&mut gmt_unix_time_buf, gmt_unix_time.timestamp() as u32);
-        buf.extend_from_slice(gmt_unix_time_buf.as_ref());
-        buf.extend_from_slice(&self.random.random_bytes);
-        buf.push(self.session_id.len as u8);
-        // cipher_suites length to Vec<u8> size 2(u16)
-        let cipher_suites_len: Vec<u8> = (2 as u16).to_be_bytes()[..2].to_vec();
-        buf.extend(&cipher_suites_len);
-        buf.extend_from_slice(&self.cipher_suites);
-        // compression length
-        buf.push(self.compression_methods.len() as u8);
-        buf.extend_from_slice(&self.compression_methods);
-        let extensions = &self.extensions;
-        // extensions length to Vec<u8> size 2(u16)
-        buf.push(0x00);
-        buf.push(0x29);
-        for extension in extensions {
-            buf.extend_from_slice(&extension.get_encoding());
+        self.get_encoding()
+    }
+}
+
+impl Default for ClientHelloPayload {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Codec for ClientHelloPayload {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        self.client_hello.encode_into(bytes);
+        self.random.encode_into(bytes);
+        self.session_id.encode_into(bytes);
+        encode_vec(bytes, LengthPrefix::U16, &self.cipher_suites);
+        encode_opaque(bytes, LengthPrefix::U8, &self.compression_methods);
+        encode_vec(bytes, LengthPrefix::U16, &self.extensions);
+    }
+
+    // Every length is checked against the enclosing structure.
+    // None if the message is truncated, contains an extension more than once or an
+    // extension body that does not parse.
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let client_hello = ProtocolVersion::decode(r)?;
+        let random = Random::decode(r)?;
+        let session_id = SessionId::decode(r)?;
+        // CipherSuite cipher_suites<2..2^16-2>;
+        let cipher_suites: Vec<CipherSuite> = read_vec(r, LengthPrefix::U16)?;
+        if cipher_suites.is_empty() {
+            return None;
+        }
+        // CompressionMethod compression_methods<1..2^8-1>;
+        let compression_methods = read_opaque(r, LengthPrefix::U8)?.to_vec();
+        if compression_methods.is_empty() {
+            return None;
+        }
+        // extensions are present only if there are bytes left after compression_methods
+        let extensions: Vec<ClientExtension> = if r.any_left() {
+            read_vec(r, LengthPrefix::U16)?
+        } else {
+            vec![]
+        };
+
+        // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-1-4--Hello-Extensions
+        // There MUST NOT be more than one extension of the same type.
+        let mut types = Vec::new();
+        for extension in &extensions {
+            let typ = extension.extension_type();
+            if types.contains(&typ) {
+                return None;
+            }
+            types.push(typ);
+        }
+
+        Some(ClientHelloPayload {
+            client_hello,
+            random,
+            session_id,
+            cipher_suites,
+            compression_methods,
+            extensions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::AlertDescription;
+
+    #[test]
+    fn read_round_trip() {
+        let encoded = ClientHelloPayload::new()
+            .with_session_id(SessionId::random())
+            .with_session_ticket(vec![0x42; 16])
+            .encode();
+        let client_hello = ClientHelloPayload::read(&encoded).unwrap();
+        assert_eq!(client_hello.encode(), encoded);
+        assert_eq!(client_hello.cipher_suites(), DEFAULT_CIPHER_SUITES);
+        assert_eq!(client_hello.session_ticket(), Some(&[0x42; 16][..]));
+        assert_eq!(client_hello.renegotiation_info(), Some(vec![]));
+        assert_eq!(client_hello.named_groups(), SUPPORTED_GROUPS);
+
+        // truncated anywhere but right before the extensions block, or with a trailing byte
+        // version(2) + random(32) + session_id(1+32) + cipher_suites(2+8) + compression_methods(1+1)
+        let extensions_start = 2 + 32 + 1 + 32 + 2 + 8 + 1 + 1;
+        for len in (0..encoded.len()).filter(|len| *len != extensions_start) {
+            assert!(ClientHelloPayload::read(&encoded[..len]).is_err());
         }
-        buf
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert_eq!(
+            ClientHelloPayload::read(&trailing)
+                .err()
+                .and_then(|e| e.alert()),
+            Some(AlertDescription::DecodeError)
+        );
+    }
+
+    #[test]
+    fn reject_duplicate_extensions() {
+        let mut client_hello = ClientHelloPayload::new();
+        client_hello
+            .extensions
+            .push(ClientExtension::RenegotiationInfo(vec![]));
+        let encoded = client_hello.encode();
+        assert!(ClientHelloPayload::read(&encoded).is_err());
     }
 }
@@ -18,38 +18,56 @@
 //     case explicit: opaque dh_Yc<1..2^16-1>;
 //     } dh_public;
 // } ClientDiffieHellmanPublic;
+//
+// For ECDHE the public value is an ECPoint (RFC 8422 5.7):
+// struct {
+//     opaque point <1..2^8-1>;
+// } ECPoint;
 
-use byteorder::{BigEndian, ByteOrder};
+use crate::codec::{decode_all, encode_opaque, read_opaque, Codec, LengthPrefix, Reader};
 use crate::enums::HandshakeType;
+use crate::error::Error;
+use crate::handshake::HandshakePayload;
 
-#[derive(Debug)]
 pub struct ClientKeyExchange {
-    handshake_type: HandshakeType,
-    length: u32,
     pubkey: Vec<u8>,
 }
 
 impl ClientKeyExchange {
-    // pub fn new(buffer: Vec<u8>, len: u32) -> ClientKeyExchange {
-    //     let handshake_type = HandshakeType::ClientKeyExchange;
-    //     let length = len;
-    //     let pubkey = buffer[3..].to_vec();
-    //
-    //     ClientKeyExchange {
-    //         handshake_type,
-    //         length,
-    //         pubkey,
-    //     }
-    // }
+    pub fn read(buf: &[u8]) -> Result<ClientKeyExchange, Error> {
+        decode_all(buf).ok_or(Error::Decode("ClientKeyExchange"))
+    }
 
+    // the client's ECDH public key
+    pub fn pubkey(&self) -> &[u8] {
+        &self.pubkey
+    }
+
+    // encode ClientKeyExchange handshake message (with header) carrying the client's ECDH public key
     pub fn encode(client_pub_key: Vec<u8>) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        buffer.push(0x10);
-        let key_len = client_pub_key.len() as u32;
-        let key_buf = [0 as u8; 4];
-        BigEndian::write_u32(&mut buffer, key_len);
-        buffer.extend_from_slice(&key_buf[1..]);
-        buffer.extend_from_slice(&client_pub_key);
-        buffer
+        let client_key_exchange = ClientKeyExchange {
+            pubkey: client_pub_key,
+        };
+        HandshakePayload::encode_message(
+            HandshakeType::ClientKeyExchange,
+            &client_key_exchange.get_encoding(),
+        )
     }
-}
\ No newline at end of file
+}
+
+impl Codec for ClientKeyExchange {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        encode_opaque(bytes, LengthPrefix::U8, &self.pubkey);
+    }
+
+    // opaque point <1..2^8-1>;
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let pubkey = read_opaque(r, LengthPrefix::U8)?;
+        if pubkey.is_empty() {
+            return None;
+        }
+        Some(ClientKeyExchange {
+            pubkey: pubkey.to_vec(),
+        })
+    }
+}
@@ -0,0 +1,271 @@
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#4--Presentation-Language
+// Encoding and decoding of the TLS presentation language: big-endian integers and
+// vectors with a length prefix of 1, 2 or 3 bytes.
+//
+// Decoding never reads past the end of the input. A value that does not fit into the
+// remaining bytes, or a vector whose length does not match its elements, is None.
+
+pub trait Codec: Sized {
+    // append the encoding of self to bytes
+    fn encode_into(&self, bytes: &mut Vec<u8>);
+
+    // read one value, None if the input is truncated or malformed
+    fn decode(r: &mut Reader) -> Option<Self>;
+
+    fn get_encoding(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.encode_into(&mut bytes);
+        bytes
+    }
+}
+
+// decode a value that must use all of buf, e.g. a handshake message body
+pub fn decode_all<T: Codec>(buf: &[u8]) -> Option<T> {
+    let mut r = Reader::new(buf);
+    let value = T::decode(&mut r)?;
+    if r.any_left() {
+        return None;
+    }
+    Some(value)
+}
+
+// A cursor over the bytes being decoded.
+pub struct Reader<'a> {
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Reader { buf, offset: 0 }
+    }
+
+    // the next len bytes, None if fewer are left
+    pub fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.offset.checked_add(len)?;
+        let bytes = self.buf.get(self.offset..end)?;
+        self.offset = end;
+        Some(bytes)
+    }
+
+    // a reader over the next len bytes, e.g. the contents of a vector
+    pub fn sub(&mut self, len: usize) -> Option<Reader<'a>> {
+        self.take(len).map(Reader::new)
+    }
+
+    pub fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.buf[self.offset..];
+        self.offset = self.buf.len();
+        rest
+    }
+
+    pub fn any_left(&self) -> bool {
+        self.offset < self.buf.len()
+    }
+
+    pub fn left(&self) -> usize {
+        self.buf.len() - self.offset
+    }
+}
+
+impl Codec for u8 {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.push(*self);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(r.take(1)?[0])
+    }
+}
+
+impl Codec for u16 {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend(self.to_be_bytes());
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(u16::from_be_bytes(r.take(2)?.try_into().ok()?))
+    }
+}
+
+// uint24, e.g. the length of a handshake message or of an ASN.1Cert
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct U24(pub u32);
+
+impl Codec for U24 {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend(&self.0.to_be_bytes()[1..]);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let [a, b, c] = r.take(3)?.try_into().ok()?;
+        Some(U24(u32::from_be_bytes([0, a, b, c])))
+    }
+}
+
+impl Codec for u32 {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend(self.to_be_bytes());
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(u32::from_be_bytes(r.take(4)?.try_into().ok()?))
+    }
+}
+
+impl Codec for u64 {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend(self.to_be_bytes());
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(u64::from_be_bytes(r.take(8)?.try_into().ok()?))
+    }
+}
+
+// two byte values such as CipherSuite, NamedCurve or SignatureAndHashAlgorithm
+impl Codec for [u8; 2] {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend(self);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        r.take(2)?.try_into().ok()
+    }
+}
+
+// Size of the length in front of a vector: <..2^8-1>, <..2^16-1> or <..2^24-1>.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LengthPrefix {
+    U8,
+    U16,
+    U24,
+}
+
+impl LengthPrefix {
+    fn encode_len(self, len: usize, bytes: &mut Vec<u8>) {
+        match self {
+            LengthPrefix::U8 => (len as u8).encode_into(bytes),
+            LengthPrefix::U16 => (len as u16).encode_into(bytes),
+            LengthPrefix::U24 => U24(len as u32).encode_into(bytes),
+        }
+    }
+
+    fn decode_len(self, r: &mut Reader) -> Option<usize> {
+        Some(match self {
+            LengthPrefix::U8 => u8::decode(r)? as usize,
+            LengthPrefix::U16 => u16::decode(r)? as usize,
+            LengthPrefix::U24 => U24::decode(r)?.0 as usize,
+        })
+    }
+}
+
+// opaque data<..>
+pub fn encode_opaque(bytes: &mut Vec<u8>, prefix: LengthPrefix, data: &[u8]) {
+    prefix.encode_len(data.len(), bytes);
+    bytes.extend_from_slice(data);
+}
+
+pub fn read_opaque<'a>(r: &mut Reader<'a>, prefix: LengthPrefix) -> Option<&'a [u8]> {
+    let len = prefix.decode_len(r)?;
+    r.take(len)
+}
+
+// T items<..>, the length counts bytes, not items
+pub fn encode_vec<T: Codec>(bytes: &mut Vec<u8>, prefix: LengthPrefix, items: &[T]) {
+    let mut encoded = Vec::new();
+    for item in items {
+        item.encode_into(&mut encoded);
+    }
+    encode_opaque(bytes, prefix, &encoded);
+}
+
+// the items must fill the vector exactly
+pub fn read_vec<T: Codec>(r: &mut Reader, prefix: LengthPrefix) -> Option<Vec<T>> {
+    let len = prefix.decode_len(r)?;
+    let mut sub = r.sub(len)?;
+    let mut items = Vec::new();
+    while sub.any_left() {
+        items.push(T::decode(&mut sub)?);
+    }
+    Some(items)
+}
+
+// opaque items<..> of opaque elements, e.g. a list of DistinguishedNames
+pub fn encode_opaque_vec(
+    bytes: &mut Vec<u8>,
+    prefix: LengthPrefix,
+    element_prefix: LengthPrefix,
+    items: &[Vec<u8>],
+) {
+    let mut encoded = Vec::new();
+    for item in items {
+        encode_opaque(&mut encoded, element_prefix, item);
+    }
+    encode_opaque(bytes, prefix, &encoded);
+}
+
+pub fn read_opaque_vec(
+    r: &mut Reader,
+    prefix: LengthPrefix,
+    element_prefix: LengthPrefix,
+) -> Option<Vec<Vec<u8>>> {
+    let len = prefix.decode_len(r)?;
+    let mut sub = r.sub(len)?;
+    let mut items = Vec::new();
+    while sub.any_left() {
+        items.push(read_opaque(&mut sub, element_prefix)?.to_vec());
+    }
+    Some(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_and_vectors() {
+        let mut bytes = Vec::new();
+        0x01u8.encode_into(&mut bytes);
+        0x0203u16.encode_into(&mut bytes);
+        U24(0x040506).encode_into(&mut bytes);
+        0x0708090au32.encode_into(&mut bytes);
+        encode_vec(&mut bytes, LengthPrefix::U16, &[[0xc0, 0x2c], [0xc0, 0x30]]);
+        encode_opaque_vec(
+            &mut bytes,
+            LengthPrefix::U8,
+            LengthPrefix::U8,
+            &[b"h2".to_vec()],
+        );
+        assert_eq!(
+            bytes,
+            [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 0, 4, 0xc0, 0x2c, 0xc0, 0x30, 3, 2, b'h', b'2']
+        );
+
+        let mut r = Reader::new(&bytes);
+        assert_eq!(u8::decode(&mut r), Some(0x01));
+        assert_eq!(u16::decode(&mut r), Some(0x0203));
+        assert_eq!(U24::decode(&mut r), Some(U24(0x040506)));
+        assert_eq!(u32::decode(&mut r), Some(0x0708090a));
+        assert_eq!(
+            read_vec::<[u8; 2]>(&mut r, LengthPrefix::U16),
+            Some(vec![[0xc0, 0x2c], [0xc0, 0x30]])
+        );
+        assert_eq!(
+            read_opaque_vec(&mut r, LengthPrefix::U8, LengthPrefix::U8),
+            Some(vec![b"h2".to_vec()])
+        );
+        assert!(!r.any_left());
+
+        // a vector longer than the input, or with a partial element
+        assert_eq!(
+            read_opaque(&mut Reader::new(&[0, 3, 1, 2]), LengthPrefix::U16),
+            None
+        );
+        assert_eq!(
+            read_vec::<u16>(&mut Reader::new(&[3, 0, 1, 2]), LengthPrefix::U8),
+            None
+        );
+        assert_eq!(decode_all::<u16>(&[0, 1, 2]), None);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::cipher_suite::{Authentication, CipherSuite};
+use crate::enums::{MaxFragmentLength, NamedGroup, SignatureAlgorithm, SignatureScheme};
+use crate::error::ConfigError;
+use crate::key_exchange::SUPPORTED_GROUPS;
+use crate::sign::SUPPORTED_SIGNATURE_SCHEMES;
+
+// Checks shared by ClientConfigBuilder::build and ServerConfigBuilder::build.
+
+// cipher_suites are in order of preference. Every suite must be implemented and
+// its authentication must be possible with one of the signature schemes.
+pub fn check_cipher_suites(
+    cipher_suites: &[CipherSuite],
+    signature_schemes: &[SignatureScheme],
+) -> Result<(), ConfigError> {
+    if cipher_suites.is_empty() {
+        return Err(ConfigError::new("no cipher suites"));
+    }
+    for (i, suite) in cipher_suites.iter().enumerate() {
+        let Some(params) = suite.params().filter(|params| params.implemented) else {
+            return Err(ConfigError::new(format!(
+                "cipher suite {:?} is not implemented",
+                suite
+            )));
+        };
+        if cipher_suites[..i].contains(suite) {
+            return Err(ConfigError::new(format!(
+                "cipher suite {:?} is listed twice",
+                suite
+            )));
+        }
+        if !signature_schemes
+            .iter()
+            .any(|scheme| authenticates(params.authentication, *scheme))
+        {
+            return Err(ConfigError::new(format!(
+                "no signature scheme for the authentication of {:?}",
+                suite
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Ed25519 certificates are used with the ECDSA suites (RFC 8422)
+fn authenticates(authentication: Authentication, scheme: SignatureScheme) -> bool {
+    let signature = scheme.hash_and_signature().signature;
+    match authentication {
+        Authentication::Rsa => signature == SignatureAlgorithm::rsa,
+        Authentication::Ecdsa => matches!(
+            signature,
+            SignatureAlgorithm::ecdsa | SignatureAlgorithm::ed25519
+        ),
+        _ => false,
+    }
+}
+
+pub fn check_named_groups(named_groups: &[NamedGroup]) -> Result<(), ConfigError> {
+    if named_groups.is_empty() {
+        return Err(ConfigError::new("no named groups"));
+    }
+    match named_groups
+        .iter()
+        .find(|group| !SUPPORTED_GROUPS.contains(group))
+    {
+        Some(group) => Err(ConfigError::new(format!(
+            "named group {:?} is not supported",
+            group
+        ))),
+        None => Ok(()),
+    }
+}
+
+pub fn check_signature_schemes(signature_schemes: &[SignatureScheme]) -> Result<(), ConfigError> {
+    if signature_schemes.is_empty() {
+        return Err(ConfigError::new("no signature schemes"));
+    }
+    match signature_schemes
+        .iter()
+        .find(|scheme| !SUPPORTED_SIGNATURE_SCHEMES.contains(scheme))
+    {
+        Some(scheme) => Err(ConfigError::new(format!(
+            "signature scheme {:?} is not supported",
+            scheme
+        ))),
+        None => Ok(()),
+    }
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc7301.html#3-1--The-Application-Layer-Protocol-Negotiation-Extension
+// opaque ProtocolName<1..2^8-1>;
+pub fn check_alpn_protocols(alpn_protocols: &[Vec<u8>]) -> Result<(), ConfigError> {
+    for (i, protocol) in alpn_protocols.iter().enumerate() {
+        if protocol.is_empty() || protocol.len() > 255 {
+            return Err(ConfigError::new(
+                "ALPN protocol names must be 1 to 255 bytes long",
+            ));
+        }
+        if alpn_protocols[..i].contains(protocol) {
+            return Err(ConfigError::new(format!(
+                "ALPN protocol {:?} is listed twice",
+                String::from_utf8_lossy(protocol)
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub fn check_max_fragment_length(
+    max_fragment_length: Option<MaxFragmentLength>,
+) -> Result<(), ConfigError> {
+    match max_fragment_length {
+        Some(max_fragment_length) if max_fragment_length.fragment_len().is_none() => {
+            Err(ConfigError::new(format!(
+                "{:?} is not a fragment length",
+                max_fragment_length
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher_suite::DEFAULT_CIPHER_SUITES;
+
+    #[test]
+    fn rejects_unusable_settings() {
+        let schemes = SUPPORTED_SIGNATURE_SCHEMES.to_vec();
+        assert_eq!(
+            check_cipher_suites(&DEFAULT_CIPHER_SUITES, &schemes),
+            Ok(())
+        );
+        assert!(check_cipher_suites(&[], &schemes).is_err());
+        assert!(check_cipher_suites(&[CipherSuite::TLS_RSA_WITH_RC4_128_MD5], &schemes).is_err());
+        assert!(check_cipher_suites(
+            &[DEFAULT_CIPHER_SUITES[0], DEFAULT_CIPHER_SUITES[0]],
+            &schemes
+        )
+        .is_err());
+        // an ECDSA suite cannot be authenticated with RSA signatures only
+        assert!(check_cipher_suites(
+            &[CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256],
+            &[SignatureScheme::rsa_pkcs1_sha256]
+        )
+        .is_err());
+
+        assert!(check_named_groups(&[NamedGroup::x448]).is_err());
+        assert!(check_signature_schemes(&[SignatureScheme::rsa_pkcs1_sha1]).is_err());
+        assert!(check_alpn_protocols(&[b"h2".to_vec(), vec![]]).is_err());
+        assert!(check_alpn_protocols(&[b"h2".to_vec(), b"h2".to_vec()]).is_err());
+        assert!(check_max_fragment_length(Some(MaxFragmentLength::Unknown(5))).is_err());
+    }
+}
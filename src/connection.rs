@@ -0,0 +1,867 @@
+use crate::cipher_suite::{Authentication, CipherSuite};
+use crate::enums::{AlertDescription, AlertLevel, ContentType, HandshakeType};
+use crate::error::Error;
+use crate::finished::Finished;
+use crate::handshake::{bytes_to_u32_be, Alert, MasterSecretInfo};
+use crate::pcap::PcapRecorder;
+use crate::record_layer::RecordLayer;
+use crate::sign::{ECDSA_SIGN, RSA_SIGN};
+use crate::tls_ciphertext::GenericAEADCipher;
+use crate::tls_plaintext::{MAX_CIPHERTEXT_LEN, RECORD_HEADER_LEN};
+use std::io::{self, Read, Write};
+
+// The largest handshake message accepted from the peer. The 24-bit length would let a peer make
+// us buffer 16 MiB, while a certificate chain, the largest message, fits easily into 64 KiB.
+const MAX_HANDSHAKE_MESSAGE_LEN: usize = 64 * 1024;
+
+// The sans-IO interface of ClientConnection and ServerConnection. A connection never touches
+// the transport itself: bytes from the peer go in with read_tls, process_new_packets runs the
+// handshake and decrypts records, and write_tls hands out the bytes to send.
+//
+//     loop {
+//         if conn.wants_write() { conn.write_tls(&mut socket)?; }
+//         if conn.wants_read() { conn.read_tls(&mut socket)?; conn.process_new_packets()?; }
+//         // conn.reader() and conn.writer() for application data
+//     }
+pub trait Connection {
+    fn common(&self) -> &ConnectionCommon;
+
+    fn common_mut(&mut self) -> &mut ConnectionCommon;
+
+    // Handle the records received so far: advance the handshake and decrypt application data.
+    // On error a fatal alert is queued for write_tls and the connection cannot be used anymore.
+    fn process_new_packets(&mut self) -> Result<(), Error>;
+
+    // Read bytes from the transport with a single read call. Ok(0) means EOF.
+    fn read_tls(&mut self, rd: &mut dyn Read) -> io::Result<usize> {
+        self.common_mut().read_tls(rd)
+    }
+
+    // Write queued records to the transport with a single write call.
+    fn write_tls(&mut self, wr: &mut dyn Write) -> io::Result<usize> {
+        self.common_mut().write_tls(wr)
+    }
+
+    // application data received from the peer
+    fn reader(&mut self) -> Reader<'_> {
+        Reader {
+            common: self.common_mut(),
+        }
+    }
+
+    // application data for the peer. Data written during the first handshake is sent
+    // once the handshake completes.
+    fn writer(&mut self) -> Writer<'_> {
+        Writer {
+            common: self.common_mut(),
+        }
+    }
+
+    // true if read_tls should be called
+    fn wants_read(&self) -> bool {
+        self.common().wants_read()
+    }
+
+    // true if write_tls has something to write
+    fn wants_write(&self) -> bool {
+        self.common().wants_write()
+    }
+
+    fn is_handshaking(&self) -> bool {
+        self.common().is_handshaking()
+    }
+
+    // queue a close_notify alert, nothing can be written after it
+    fn send_close_notify(&mut self) {
+        self.common_mut().send_close_notify()
+    }
+
+    // Do the I/O until the handshake completes if it is in progress, otherwise until the queued
+    // records are written or some bytes were read. Returns the number of bytes read and written.
+    fn complete_io<T: Read + Write>(&mut self, io: &mut T) -> io::Result<(usize, usize)>
+    where
+        Self: Sized,
+    {
+        let until_handshaked = self.is_handshaking();
+        let mut eof = false;
+        let mut rdlen = 0;
+        let mut wrlen = 0;
+        loop {
+            wrlen += self.write_all_tls(io)?;
+            if !until_handshaked && wrlen > 0 {
+                return Ok((rdlen, wrlen));
+            }
+            if !eof && self.wants_read() {
+                match self.read_tls(io)? {
+                    0 => eof = true,
+                    n => rdlen += n,
+                }
+            }
+            if let Err(error) = self.process_new_packets() {
+                // try to tell the peer why the connection is closed
+                let _ = self.write_all_tls(io);
+                return Err(error.into());
+            }
+            match (eof, until_handshaked, self.is_handshaking()) {
+                // the last flight of the handshake may still be queued
+                (_, true, false) => {
+                    wrlen += self.write_all_tls(io)?;
+                    return Ok((rdlen, wrlen));
+                }
+                (_, false, _) => return Ok((rdlen, wrlen)),
+                (true, true, true) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "connection closed during the handshake",
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn write_all_tls<T: Write>(&mut self, io: &mut T) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let mut written = 0;
+        while self.wants_write() {
+            match self.write_tls(io)? {
+                0 => return Err(io::ErrorKind::WriteZero.into()),
+                n => written += n,
+            }
+        }
+        io.flush()?;
+        Ok(written)
+    }
+}
+
+// A handshake message or ChangeCipherSpec for the handshake state machine.
+pub enum Message {
+    Handshake(HandshakeType, Vec<u8>),
+    ChangeCipherSpec,
+}
+
+// State shared by the client and the server side of a connection: the record layer, the buffers
+// between it and the transport, and the messages of the current handshake.
+pub struct ConnectionCommon {
+    record_layer: RecordLayer,
+    // bytes from read_tls that do not form a complete record yet
+    received_tls: Vec<u8>,
+    // records waiting for write_tls
+    sendable_tls: Vec<u8>,
+    // decrypted application data waiting for reader()
+    received_plaintext: Vec<u8>,
+    // application data written before the first handshake completed
+    sendable_plaintext: Vec<u8>,
+    // handshake bytes received but not yet parsed into a message
+    handshake_buffer: Vec<u8>,
+    // all handshake messages sent and received, used for Finished and CertificateVerify
+    handshake_messages: Vec<u8>,
+    // length of handshake_messages before the last received message was added
+    last_message_start: usize,
+    handshaking: bool,
+    // a handshake completed, application data may be exchanged
+    established: bool,
+    // the peer sent close_notify
+    peer_closed: bool,
+    // read_tls reached the end of the transport
+    eof: bool,
+    // process_new_packets returned an error
+    failed: bool,
+    // captures the bytes read and written, see set_recorder
+    recorder: Option<PcapRecorder>,
+}
+
+impl ConnectionCommon {
+    pub fn new() -> Self {
+        ConnectionCommon {
+            record_layer: RecordLayer::new(),
+            received_tls: Vec::new(),
+            sendable_tls: Vec::new(),
+            received_plaintext: Vec::new(),
+            sendable_plaintext: Vec::new(),
+            handshake_buffer: Vec::new(),
+            handshake_messages: Vec::new(),
+            last_message_start: 0,
+            handshaking: true,
+            established: false,
+            peer_closed: false,
+            eof: false,
+            failed: false,
+            recorder: None,
+        }
+    }
+
+    // Finished only covers the messages of the current handshake
+    pub fn start_handshake(&mut self) {
+        self.handshake_messages.clear();
+        self.last_message_start = 0;
+        self.handshaking = true;
+    }
+
+    // a handshake the peer started while connected, e.g. with a ClientHello that asks for
+    // renegotiation. message is that first handshake message, header included.
+    pub fn start_peer_handshake(&mut self, message: &[u8]) {
+        self.start_handshake();
+        self.handshake_messages.extend(message);
+    }
+
+    // send the application data written during the first handshake
+    pub fn handshake_done(&mut self) {
+        self.handshaking = false;
+        if !self.established {
+            self.established = true;
+            let data = std::mem::take(&mut self.sendable_plaintext);
+            if !data.is_empty() {
+                self.record_layer.write_record(
+                    &mut self.sendable_tls,
+                    ContentType::ApplicationData,
+                    &data,
+                );
+            }
+        }
+    }
+
+    pub fn is_handshaking(&self) -> bool {
+        self.handshaking
+    }
+
+    // true once a handshake completed, also while renegotiating
+    pub fn is_established(&self) -> bool {
+        self.established
+    }
+
+    pub fn handshake_messages(&self) -> &[u8] {
+        &self.handshake_messages
+    }
+
+    // the handshake messages before the last received one, which the peer's
+    // Finished or CertificateVerify covers
+    pub fn handshake_messages_before_last(&self) -> &[u8] {
+        &self.handshake_messages[..self.last_message_start]
+    }
+
+    pub fn set_max_fragment_len(&mut self, max_fragment_len: usize) {
+        self.record_layer.set_max_fragment_len(max_fragment_len);
+    }
+
+    pub fn send_handshake(&mut self, message: Vec<u8>) {
+        self.handshake_messages.extend(&message);
+        self.record_layer
+            .write_record(&mut self.sendable_tls, ContentType::Handshake, &message);
+    }
+
+    pub fn send_change_cipher_spec(&mut self, cipher: GenericAEADCipher) {
+        // struct { enum { change_cipher_spec(1), (255) } type; } ChangeCipherSpec;
+        self.record_layer.write_record(
+            &mut self.sendable_tls,
+            ContentType::ChangeCipherSpec,
+            &[0x01],
+        );
+        self.record_layer.set_encrypter(cipher);
+    }
+
+    // called after receiving ChangeCipherSpec
+    pub fn set_decrypter(&mut self, cipher: GenericAEADCipher) {
+        self.record_layer.set_decrypter(cipher);
+    }
+
+    // returns the verify_data that was sent
+    pub fn send_finished(&mut self, secrets: &MasterSecretInfo, label: &[u8]) -> Vec<u8> {
+        let verify_data = secrets.verify_data(label, &self.handshake_messages);
+        self.send_handshake(Finished::new(verify_data.clone()).encode());
+        verify_data
+    }
+
+    // body is the peer's Finished, the last received message. Returns its verify_data.
+    pub fn check_finished(
+        &self,
+        secrets: &MasterSecretInfo,
+        label: &[u8],
+        body: Vec<u8>,
+    ) -> Result<Vec<u8>, Error> {
+        // the hash covers all handshake messages before the peer's Finished
+        let expected = secrets.verify_data(label, self.handshake_messages_before_last());
+        let finished = Finished::read(&body)?;
+        if finished.verify_data() != expected {
+            return Err(Error::Crypto(String::from("Finished verify_data mismatch")));
+        }
+        Ok(expected)
+    }
+
+    pub fn send_alert(&mut self, level: AlertLevel, description: AlertDescription) {
+        let alert = Alert { level, description };
+        self.record_layer
+            .write_record(&mut self.sendable_tls, ContentType::Alert, &alert.encode());
+    }
+
+    pub fn send_close_notify(&mut self) {
+        self.send_alert(AlertLevel::Warning, AlertDescription::CloseNotify)
+    }
+
+    // queue the fatal alert of an error before it is returned to the caller, see Error::alert.
+    // The connection is unusable afterwards.
+    pub fn abort_on_error<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        if let Err(error) = &result {
+            self.failed = true;
+            if let Some(description) = error.alert() {
+                self.send_alert(AlertLevel::Fatal, description);
+            }
+        }
+        result
+    }
+
+    pub fn check_failed(&self) -> Result<(), Error> {
+        if self.failed {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "the connection failed earlier",
+            )));
+        }
+        Ok(())
+    }
+
+    // The next handshake message or ChangeCipherSpec, None if more bytes are needed.
+    // Alerts and application data are handled here. Messages of the current handshake are
+    // added to the handshake hashes, HelloRequest never is.
+    pub fn next_message(&mut self) -> Result<Option<Message>, Error> {
+        loop {
+            if let Some(message) = self.take_handshake_message()? {
+                let msg_type = HandshakeType::read(message[0]);
+                if self.handshaking && msg_type != HandshakeType::HelloRequest {
+                    self.last_message_start = self.handshake_messages.len();
+                    self.handshake_messages.extend(&message);
+                }
+                return Ok(Some(Message::Handshake(msg_type, message[4..].to_vec())));
+            }
+            // anything after close_notify is ignored
+            if self.peer_closed {
+                return Ok(None);
+            }
+
+            let Some((content_type, fragment)) =
+                self.record_layer.read_record(&mut self.received_tls)?
+            else {
+                return Ok(None);
+            };
+            match content_type {
+                ContentType::Handshake => self.handshake_buffer.extend(fragment),
+                // ChangeCipherSpec must not be interleaved with a partial handshake message
+                ContentType::ChangeCipherSpec
+                    if fragment == [0x01] && self.handshake_buffer.is_empty() =>
+                {
+                    return Ok(Some(Message::ChangeCipherSpec))
+                }
+                ContentType::Alert => self.process_alert(&fragment)?,
+                ContentType::ApplicationData if self.established => {
+                    self.received_plaintext.extend(fragment)
+                }
+                _ => {
+                    return Err(Error::UnexpectedMessage(format!(
+                        "unexpected {:?} record",
+                        content_type
+                    )))
+                }
+            }
+        }
+    }
+
+    // Every alert aborts the handshake. Afterwards only fatal alerts do,
+    // warnings other than close_notify are ignored.
+    fn process_alert(&mut self, fragment: &[u8]) -> Result<(), Error> {
+        let alert = Alert::read(fragment)?;
+        if self.handshaking || alert.level == AlertLevel::Fatal {
+            return Err(Error::AlertReceived(alert.description));
+        }
+        if alert.description == AlertDescription::CloseNotify {
+            self.peer_closed = true;
+        }
+        Ok(())
+    }
+
+    // the next complete handshake message, header included. Fails as soon as the header
+    // announces a message longer than MAX_HANDSHAKE_MESSAGE_LEN.
+    fn take_handshake_message(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        if self.handshake_buffer.len() < 4 {
+            return Ok(None);
+        }
+        let len = bytes_to_u32_be(&self.handshake_buffer[1..4]) as usize;
+        if len > MAX_HANDSHAKE_MESSAGE_LEN {
+            return Err(Error::HandshakeFailure(format!(
+                "handshake message of {} bytes, at most {} are allowed",
+                len, MAX_HANDSHAKE_MESSAGE_LEN
+            )));
+        }
+        if self.handshake_buffer.len() < 4 + len {
+            return Ok(None);
+        }
+        Ok(Some(self.handshake_buffer.drain(..4 + len).collect()))
+    }
+
+    pub fn read_tls(&mut self, rd: &mut dyn Read) -> io::Result<usize> {
+        self.check_failed()?;
+        // process_new_packets consumes every complete record, so more than one record
+        // buffered means it was not called
+        if self.received_tls.len() >= RECORD_HEADER_LEN + MAX_CIPHERTEXT_LEN {
+            return Err(io::Error::other(
+                "received records must be processed before reading more",
+            ));
+        }
+        let mut buf = vec![0; RECORD_HEADER_LEN + MAX_CIPHERTEXT_LEN];
+        let n = rd.read(&mut buf)?;
+        if n == 0 {
+            self.eof = true;
+        }
+        if let Some(recorder) = &mut self.recorder {
+            // a broken capture must not break the connection
+            let _ = match n {
+                0 => recorder.peer_closed(),
+                n => recorder.received(&buf[..n]),
+            };
+        }
+        self.received_tls.extend(&buf[..n]);
+        Ok(n)
+    }
+
+    pub fn write_tls(&mut self, wr: &mut dyn Write) -> io::Result<usize> {
+        let n = wr.write(&self.sendable_tls)?;
+        if let Some(recorder) = &mut self.recorder {
+            let _ = recorder.sent(&self.sendable_tls[..n]);
+        }
+        self.sendable_tls.drain(..n);
+        Ok(n)
+    }
+
+    // record the bytes of read_tls and write_tls from now on, is_client picks the side of the
+    // TCP stream. Set it before the first read_tls or write_tls for a complete capture.
+    pub fn set_recorder(&mut self, mut recorder: PcapRecorder, is_client: bool) {
+        let _ = recorder.start(is_client);
+        self.recorder = Some(recorder);
+    }
+
+    pub fn wants_read(&self) -> bool {
+        !self.failed
+            && !self.peer_closed
+            && !self.eof
+            && (self.handshaking || self.received_plaintext.is_empty())
+    }
+
+    pub fn wants_write(&self) -> bool {
+        !self.sendable_tls.is_empty()
+    }
+
+    fn write_plaintext(&mut self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        if self.established {
+            self.record_layer.write_record(
+                &mut self.sendable_tls,
+                ContentType::ApplicationData,
+                data,
+            );
+        } else {
+            self.sendable_plaintext.extend(data);
+        }
+    }
+}
+
+impl Default for ConnectionCommon {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Application data received from the peer, see Connection::reader.
+pub struct Reader<'a> {
+    common: &'a mut ConnectionCommon,
+}
+
+impl Read for Reader<'_> {
+    // Ok(0) once the peer sent close_notify and everything was read. WouldBlock if there is
+    // no data yet, UnexpectedEof if the transport was closed without close_notify.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let received = &mut self.common.received_plaintext;
+        if received.is_empty() {
+            if self.common.peer_closed {
+                return Ok(0);
+            }
+            if self.common.eof {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "peer closed the connection without close_notify, the data may be truncated",
+                ));
+            }
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let n = buf.len().min(received.len());
+        buf[..n].copy_from_slice(&received[..n]);
+        received.drain(..n);
+        Ok(n)
+    }
+}
+
+// Application data for the peer, see Connection::writer.
+pub struct Writer<'a> {
+    common: &'a mut ConnectionCommon,
+}
+
+impl Write for Writer<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.common.write_plaintext(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// the first of the enabled suites (the server's preference order) that the client offered and
+// whose ServerKeyExchange is signed by a key of certificate_type.
+// Ed25519 keys use ecdsa_sign (RFC 8422).
+pub fn choose_cipher_suite(
+    enabled: &[CipherSuite],
+    certificate_type: u8,
+    offered: &[CipherSuite],
+) -> Option<CipherSuite> {
+    let authentication = match certificate_type {
+        RSA_SIGN => Authentication::Rsa,
+        ECDSA_SIGN => Authentication::Ecdsa,
+        _ => return None,
+    };
+    enabled.iter().copied().find(|suite| {
+        offered.contains(suite)
+            && suite
+                .params()
+                .is_some_and(|params| params.authentication == authentication)
+    })
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#6-3--Key-Calculation
+// returns (client write cipher, server write cipher). The implemented suites are AEAD suites,
+// so the key block has no MAC keys.
+pub fn key_expansion(secrets: &MasterSecretInfo) -> (GenericAEADCipher, GenericAEADCipher) {
+    let suite = secrets.suite();
+    let algorithm = suite
+        .bulk_cipher
+        .aead_algorithm()
+        .expect("implemented suites use an AEAD cipher");
+    let key_block = secrets.key_block();
+    let (client_write_key, rest) = key_block.split_at(suite.key_len());
+    let (server_write_key, rest) = rest.split_at(suite.key_len());
+    let (client_write_iv, server_write_iv) = rest.split_at(suite.fixed_iv_len());
+
+    (
+        GenericAEADCipher::new(algorithm, client_write_key, client_write_iv),
+        GenericAEADCipher::new(algorithm, server_write_key, server_write_iv),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate::spki_sha256;
+    use crate::client::{ClientConfig, ClientConfigBuilder, ClientConnection, RenegotiationPolicy};
+    use crate::ct::{CtLogList, CtPolicy, SctSource};
+    use crate::error::CertificateError;
+    use crate::loader::load_private_key;
+    use crate::ocsp::OcspResponseFile;
+    use crate::pinning::PinSet;
+    use crate::server::{ServerConfig, ServerConnection};
+    use crate::session::ServerSessionCache;
+    use crate::sign::CertifiedKey;
+    use crate::verify::RootCertStore;
+    use std::sync::Arc;
+    use std::{env, fs};
+
+    const LEAF: &[u8] = include_bytes!("../testdata/ct/leaf.der");
+    const LEAF_KEY: &[u8] = include_bytes!("../testdata/ct/leaf.key");
+    const CA: &[u8] = include_bytes!("../testdata/ocsp/ca.der");
+    const UNUSED: &[u8] = include_bytes!("../testdata/ocsp/responder.der");
+    const LEAF_OCSP: &[u8] = include_bytes!("../testdata/ct/leaf.ocsp");
+    const LOG_LIST: &[u8] = include_bytes!("../testdata/ct/log_list.json");
+    // LEAF is valid from 2026-10-19 for a year
+    const NOW: u64 = 1792454400;
+
+    // move everything queued by one side to the other, one read_tls call at a time
+    fn transfer(from: &mut dyn Connection, to: &mut dyn Connection) -> Result<usize, Error> {
+        let mut buf = Vec::new();
+        while from.wants_write() {
+            from.write_tls(&mut buf)?;
+        }
+        let mut rd = &buf[..];
+        while !rd.is_empty() {
+            to.read_tls(&mut rd)?;
+            to.process_new_packets()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn handshake(client: &mut ClientConnection, server: &mut ServerConnection) {
+        while client.is_handshaking() || server.is_handshaking() {
+            let sent = transfer(client, server).unwrap() + transfer(server, client).unwrap();
+            assert!(sent > 0, "the handshake stalled");
+        }
+    }
+
+    #[test]
+    fn handshake_and_resume_in_memory() {
+        let key = load_private_key(LEAF_KEY).unwrap();
+        let server_config = ServerConfig::builder()
+            .with_single_cert(CertifiedKey::new(vec![LEAF.to_vec()], key))
+            .with_session_cache(Arc::new(ServerSessionCache::new(16, 3600)))
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let client_config = ClientConfig::builder()
+            .with_no_certificate_verification()
+            .build()
+            .map(Arc::new)
+            .unwrap();
+
+        let mut client = ClientConnection::new(Arc::clone(&client_config), "localhost");
+        let mut server = ServerConnection::new(Arc::clone(&server_config));
+        // written before the handshake, sent after it
+        client.writer().write_all(b"ping").unwrap();
+        handshake(&mut client, &mut server);
+        assert!(!client.is_resumed());
+        assert_eq!(client.peer_certificates(), [LEAF.to_vec()]);
+
+        transfer(&mut client, &mut server).unwrap();
+        let mut received = [0; 4];
+        server.reader().read_exact(&mut received).unwrap();
+        assert_eq!(&received, b"ping");
+        assert_eq!(
+            server.reader().read(&mut received).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        server.send_close_notify();
+        transfer(&mut server, &mut client).unwrap();
+        assert_eq!(client.reader().read(&mut received).unwrap(), 0);
+        assert!(!client.wants_read());
+
+        let mut client = ClientConnection::new(client_config, "localhost");
+        let mut server = ServerConnection::new(server_config);
+        handshake(&mut client, &mut server);
+        assert!(client.is_resumed() && server.is_resumed());
+
+        // the transport closes without close_notify
+        client.read_tls(&mut &[][..]).unwrap();
+        assert_eq!(
+            client.reader().read(&mut received).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    fn renegotiation_configs(
+        policy: RenegotiationPolicy,
+    ) -> (Arc<ClientConfig>, Arc<ServerConfig>) {
+        let server_config = ServerConfig::builder()
+            .with_single_cert(CertifiedKey::new(
+                vec![LEAF.to_vec()],
+                load_private_key(LEAF_KEY).unwrap(),
+            ))
+            .with_secure_renegotiation(true)
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let client_config = ClientConfig::builder()
+            .with_no_certificate_verification()
+            .with_renegotiation_policy(policy)
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        (client_config, server_config)
+    }
+
+    fn ping(client: &mut ClientConnection, server: &mut ServerConnection) {
+        client.writer().write_all(b"ping").unwrap();
+        transfer(client, server).unwrap();
+        let mut received = [0; 4];
+        server.reader().read_exact(&mut received).unwrap();
+        assert_eq!(&received, b"ping");
+    }
+
+    #[test]
+    fn secure_renegotiation() {
+        let (client_config, server_config) =
+            renegotiation_configs(RenegotiationPolicy::Renegotiate);
+        let mut client = ClientConnection::new(client_config, "localhost");
+        let mut server = ServerConnection::new(server_config);
+        handshake(&mut client, &mut server);
+
+        // HelloRequest, answered with a ClientHello bound to the first handshake
+        server.request_renegotiation().unwrap();
+        transfer(&mut server, &mut client).unwrap();
+        assert!(client.is_handshaking());
+        handshake(&mut client, &mut server);
+        ping(&mut client, &mut server);
+
+        // bound to the renegotiation, not to the first handshake
+        client.renegotiate().unwrap();
+        assert!(matches!(client.renegotiate(), Err(Error::Renegotiation(_))));
+        handshake(&mut client, &mut server);
+        ping(&mut client, &mut server);
+    }
+
+    #[test]
+    fn hello_request_is_refused() {
+        let (client_config, server_config) = renegotiation_configs(RenegotiationPolicy::Refuse);
+        let mut client = ClientConnection::new(client_config, "localhost");
+        let mut server = ServerConnection::new(server_config);
+        handshake(&mut client, &mut server);
+
+        server.request_renegotiation().unwrap();
+        transfer(&mut server, &mut client).unwrap();
+        assert!(!client.is_handshaking());
+        let mut buf = Vec::new();
+        while client.wants_write() {
+            client.write_tls(&mut buf).unwrap();
+        }
+        let (content_type, fragment) = server
+            .common_mut()
+            .record_layer
+            .read_record(&mut buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(content_type, ContentType::Alert);
+        let alert = Alert::read(&fragment).unwrap();
+        assert_eq!(alert.level, AlertLevel::Warning);
+        assert_eq!(alert.description, AlertDescription::NoRenegotiation);
+
+        // the connection goes on with the keys of the first handshake
+        ping(&mut client, &mut server);
+    }
+
+    // a ClientHello header announcing len bytes, in a plaintext record of its own
+    fn read_hello_header(len: u32) -> Result<(), Error> {
+        let server_config = ServerConfig::builder()
+            .with_single_cert(CertifiedKey::new(
+                vec![LEAF.to_vec()],
+                load_private_key(LEAF_KEY).unwrap(),
+            ))
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let mut server = ServerConnection::new(server_config);
+        let mut record = vec![0x16, 0x03, 0x03, 0x00, 0x04, 0x01];
+        record.extend(&len.to_be_bytes()[1..]);
+        server.read_tls(&mut &record[..]).unwrap();
+        server.process_new_packets()
+    }
+
+    #[test]
+    fn oversized_handshake_message_fails_at_the_header() {
+        assert!(read_hello_header(MAX_HANDSHAKE_MESSAGE_LEN as u32).is_ok());
+        assert!(matches!(
+            read_hello_header(MAX_HANDSHAKE_MESSAGE_LEN as u32 + 1),
+            Err(Error::HandshakeFailure(_))
+        ));
+        assert!(matches!(
+            read_hello_header(0xff_ffff),
+            Err(Error::HandshakeFailure(_))
+        ));
+    }
+
+    // the server's chain leads to CA, UNUSED is appended but not on the path
+    fn handshake_with_pin(pin: &[u8]) -> Result<(), Error> {
+        let chain = vec![LEAF.to_vec(), UNUSED.to_vec()];
+        let server_config = ServerConfig::builder()
+            .with_single_cert(CertifiedKey::new(
+                chain,
+                load_private_key(LEAF_KEY).unwrap(),
+            ))
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let mut roots = RootCertStore::new();
+        roots.add(CA).unwrap();
+        let mut pins = PinSet::new();
+        pins.add("localhost", spki_sha256(pin).unwrap());
+        let client_config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_pins(pins)
+            .with_time_provider(|| NOW)
+            .build()
+            .map(Arc::new)
+            .unwrap();
+
+        let mut client = ClientConnection::new(client_config, "localhost");
+        let mut server = ServerConnection::new(server_config);
+        while client.is_handshaking() {
+            transfer(&mut client, &mut server)?;
+            transfer(&mut server, &mut client)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn pins_only_match_the_verified_path() {
+        assert!(handshake_with_pin(CA).is_ok());
+        assert!(matches!(
+            handshake_with_pin(UNUSED),
+            Err(Error::Certificate(CertificateError::Bad(_)))
+        ));
+    }
+
+    // the server sends only its leaf and staples LEAF_OCSP
+    fn handshake_with_staple(client_config: ClientConfigBuilder) -> ClientConnection {
+        // tests run in parallel, one file per thread
+        let name = format!(
+            "tls-12-staple-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        );
+        let path = env::temp_dir().join(name);
+        fs::write(&path, LEAF_OCSP).unwrap();
+        let certified_key =
+            CertifiedKey::new(vec![LEAF.to_vec()], load_private_key(LEAF_KEY).unwrap())
+                .with_ocsp(Arc::new(OcspResponseFile::new(&path)));
+        let server_config = ServerConfig::builder()
+            .with_single_cert(certified_key)
+            .with_time_provider(|| NOW)
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let mut roots = RootCertStore::new();
+        roots.add(CA).unwrap();
+        let client_config = client_config
+            .with_root_certificates(roots)
+            .with_time_provider(|| NOW)
+            .build()
+            .map(Arc::new)
+            .unwrap();
+
+        let mut client = ClientConnection::new(client_config, "localhost");
+        let mut server = ServerConnection::new(server_config);
+        handshake(&mut client, &mut server);
+        fs::remove_file(&path).unwrap();
+        client
+    }
+
+    // the OCSP issuer comes from the verified path, not the server's list
+    #[test]
+    fn staple_with_a_leaf_only_chain() {
+        let client = handshake_with_staple(ClientConfig::builder());
+        assert_eq!(client.peer_certificates(), [LEAF.to_vec()]);
+        assert_eq!(client.ocsp_response(), Some(LEAF_OCSP));
+    }
+
+    // so does the issuer for the SCTs in the stapled response and in the certificate
+    #[test]
+    fn ct_with_a_leaf_only_chain() {
+        let logs = Arc::new(CtLogList::from_json(LOG_LIST).unwrap());
+        let client =
+            handshake_with_staple(ClientConfig::builder().with_ct_policy(CtPolicy::new(logs, 1)));
+        let sources: Vec<SctSource> = client
+            .verified_scts()
+            .iter()
+            .map(|sct| sct.source)
+            .collect();
+        assert!(sources.contains(&SctSource::Ocsp));
+        assert!(sources.contains(&SctSource::Embedded));
+    }
+}
@@ -0,0 +1,270 @@
+use crate::certificate::verify_signed_data;
+use rustls_pemfile::Item;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use x509_parser::prelude::*;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5280.html#5--CRL-and-CRL-Extensions-Profile
+// Revocation checking against CRLs. Every certificate of the validated path except the trust
+// anchor is looked up in the CRLs of its issuer, the next certificate of the path. A CRL is
+// only used when it is signed by the issuer and current (thisUpdate <= now <= nextUpdate).
+
+// Source of CRLs, e.g. files on disk or a cache filled by a downloader.
+pub trait CrlProvider: Send + Sync {
+    // DER encoded CRLs whose issuer is the DER encoded Name issuer
+    fn crls(&self, issuer: &[u8]) -> Vec<Vec<u8>>;
+}
+
+// CRLs loaded from DER or PEM files.
+#[derive(Default)]
+pub struct CrlStore {
+    // (DER encoded issuer Name, DER encoded CRL)
+    crls: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl CrlStore {
+    pub fn new() -> Self {
+        CrlStore::default()
+    }
+
+    // add the CRLs of a PEM file ("X509 CRL"), or a single DER encoded CRL
+    pub fn add(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if !bytes.windows(10).any(|window| window == b"-----BEGIN") {
+            return self.add_der(bytes.to_vec());
+        }
+        let mut found = false;
+        for item in rustls_pemfile::read_all(&mut &bytes[..])? {
+            if let Item::Crl(der) = item {
+                self.add_der(der)?;
+                found = true;
+            }
+        }
+        if !found {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "no X509 CRL found",
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.add(&fs::read(path)?)
+    }
+
+    fn add_der(&mut self, der: Vec<u8>) -> io::Result<()> {
+        let Ok((_, crl)) = parse_x509_crl(&der) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid CRL"));
+        };
+        let issuer = crl.issuer().as_raw().to_vec();
+        self.crls.push((issuer, der));
+        Ok(())
+    }
+}
+
+impl CrlProvider for CrlStore {
+    fn crls(&self, issuer: &[u8]) -> Vec<Vec<u8>> {
+        self.crls
+            .iter()
+            .filter(|(crl_issuer, _)| crl_issuer == issuer)
+            .map(|(_, crl)| crl.clone())
+            .collect()
+    }
+}
+
+// What to do when there is no current CRL signed by the issuer of a certificate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnknownStatusPolicy {
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RevocationError {
+    // serial number of the revoked certificate, hex encoded
+    Revoked(String),
+    // no current CRL for the certificate with this subject, with UnknownStatusPolicy::Deny
+    UnknownStatus(String),
+}
+
+impl std::fmt::Display for RevocationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RevocationError::Revoked(serial) => write!(f, "certificate {} is revoked", serial),
+            RevocationError::UnknownStatus(subject) => {
+                write!(f, "no current CRL for the certificate of {}", subject)
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CrlChecker {
+    provider: Arc<dyn CrlProvider>,
+    unknown_status: UnknownStatusPolicy,
+}
+
+impl CrlChecker {
+    // certificates without a current CRL are accepted
+    pub fn new(provider: Arc<dyn CrlProvider>) -> Self {
+        CrlChecker {
+            provider,
+            unknown_status: UnknownStatusPolicy::Allow,
+        }
+    }
+
+    pub fn with_unknown_status_policy(mut self, policy: UnknownStatusPolicy) -> Self {
+        self.unknown_status = policy;
+        self
+    }
+
+    // path is DER encoded, leaf first and ending with the trust anchor, as returned by
+    // verify::verify_chain. The peer's certificate_list must not be passed as is: it may be
+    // out of order or padded with unrelated certificates.
+    pub fn check(&self, path: &[Vec<u8>], now: u64) -> Result<(), RevocationError> {
+        for pair in path.windows(2) {
+            let (cert, issuer) = (&pair[0], &pair[1]);
+            let Ok((_, parsed)) = parse_x509_certificate(cert) else {
+                return Err(RevocationError::UnknownStatus(String::from(
+                    "(invalid certificate)",
+                )));
+            };
+            let crls = self.provider.crls(parsed.issuer().as_raw());
+            let mut checked = false;
+            for crl in crls.iter().filter(|crl| is_current_crl(crl, issuer, now)) {
+                let Ok((_, crl)) = parse_x509_crl(crl) else {
+                    continue;
+                };
+                if crl
+                    .iter_revoked_certificates()
+                    .any(|revoked| revoked.raw_serial() == parsed.raw_serial())
+                {
+                    return Err(RevocationError::Revoked(parsed.raw_serial_as_string()));
+                }
+                checked = true;
+            }
+            if !checked && self.unknown_status == UnknownStatusPolicy::Deny {
+                return Err(RevocationError::UnknownStatus(parsed.subject().to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+// the CRL is signed by issuer and valid at now
+fn is_current_crl(crl: &[u8], issuer: &[u8], now: u64) -> bool {
+    let (Ok((_, crl)), Ok((_, parsed_issuer))) =
+        (parse_x509_crl(crl), parse_x509_certificate(issuer))
+    else {
+        return false;
+    };
+    let now = now as i64;
+    crl.issuer().as_raw() == parsed_issuer.subject().as_raw()
+        && verify_signed_data(
+            issuer,
+            crl.signature_algorithm.algorithm.as_bytes(),
+            crl.tbs_cert_list.as_ref(),
+            &crl.signature_value.data,
+        )
+        && crl.last_update().timestamp() <= now
+        && crl
+            .next_update()
+            .is_none_or(|next_update| now <= next_update.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::{verify_chain, RootCertStore};
+
+    const CA: &[u8] = include_bytes!("../testdata/ocsp/ca.der");
+    const LEAF: &[u8] = include_bytes!("../testdata/ocsp/leaf.der");
+    const REVOKED_LEAF: &[u8] = include_bytes!("../testdata/ocsp/revoked-leaf.der");
+    const CRL: &[u8] = include_bytes!("../testdata/crl/ca.crl");
+    const CRL_PEM: &[u8] = include_bytes!("../testdata/crl/ca.crl.pem");
+    const INTERMEDIATE: &[u8] = include_bytes!("../testdata/crl/intermediate.der");
+    const INTERMEDIATE_LEAF: &[u8] = include_bytes!("../testdata/crl/intermediate-leaf.der");
+    const INTERMEDIATE_CRL: &[u8] = include_bytes!("../testdata/crl/intermediate-revoked.crl");
+    // the CRL was issued at 1792396321 and expires 7 days later
+    const NOW: u64 = 1792396321 + 60;
+    const EXPIRED: u64 = 1792396321 + 8 * 24 * 60 * 60;
+
+    fn checker(crl: &[u8], policy: UnknownStatusPolicy) -> CrlChecker {
+        let mut store = CrlStore::new();
+        store.add(crl).unwrap();
+        CrlChecker::new(Arc::new(store)).with_unknown_status_policy(policy)
+    }
+
+    #[test]
+    fn revoked_certificate() {
+        for crl in [CRL, CRL_PEM] {
+            let checker = checker(crl, UnknownStatusPolicy::Deny);
+            assert_eq!(checker.check(&[LEAF.to_vec(), CA.to_vec()], NOW), Ok(()));
+            assert!(matches!(
+                checker.check(&[REVOKED_LEAF.to_vec(), CA.to_vec()], NOW),
+                Err(RevocationError::Revoked(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn stale_or_forged_crl_is_ignored() {
+        let chain = [REVOKED_LEAF.to_vec(), CA.to_vec()];
+        let deny = checker(CRL, UnknownStatusPolicy::Deny);
+        assert!(matches!(
+            deny.check(&chain, EXPIRED),
+            Err(RevocationError::UnknownStatus(_))
+        ));
+        let allow = checker(CRL, UnknownStatusPolicy::Allow);
+        assert_eq!(allow.check(&chain, EXPIRED), Ok(()));
+
+        // the last byte belongs to the signature
+        let mut forged = CRL.to_vec();
+        *forged.last_mut().unwrap() ^= 0x01;
+        let deny = checker(&forged, UnknownStatusPolicy::Deny);
+        assert!(matches!(
+            deny.check(&chain, NOW),
+            Err(RevocationError::UnknownStatus(_))
+        ));
+    }
+
+    fn verified_path(chain: &[&[u8]]) -> Vec<Vec<u8>> {
+        let mut roots = RootCertStore::new();
+        roots.add(CA).unwrap();
+        let chain: Vec<Vec<u8>> = chain.iter().map(|cert| cert.to_vec()).collect();
+        verify_chain(&chain, &roots, NOW).unwrap()
+    }
+
+    #[test]
+    fn revoked_intermediate() {
+        // the server does not send the trust anchor, the intermediate is checked against it
+        let path = verified_path(&[INTERMEDIATE_LEAF, INTERMEDIATE]);
+        assert_eq!(path.len(), 3);
+        let checker = checker(INTERMEDIATE_CRL, UnknownStatusPolicy::Allow);
+        assert!(matches!(
+            checker.check(&path, NOW),
+            Err(RevocationError::Revoked(_))
+        ));
+    }
+
+    #[test]
+    fn reordered_chain() {
+        let checker = checker(INTERMEDIATE_CRL, UnknownStatusPolicy::Allow);
+        let path = verified_path(&[INTERMEDIATE_LEAF, CA, INTERMEDIATE]);
+        assert_eq!(path, [INTERMEDIATE_LEAF, INTERMEDIATE, CA]);
+        assert!(matches!(
+            checker.check(&path, NOW),
+            Err(RevocationError::Revoked(_))
+        ));
+
+        // an unrelated certificate between the leaf and its issuer is not on the path
+        let path = verified_path(&[REVOKED_LEAF, LEAF, CA]);
+        assert_eq!(path, [REVOKED_LEAF, CA]);
+        assert!(matches!(
+            checker.check(&path, NOW),
+            Err(RevocationError::Revoked(_))
+        ));
+    }
+}
@@ -0,0 +1,495 @@
+use crate::der::{self, Reader};
+use crate::handshake::bytes_to_u32_be;
+use crate::ocsp::OcspResponse;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::{digest, signature};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use x509_parser::prelude::*;
+
+// SignedCertificateTimestampList X.509v3 extension 1.3.6.1.4.1.11129.2.4.2
+const EMBEDDED_SCT_LIST: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x02];
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc6962.html#3-2--Structure-of-the-Signed-Certificate-Timestamp
+// struct {
+//     Version sct_version;
+//     LogID id;
+//     uint64 timestamp;
+//     CtExtensions extensions;
+//     digitally-signed struct {
+//         Version sct_version;
+//         SignatureType signature_type = certificate_timestamp;
+//         uint64 timestamp;
+//         LogEntryType entry_type;
+//         select(entry_type) {
+//             case x509_entry: ASN.1Cert;
+//             case precert_entry: PreCert;
+//         } signed_entry;
+//        CtExtensions extensions;
+//     };
+// } SignedCertificateTimestamp;
+//
+// opaque SerializedSCT<1..2^16-1>;
+// struct {
+//     SerializedSCT sct_list <1..2^16-1>;
+// } SignedCertificateTimestampList;
+#[derive(Debug, Clone)]
+pub struct SignedCertificateTimestamp {
+    log_id: [u8; 32],
+    // milliseconds since the UNIX epoch
+    timestamp: u64,
+    extensions: Vec<u8>,
+    signature_algorithm: [u8; 2],
+    signature: Vec<u8>,
+}
+
+impl SignedCertificateTimestamp {
+    // a SerializedSCT. None if it is malformed or not v1(0).
+    pub fn read(buf: &[u8]) -> Option<Self> {
+        if buf.len() < 1 + 32 + 8 + 2 || buf[0] != 0 {
+            return None;
+        }
+        let log_id = buf[1..33].try_into().ok()?;
+        let timestamp = u64::from_be_bytes(buf[33..41].try_into().ok()?);
+        let (extensions, rest) = read_u16_vector(&buf[41..])?;
+        if rest.len() < 2 {
+            return None;
+        }
+        let signature_algorithm = [rest[0], rest[1]];
+        let (signature, rest) = read_u16_vector(&rest[2..])?;
+        if !rest.is_empty() {
+            return None;
+        }
+        Some(SignedCertificateTimestamp {
+            log_id,
+            timestamp,
+            extensions: extensions.to_vec(),
+            signature_algorithm,
+            signature: signature.to_vec(),
+        })
+    }
+
+    // a SignedCertificateTimestampList. Malformed SCTs are skipped, they might be of
+    // a later version.
+    pub fn read_list(buf: &[u8]) -> Option<Vec<Self>> {
+        let (mut list, rest) = read_u16_vector(buf)?;
+        if !rest.is_empty() {
+            return None;
+        }
+        let mut scts = Vec::new();
+        while !list.is_empty() {
+            let (sct, rest) = read_u16_vector(list)?;
+            scts.extend(SignedCertificateTimestamp::read(sct));
+            list = rest;
+        }
+        Some(scts)
+    }
+
+    pub fn log_id(&self) -> &[u8; 32] {
+        &self.log_id
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    // signed_entry is the TLS encoded ASN.1Cert or PreCert
+    fn verify(&self, log: &CtLog, entry_type: LogEntryType, signed_entry: &[u8]) -> bool {
+        let algorithm: &dyn signature::VerificationAlgorithm = match self.signature_algorithm {
+            // ecdsa + sha256, rsa + sha256
+            [0x04, 0x03] => &signature::ECDSA_P256_SHA256_ASN1,
+            [0x04, 0x01] => &signature::RSA_PKCS1_2048_8192_SHA256,
+            _ => return false,
+        };
+        let Ok((_, key)) = SubjectPublicKeyInfo::from_der(&log.key) else {
+            return false;
+        };
+
+        // sct_version v1(0), signature_type certificate_timestamp(0)
+        let mut message = vec![0x00, 0x00];
+        message.extend_from_slice(&self.timestamp.to_be_bytes());
+        message.extend_from_slice(&(entry_type as u16).to_be_bytes());
+        message.extend_from_slice(signed_entry);
+        message.extend_from_slice(&(self.extensions.len() as u16).to_be_bytes());
+        message.extend_from_slice(&self.extensions);
+
+        signature::UnparsedPublicKey::new(algorithm, &key.subject_public_key.data)
+            .verify(&message, &self.signature)
+            .is_ok()
+    }
+}
+
+// enum { x509_entry(0), precert_entry(1), (65535) } LogEntryType;
+#[derive(Debug, Clone, Copy)]
+enum LogEntryType {
+    X509 = 0,
+    Precert = 1,
+}
+
+// Where the server delivered an SCT.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SctSource {
+    TlsExtension,
+    Ocsp,
+    Embedded,
+}
+
+// A CT log: its description and DER encoded SubjectPublicKeyInfo.
+// The LogID is the SHA-256 hash of the key.
+#[derive(Debug, Clone)]
+pub struct CtLog {
+    pub description: String,
+    log_id: [u8; 32],
+    key: Vec<u8>,
+}
+
+// Logs trusted to issue SCTs, read from a JSON log list in the format published at
+// https://www.gstatic.com/ct/log_list/v3/log_list.json
+#[derive(Debug, Clone, Default)]
+pub struct CtLogList {
+    logs: Vec<CtLog>,
+}
+
+#[derive(Deserialize)]
+struct LogListJson {
+    operators: Vec<OperatorJson>,
+}
+
+#[derive(Deserialize)]
+struct OperatorJson {
+    logs: Vec<LogJson>,
+}
+
+#[derive(Deserialize)]
+struct LogJson {
+    description: String,
+    // base64 encoded SubjectPublicKeyInfo
+    key: String,
+}
+
+impl CtLogList {
+    pub fn from_json(json: &[u8]) -> io::Result<Self> {
+        let list: LogListJson = serde_json::from_slice(json)?;
+        let mut logs = Vec::new();
+        for log in list
+            .operators
+            .into_iter()
+            .flat_map(|operator| operator.logs)
+        {
+            let key = STANDARD
+                .decode(&log.key)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let log_id = digest::digest(&digest::SHA256, &key)
+                .as_ref()
+                .try_into()
+                .unwrap();
+            logs.push(CtLog {
+                description: log.description,
+                log_id,
+                key,
+            });
+        }
+        Ok(CtLogList { logs })
+    }
+
+    pub fn load_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        CtLogList::from_json(&fs::read(path)?)
+    }
+
+    pub fn logs(&self) -> &[CtLog] {
+        &self.logs
+    }
+
+    fn find(&self, log_id: &[u8; 32]) -> Option<&CtLog> {
+        self.logs.iter().find(|log| &log.log_id == log_id)
+    }
+}
+
+// An SCT with a valid signature from a known log.
+#[derive(Debug, Clone)]
+pub struct VerifiedSct {
+    pub log_id: [u8; 32],
+    pub log_description: String,
+    // milliseconds since the UNIX epoch
+    pub timestamp: u64,
+    pub source: SctSource,
+}
+
+// Fewer distinct logs than required issued valid SCTs for the certificate.
+#[derive(Debug, PartialEq)]
+pub struct CtError {
+    pub distinct_logs: usize,
+    pub required: usize,
+}
+
+impl fmt::Display for CtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "valid SCTs from {} distinct logs, {} required",
+            self.distinct_logs, self.required
+        )
+    }
+}
+
+impl std::error::Error for CtError {}
+
+// Require valid SCTs from at least min_distinct_logs different logs of the list.
+// SCTs are taken from the TLS extension, the stapled OCSP response and the leaf.
+#[derive(Clone)]
+pub struct CtPolicy {
+    logs: Arc<CtLogList>,
+    min_distinct_logs: usize,
+}
+
+impl CtPolicy {
+    pub fn new(logs: Arc<CtLogList>, min_distinct_logs: usize) -> Self {
+        CtPolicy {
+            logs,
+            min_distinct_logs,
+        }
+    }
+
+    // chain is the verified path, DER encoded and leaf first, so chain[1] is the issuer of
+    // the leaf for OCSP and embedded SCTs.
+    // now is in seconds, SCTs from the future are not valid.
+    pub fn check(
+        &self,
+        chain: &[Vec<u8>],
+        tls_scts: &[SignedCertificateTimestamp],
+        ocsp_response: Option<&OcspResponse>,
+        now: u64,
+    ) -> Result<Vec<VerifiedSct>, CtError> {
+        let mut verified = Vec::new();
+        if let Some(leaf) = chain.first() {
+            // ASN.1Cert
+            let mut x509_entry = (leaf.len() as u32).to_be_bytes()[1..].to_vec();
+            x509_entry.extend_from_slice(leaf);
+            for sct in tls_scts {
+                verified.extend(self.verify(
+                    sct,
+                    LogEntryType::X509,
+                    &x509_entry,
+                    SctSource::TlsExtension,
+                    now,
+                ));
+            }
+
+            if let (Some(issuer), Some(response)) = (chain.get(1), ocsp_response) {
+                let scts = response
+                    .sct_list(leaf, issuer)
+                    .and_then(SignedCertificateTimestamp::read_list)
+                    .unwrap_or_default();
+                for sct in &scts {
+                    verified.extend(self.verify(
+                        sct,
+                        LogEntryType::X509,
+                        &x509_entry,
+                        SctSource::Ocsp,
+                        now,
+                    ));
+                }
+            }
+
+            if let Some((precert_entry, scts)) =
+                chain.get(1).and_then(|issuer| embedded_scts(leaf, issuer))
+            {
+                for sct in &scts {
+                    verified.extend(self.verify(
+                        sct,
+                        LogEntryType::Precert,
+                        &precert_entry,
+                        SctSource::Embedded,
+                        now,
+                    ));
+                }
+            }
+        }
+
+        let distinct_logs = verified
+            .iter()
+            .map(|sct| sct.log_id)
+            .collect::<HashSet<_>>()
+            .len();
+        if distinct_logs < self.min_distinct_logs {
+            return Err(CtError {
+                distinct_logs,
+                required: self.min_distinct_logs,
+            });
+        }
+        Ok(verified)
+    }
+
+    fn verify(
+        &self,
+        sct: &SignedCertificateTimestamp,
+        entry_type: LogEntryType,
+        signed_entry: &[u8],
+        source: SctSource,
+        now: u64,
+    ) -> Option<VerifiedSct> {
+        let log = self.logs.find(sct.log_id())?;
+        if sct.timestamp() > now.saturating_mul(1000) || !sct.verify(log, entry_type, signed_entry)
+        {
+            return None;
+        }
+        Some(VerifiedSct {
+            log_id: log.log_id,
+            log_description: log.description.clone(),
+            timestamp: sct.timestamp(),
+            source,
+        })
+    }
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc6962.html#3-2--Structure-of-the-Signed-Certificate-Timestamp
+// struct {
+//     opaque issuer_key_hash[32];
+//     TBSCertificate tbs_certificate;
+// } PreCert;
+//
+// The SCTs embedded in leaf and the PreCert they sign: tbs_certificate is the leaf's
+// TBSCertificate without the SCT list extension.
+fn embedded_scts(leaf: &[u8], issuer: &[u8]) -> Option<(Vec<u8>, Vec<SignedCertificateTimestamp>)> {
+    let (_, leaf) = parse_x509_certificate(leaf).ok()?;
+    let (_, issuer) = parse_x509_certificate(issuer).ok()?;
+    let extension = leaf
+        .extensions()
+        .iter()
+        .find(|extension| extension.oid.as_bytes() == EMBEDDED_SCT_LIST)?;
+    let scts = SignedCertificateTimestamp::read_list(
+        Reader::new(extension.value).read(der::OCTET_STRING)?,
+    )?;
+
+    let tbs_certificate = remove_sct_extension(leaf.tbs_certificate.as_ref())?;
+    let mut precert_entry = digest::digest(&digest::SHA256, issuer.public_key().raw)
+        .as_ref()
+        .to_vec();
+    precert_entry.extend_from_slice(&(tbs_certificate.len() as u32).to_be_bytes()[1..]);
+    precert_entry.extend_from_slice(&tbs_certificate);
+    Some((precert_entry, scts))
+}
+
+// TBSCertificate ::= SEQUENCE { ..., extensions [3] EXPLICIT Extensions OPTIONAL }
+// Extensions ::= SEQUENCE SIZE (1..MAX) OF Extension
+fn remove_sct_extension(tbs_certificate: &[u8]) -> Option<Vec<u8>> {
+    let mut outer = Reader::new(tbs_certificate);
+    let mut fields = Reader::new(outer.read(der::SEQUENCE)?);
+    let mut contents = Vec::new();
+    while !fields.is_empty() {
+        let (tag, value, encoding) = fields.read_any()?;
+        if tag != der::context(3) {
+            contents.extend_from_slice(encoding);
+            continue;
+        }
+        let mut extensions = Reader::new(value);
+        let mut extensions = Reader::new(extensions.read(der::SEQUENCE)?);
+        let mut kept = Vec::new();
+        while !extensions.is_empty() {
+            let extension = extensions.read_raw(der::SEQUENCE)?;
+            let mut fields = Reader::new(extension);
+            let mut fields = Reader::new(fields.read(der::SEQUENCE)?);
+            if fields.read(der::OID)? != EMBEDDED_SCT_LIST {
+                kept.extend_from_slice(extension);
+            }
+        }
+        if !kept.is_empty() {
+            contents.extend(der::encode(
+                der::context(3),
+                &der::encode(der::SEQUENCE, &kept),
+            ));
+        }
+    }
+    Some(der::encode(der::SEQUENCE, &contents))
+}
+
+// opaque <0..2^16-1>, returns (contents, rest)
+fn read_u16_vector(buf: &[u8]) -> Option<(&[u8], &[u8])> {
+    if buf.len() < 2 {
+        return None;
+    }
+    let len = bytes_to_u32_be(&buf[..2]) as usize;
+    let rest = &buf[2..];
+    if rest.len() < len {
+        return None;
+    }
+    Some(rest.split_at(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA: &[u8] = include_bytes!("../testdata/ocsp/ca.der");
+    const LEAF: &[u8] = include_bytes!("../testdata/ct/leaf.der");
+    const TLS_SCTS: &[u8] = include_bytes!("../testdata/ct/tls.sct");
+    const OCSP: &[u8] = include_bytes!("../testdata/ct/leaf.ocsp");
+    const LOG_LIST: &[u8] = include_bytes!("../testdata/ct/log_list.json");
+    // the SCTs were issued at 1792396321
+    const NOW: u64 = 1792396321 + 60;
+
+    fn sources(verified: &[VerifiedSct]) -> Vec<(SctSource, &str)> {
+        verified
+            .iter()
+            .map(|sct| (sct.source, sct.log_description.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn verify_scts_from_every_source() {
+        let logs = Arc::new(CtLogList::from_json(LOG_LIST).unwrap());
+        let chain = [LEAF.to_vec(), CA.to_vec()];
+        let tls_scts = SignedCertificateTimestamp::read_list(TLS_SCTS).unwrap();
+        let ocsp = OcspResponse::read(OCSP).unwrap();
+
+        let policy = CtPolicy::new(logs.clone(), 3);
+        let verified = policy.check(&chain, &tls_scts, Some(&ocsp), NOW).unwrap();
+        assert_eq!(
+            sources(&verified),
+            vec![
+                (SctSource::TlsExtension, "Test Log 1"),
+                (SctSource::TlsExtension, "Test Log 2"),
+                (SctSource::Ocsp, "Test Log 2"),
+                (SctSource::Embedded, "Test Log 0"),
+                (SctSource::Embedded, "Test Log 1"),
+            ]
+        );
+
+        // embedded SCTs only, from 2 distinct logs
+        assert_eq!(
+            policy
+                .check(&chain, &[], None, NOW)
+                .unwrap_err()
+                .distinct_logs,
+            2
+        );
+        assert!(CtPolicy::new(logs.clone(), 2)
+            .check(&chain, &[], None, NOW)
+            .is_ok());
+        // SCTs issued after now
+        assert!(CtPolicy::new(logs, 1)
+            .check(&chain, &tls_scts, None, 1792396321 - 60)
+            .is_err());
+    }
+
+    #[test]
+    fn reject_scts_over_other_entries() {
+        let logs = Arc::new(CtLogList::from_json(LOG_LIST).unwrap());
+        // the TLS SCTs sign the leaf, not the CA certificate
+        let tls_scts = SignedCertificateTimestamp::read_list(TLS_SCTS).unwrap();
+        let policy = CtPolicy::new(logs, 1);
+        assert!(policy.check(&[CA.to_vec()], &tls_scts, None, NOW).is_err());
+
+        // an unknown log
+        let policy = CtPolicy::new(Arc::new(CtLogList::default()), 1);
+        assert!(policy
+            .check(&[LEAF.to_vec(), CA.to_vec()], &tls_scts, None, NOW)
+            .is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::NaiveDateTime;
+
+// Ref: https://www.itu.int/rec/T-REC-X.690
+// A minimal DER reader for the structures x509-parser does not decode (OCSP responses).
+// Only definite lengths of up to 4 bytes are accepted.
+pub const BOOLEAN: u8 = 0x01;
+pub const INTEGER: u8 = 0x02;
+pub const BIT_STRING: u8 = 0x03;
+pub const OCTET_STRING: u8 = 0x04;
+pub const OID: u8 = 0x06;
+pub const ENUMERATED: u8 = 0x0a;
+pub const GENERALIZED_TIME: u8 = 0x18;
+pub const SEQUENCE: u8 = 0x30;
+
+// [n] EXPLICIT or constructed IMPLICIT tag
+pub const fn context(n: u8) -> u8 {
+    0xa0 | n
+}
+
+// [n] IMPLICIT tag of a primitive type
+pub const fn context_primitive(n: u8) -> u8 {
+    0x80 | n
+}
+
+// DER encoding of an element with definite length
+pub fn encode(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    if contents.len() < 0x80 {
+        encoded.push(contents.len() as u8);
+    } else {
+        let len = (contents.len() as u32).to_be_bytes();
+        let skip = len.iter().take_while(|&&b| b == 0).count();
+        encoded.push(0x80 | (4 - skip) as u8);
+        encoded.extend_from_slice(&len[skip..]);
+    }
+    encoded.extend_from_slice(contents);
+    encoded
+}
+
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    // returns (tag, contents, the whole encoding)
+    pub fn read_any(&mut self) -> Option<(u8, &'a [u8], &'a [u8])> {
+        let tag = *self.data.first()?;
+        let first = *self.data.get(1)? as usize;
+        let (len, header_len) = if first < 0x80 {
+            (first, 2)
+        } else {
+            let count = first & 0x7f;
+            if count == 0 || count > 4 {
+                return None;
+            }
+            let bytes = self.data.get(2..2 + count)?;
+            let len = bytes.iter().fold(0usize, |len, &b| (len << 8) | b as usize);
+            (len, 2 + count)
+        };
+        let end = header_len.checked_add(len)?;
+        let encoding = self.data.get(..end)?;
+        self.data = &self.data[end..];
+        Some((tag, &encoding[header_len..], encoding))
+    }
+
+    // contents of the next element, None if it does not have the expected tag
+    pub fn read(&mut self, tag: u8) -> Option<&'a [u8]> {
+        if self.peek_tag()? != tag {
+            return None;
+        }
+        let (_, contents, _) = self.read_any()?;
+        Some(contents)
+    }
+
+    // the whole encoding of the next element (tag and length included)
+    pub fn read_raw(&mut self, tag: u8) -> Option<&'a [u8]> {
+        if self.peek_tag()? != tag {
+            return None;
+        }
+        let (_, _, encoding) = self.read_any()?;
+        Some(encoding)
+    }
+
+    // contents of the next element if it has the tag, otherwise nothing is consumed
+    pub fn read_optional(&mut self, tag: u8) -> Option<&'a [u8]> {
+        if self.peek_tag() != Some(tag) {
+            return None;
+        }
+        self.read(tag)
+    }
+
+    // the BIT STRING contents without the unused bits count, which must be 0
+    pub fn read_bit_string(&mut self) -> Option<&'a [u8]> {
+        match self.read(BIT_STRING)? {
+            [0, bits @ ..] => Some(bits),
+            _ => None,
+        }
+    }
+
+    // GeneralizedTime as seconds since the UNIX epoch
+    pub fn read_generalized_time(&mut self) -> Option<u64> {
+        let time = std::str::from_utf8(self.read(GENERALIZED_TIME)?).ok()?;
+        let time = NaiveDateTime::parse_from_str(time, "%Y%m%d%H%M%SZ").ok()?;
+        u64::try_from(time.and_utc().timestamp()).ok()
+    }
+}
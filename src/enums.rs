@@ -1,10 +1,30 @@
-use crate::server_hello::ServerHelloPayload;
+use crate::codec::{Codec, Reader};
 
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7--The-TLS-Handshaking-Protocols
 // enum { warning(1), fatal(2), (255) } AlertLevel;
+#[derive(Debug, PartialEq)]
 pub enum AlertLevel {
     Warning,
     Fatal,
+    Unknown,
+}
+
+impl AlertLevel {
+    pub fn encode(&self) -> u8 {
+        match self {
+            Self::Warning => 0x01,
+            Self::Fatal => 0x02,
+            Self::Unknown => 0xff,
+        }
+    }
+
+    pub fn read(byte: u8) -> Self {
+        match byte {
+            0x01 => Self::Warning,
+            0x02 => Self::Fatal,
+            _ => Self::Unknown,
+        }
+    }
 }
 
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7--The-TLS-Handshaking-Protocols
@@ -35,6 +55,13 @@ pub enum AlertLevel {
 //           unsupported_extension(110),
 //           (255)
 //       } AlertDescription;
+//
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#9--Error-Alerts
+// unrecognized_name(112), bad_certificate_status_response(113)
+//
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc7301.html#3-2--Protocol-Selection
+// no_application_protocol(120)
+#[derive(Debug, PartialEq)]
 pub enum AlertDescription {
     CloseNotify,
     UnexpectedMessage,
@@ -61,6 +88,82 @@ pub enum AlertDescription {
     UserCanceled,
     NoRenegotiation,
     UnsupportedExtension,
+    UnrecognizedName,
+    BadCertificateStatusResponse,
+    NoApplicationProtocol,
+    Unknown,
+}
+
+impl AlertDescription {
+    pub fn encode(&self) -> u8 {
+        use self::AlertDescription::*;
+        match self {
+            CloseNotify => 0,
+            UnexpectedMessage => 10,
+            BadRecordMac => 20,
+            DecryptionFailed => 21,
+            RecordOverflow => 22,
+            DecompressionFailure => 30,
+            HandshakeFailure => 40,
+            NoCertificateReserved => 41,
+            BadCertificate => 42,
+            UnsupportedCertificate => 43,
+            CertificateRevoked => 44,
+            CertificateExpired => 45,
+            CertificateUnknown => 46,
+            IllegalParameter => 47,
+            UnknownCa => 48,
+            AccessDenied => 49,
+            DecodeError => 50,
+            DecryptError => 51,
+            ExportRestrictionReserved => 60,
+            ProtocolVersion => 70,
+            InsufficientSecurity => 71,
+            InternalError => 80,
+            UserCanceled => 90,
+            NoRenegotiation => 100,
+            UnsupportedExtension => 110,
+            UnrecognizedName => 112,
+            BadCertificateStatusResponse => 113,
+            NoApplicationProtocol => 120,
+            Unknown => 255,
+        }
+    }
+
+    pub fn read(byte: u8) -> Self {
+        use self::AlertDescription::*;
+        match byte {
+            0 => CloseNotify,
+            10 => UnexpectedMessage,
+            20 => BadRecordMac,
+            21 => DecryptionFailed,
+            22 => RecordOverflow,
+            30 => DecompressionFailure,
+            40 => HandshakeFailure,
+            41 => NoCertificateReserved,
+            42 => BadCertificate,
+            43 => UnsupportedCertificate,
+            44 => CertificateRevoked,
+            45 => CertificateExpired,
+            46 => CertificateUnknown,
+            47 => IllegalParameter,
+            48 => UnknownCa,
+            49 => AccessDenied,
+            50 => DecodeError,
+            51 => DecryptError,
+            60 => ExportRestrictionReserved,
+            70 => ProtocolVersion,
+            71 => InsufficientSecurity,
+            80 => InternalError,
+            90 => UserCanceled,
+            100 => NoRenegotiation,
+            110 => UnsupportedExtension,
+            112 => UnrecognizedName,
+            113 => BadCertificateStatusResponse,
+            120 => NoApplicationProtocol,
+            _ => Unknown,
+        }
+    }
 }
 
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#A-1--Record-Layer
@@ -68,11 +171,13 @@ pub enum AlertDescription {
 //     change_cipher_spec(20), alert(21), handshake(22),
 //     application_data(23), (255)
 // } ContentType;
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ContentType {
     ChangeCipherSpec,
     Alert,
     Handshake,
     ApplicationData,
+    Unknown,
 }
 
 impl ContentType {
@@ -82,23 +187,37 @@ impl ContentType {
             Self::Alert => 0x15,
             Self::Handshake => 0x16,
             Self::ApplicationData => 0x17,
-            _ => 0xff,
+            Self::Unknown => 0xff,
+        }
+    }
+
+    pub fn read(byte: u8) -> Self {
+        match byte {
+            0x14 => Self::ChangeCipherSpec,
+            0x15 => Self::Alert,
+            0x16 => Self::Handshake,
+            0x17 => Self::ApplicationData,
+            _ => Self::Unknown,
         }
     }
 }
 
 // enum {
 //     hello_request(0), client_hello(1), server_hello(2),
+//     new_session_ticket(4), /* RFC 5077 */
 //     certificate(11), server_key_exchange (12),
 //     certificate_request(13), server_hello_done(14),
 //     certificate_verify(15), client_key_exchange(16),
-//     finished(20), (255)
+//     finished(20),
+//     certificate_status(22), /* RFC 6066 */
+//     (255)
 // } HandshakeType;
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum HandshakeType {
     HelloRequest,
     ClientHello,
     ServerHello,
+    NewSessionTicket,
     Certificate,
     ServerKeyExchange,
     CertificateRequest,
@@ -106,6 +225,7 @@ pub enum HandshakeType {
     CertificateVerify,
     ClientKeyExchange,
     Finished,
+    CertificateStatus,
     Unknown,
 }
 
@@ -116,6 +236,7 @@ impl HandshakeType {
             HelloRequest => 0x00,
             ClientHello => 0x01,
             ServerHello => 0x02,
+            NewSessionTicket => 0x04,
             Certificate => 0x0b,
             ServerKeyExchange => 0x0c,
             CertificateRequest => 0x0d,
@@ -123,7 +244,8 @@ impl HandshakeType {
             CertificateVerify => 0x0f,
             ClientKeyExchange => 0x10,
             Finished => 0x14,
-            _ => 0xff,
+            CertificateStatus => 0x16,
+            Unknown => 0xff,
         }
     }
 
@@ -132,6 +254,7 @@ impl HandshakeType {
             0x00 => Self::HelloRequest,
             0x01 => Self::ClientHello,
             0x02 => Self::ServerHello,
+            0x04 => Self::NewSessionTicket,
             0x0b => Self::Certificate,
             0x0c => Self::ServerKeyExchange,
             0x0d => Self::CertificateRequest,
@@ -139,7 +262,330 @@ impl HandshakeType {
             0x0f => Self::CertificateVerify,
             0x10 => Self::ClientKeyExchange,
             0x14 => Self::Finished,
+            0x16 => Self::CertificateStatus,
             _ => Self::Unknown,
         }
     }
 }
+
+// An enum over a registry of u8 or u16 code points. Values that are not listed are kept in
+// Unknown, so they are encoded back unchanged.
+macro_rules! enum_with_unknown {
+    ($(#[$meta:meta])* pub enum $name:ident: u8 { $($variant:ident = $value:literal,)* }) => {
+        $(#[$meta])*
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($variant,)*
+            Unknown(u8),
+        }
+
+        impl $name {
+            pub fn get_u8(self) -> u8 {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+
+            pub fn from_u8(value: u8) -> Self {
+                match value {
+                    $($value => $name::$variant,)*
+                    _ => $name::Unknown(value),
+                }
+            }
+        }
+
+        impl Codec for $name {
+            fn encode_into(&self, bytes: &mut Vec<u8>) {
+                self.get_u8().encode_into(bytes);
+            }
+
+            fn decode(r: &mut Reader) -> Option<Self> {
+                Some($name::from_u8(u8::decode(r)?))
+            }
+        }
+    };
+    ($(#[$meta:meta])* pub enum $name:ident: u16 { $($variant:ident = $value:literal,)* }) => {
+        $(#[$meta])*
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($variant,)*
+            Unknown(u16),
+        }
+
+        impl $name {
+            pub fn get_u16(self) -> u16 {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+
+            pub fn from_u16(value: u16) -> Self {
+                match value {
+                    $($value => $name::$variant,)*
+                    _ => $name::Unknown(value),
+                }
+            }
+        }
+
+        impl Codec for $name {
+            fn encode_into(&self, bytes: &mut Vec<u8>) {
+                self.get_u16().encode_into(bytes);
+            }
+
+            fn decode(r: &mut Reader) -> Option<Self> {
+                Some($name::from_u16(u16::decode(r)?))
+            }
+        }
+    };
+}
+
+enum_with_unknown! {
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc8422.html#5-1-1--Supported-Elliptic-Curves-Extension
+    // enum {
+    //     deprecated(1..22),
+    //     secp256r1 (23), secp384r1 (24), secp521r1 (25),
+    //     x25519(29), x448(30),
+    //     reserved (0xFE00..0xFEFF),
+    //     deprecated(0xFF01..0xFF02),
+    //     (0xFFFF)
+    // } NamedCurve;
+    //
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc7919.html#2--Named-Group-Overview
+    // ffdhe2048(256), ffdhe3072(257), ffdhe4096(258), ffdhe6144(259), ffdhe8192(260)
+    pub enum NamedGroup: u16 {
+        sect163k1 = 0x0001,
+        sect163r1 = 0x0002,
+        sect163r2 = 0x0003,
+        sect193r1 = 0x0004,
+        sect193r2 = 0x0005,
+        sect233k1 = 0x0006,
+        sect233r1 = 0x0007,
+        sect239k1 = 0x0008,
+        sect283k1 = 0x0009,
+        sect283r1 = 0x000a,
+        sect409k1 = 0x000b,
+        sect409r1 = 0x000c,
+        sect571k1 = 0x000d,
+        sect571r1 = 0x000e,
+        secp160k1 = 0x000f,
+        secp160r1 = 0x0010,
+        secp160r2 = 0x0011,
+        secp192k1 = 0x0012,
+        secp192r1 = 0x0013,
+        secp224k1 = 0x0014,
+        secp224r1 = 0x0015,
+        secp256k1 = 0x0016,
+        secp256r1 = 0x0017,
+        secp384r1 = 0x0018,
+        secp521r1 = 0x0019,
+        x25519 = 0x001d,
+        x448 = 0x001e,
+        ffdhe2048 = 0x0100,
+        ffdhe3072 = 0x0101,
+        ffdhe4096 = 0x0102,
+        ffdhe6144 = 0x0103,
+        ffdhe8192 = 0x0104,
+        arbitrary_explicit_prime_curves = 0xff01,
+        arbitrary_explicit_char2_curves = 0xff02,
+    }
+}
+
+enum_with_unknown! {
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc8422.html#5-1-2--Supported-Point-Formats-Extension
+    // enum {
+    //     uncompressed (0),
+    //     deprecated (1..2),
+    //     reserved (248..255)
+    // } ECPointFormat;
+    //
+    // RFC 4492 named the deprecated values ansiX962_compressed_prime and ansiX962_compressed_char2.
+    pub enum ECPointFormat: u8 {
+        uncompressed = 0,
+        ansiX962_compressed_prime = 1,
+        ansiX962_compressed_char2 = 2,
+    }
+}
+
+enum_with_unknown! {
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc8422.html#5-4--Server-Key-Exchange
+    // enum {
+    //     deprecated (1..2),
+    //     named_curve (3),
+    //     reserved(248..255)
+    // } ECCurveType;
+    pub enum ECCurveType: u8 {
+        explicit_prime = 1,
+        explicit_char2 = 2,
+        named_curve = 3,
+    }
+}
+
+enum_with_unknown! {
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-1-4-1--Signature-Algorithms
+    // enum {
+    //     none(0), md5(1), sha1(2), sha224(3), sha256(4), sha384(5),
+    //     sha512(6), (255)
+    // } HashAlgorithm;
+    //
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc8422.html#5-1-3--Signature-Algorithms-Extension
+    // Intrinsic(8) is the hash of Ed25519 and Ed448, which hash the message themselves.
+    pub enum HashAlgorithm: u8 {
+        none = 0,
+        md5 = 1,
+        sha1 = 2,
+        sha224 = 3,
+        sha256 = 4,
+        sha384 = 5,
+        sha512 = 6,
+        intrinsic = 8,
+    }
+}
+
+enum_with_unknown! {
+    // enum { anonymous(0), rsa(1), dsa(2), ecdsa(3), (255) } SignatureAlgorithm;
+    //
+    // ed25519(7) and ed448(8) come from RFC 8422.
+    pub enum SignatureAlgorithm: u8 {
+        anonymous = 0,
+        rsa = 1,
+        dsa = 2,
+        ecdsa = 3,
+        ed25519 = 7,
+        ed448 = 8,
+    }
+}
+
+// struct {
+//     HashAlgorithm hash;
+//     SignatureAlgorithm signature;
+// } SignatureAndHashAlgorithm;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureAndHashAlgorithm {
+    pub hash: HashAlgorithm,
+    pub signature: SignatureAlgorithm,
+}
+
+impl Codec for SignatureAndHashAlgorithm {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        self.hash.encode_into(bytes);
+        self.signature.encode_into(bytes);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(SignatureAndHashAlgorithm {
+            hash: HashAlgorithm::decode(r)?,
+            signature: SignatureAlgorithm::decode(r)?,
+        })
+    }
+}
+
+enum_with_unknown! {
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc8446.html#4-2-3--Signature-Algorithms
+    // SignatureScheme values. In TLS 1.2 the same two bytes are a SignatureAndHashAlgorithm
+    // {hash, signature}, see hash_and_signature. The rsa_pss_* values are from RFC 8446 and
+    // are usable in TLS 1.2 as well.
+    pub enum SignatureScheme: u16 {
+        rsa_pkcs1_sha1 = 0x0201,
+        ecdsa_sha1 = 0x0203,
+        rsa_pkcs1_sha256 = 0x0401,
+        ecdsa_secp256r1_sha256 = 0x0403,
+        rsa_pkcs1_sha384 = 0x0501,
+        ecdsa_secp384r1_sha384 = 0x0503,
+        rsa_pkcs1_sha512 = 0x0601,
+        ecdsa_secp521r1_sha512 = 0x0603,
+        rsa_pss_rsae_sha256 = 0x0804,
+        rsa_pss_rsae_sha384 = 0x0805,
+        rsa_pss_rsae_sha512 = 0x0806,
+        ed25519 = 0x0807,
+        ed448 = 0x0808,
+        rsa_pss_pss_sha256 = 0x0809,
+        rsa_pss_pss_sha384 = 0x080a,
+        rsa_pss_pss_sha512 = 0x080b,
+    }
+}
+
+impl SignatureScheme {
+    // the TLS 1.2 reading of the value. ecdsa_secp256r1_sha256 is {sha256, ecdsa}, which
+    // does not restrict the curve.
+    pub fn hash_and_signature(self) -> SignatureAndHashAlgorithm {
+        let [hash, signature] = self.get_u16().to_be_bytes();
+        SignatureAndHashAlgorithm {
+            hash: HashAlgorithm::from_u8(hash),
+            signature: SignatureAlgorithm::from_u8(signature),
+        }
+    }
+}
+
+impl From<SignatureAndHashAlgorithm> for SignatureScheme {
+    fn from(algorithm: SignatureAndHashAlgorithm) -> Self {
+        SignatureScheme::from_u16(u16::from_be_bytes([
+            algorithm.hash.get_u8(),
+            algorithm.signature.get_u8(),
+        ]))
+    }
+}
+
+enum_with_unknown! {
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#4--Maximum-Fragment-Length-Negotiation
+    // enum{
+    //     2^9(1), 2^10(2), 2^11(3), 2^12(4), (255)
+    // } MaxFragmentLength;
+    pub enum MaxFragmentLength: u8 {
+        len_512 = 1,
+        len_1024 = 2,
+        len_2048 = 3,
+        len_4096 = 4,
+    }
+}
+
+impl MaxFragmentLength {
+    // the limit on TLSPlaintext.length, None for values outside the enum
+    pub fn fragment_len(self) -> Option<usize> {
+        match self {
+            MaxFragmentLength::len_512 => Some(512),
+            MaxFragmentLength::len_1024 => Some(1024),
+            MaxFragmentLength::len_2048 => Some(2048),
+            MaxFragmentLength::len_4096 => Some(4096),
+            MaxFragmentLength::Unknown(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::decode_all;
+
+    #[test]
+    fn unknown_values_round_trip() {
+        assert_eq!(decode_all(&[0x00, 0x1d]), Some(NamedGroup::x25519));
+        assert_eq!(decode_all(&[0x0a, 0x0a]), Some(NamedGroup::Unknown(0x0a0a)));
+        assert_eq!(NamedGroup::Unknown(0x0a0a).get_encoding(), [0x0a, 0x0a]);
+        assert_eq!(decode_all(&[0x00]), Some(ECPointFormat::uncompressed));
+        assert_eq!(ECPointFormat::Unknown(0xf8).get_encoding(), [0xf8]);
+
+        let scheme = SignatureScheme::ecdsa_secp256r1_sha256;
+        assert_eq!(
+            scheme.hash_and_signature(),
+            SignatureAndHashAlgorithm {
+                hash: HashAlgorithm::sha256,
+                signature: SignatureAlgorithm::ecdsa,
+            }
+        );
+        assert_eq!(SignatureScheme::from(scheme.hash_and_signature()), scheme);
+        assert_eq!(
+            decode_all::<SignatureAndHashAlgorithm>(&[0x08, 0x07]).map(SignatureScheme::from),
+            Some(SignatureScheme::ed25519)
+        );
+        assert_eq!(
+            SignatureScheme::from_u16(0x0702)
+                .hash_and_signature()
+                .signature,
+            SignatureAlgorithm::dsa
+        );
+    }
+}
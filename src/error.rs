@@ -0,0 +1,178 @@
+use crate::enums::AlertDescription;
+use std::fmt;
+use std::io;
+
+// Errors of a TLS connection. Every error detected locally maps to the fatal alert that is
+// sent to the peer, see Error::alert. Alerts received from the peer and I/O errors are
+// reported without sending an alert.
+#[derive(Debug)]
+pub enum Error {
+    // a message or record that cannot be parsed, e.g. truncated or with trailing bytes.
+    // Names the structure that failed to parse.
+    Decode(&'static str),
+    // a message that is not allowed in the current state of the handshake
+    UnexpectedMessage(String),
+    // no common parameters, or the peer selected parameters we did not offer
+    HandshakeFailure(String),
+    // a field with a value that is not allowed, e.g. an invalid public key
+    IllegalParameter(String),
+    ProtocolVersion(String),
+    // the server has no certificate for the requested server name
+    UnrecognizedName(String),
+    // the client offered ALPN protocols, none of which the server supports
+    NoApplicationProtocol(String),
+    // a record that does not authenticate
+    BadRecordMac,
+    // a record longer than the limit of RFC 5246 6.2.3
+    RecordOverflow,
+    // a signature or Finished verify_data that does not verify
+    Crypto(String),
+    Certificate(CertificateError),
+    // a local failure, e.g. the private key cannot sign
+    Internal(String),
+    // a fatal alert from the peer, or close_notify during the handshake
+    AlertReceived(AlertDescription),
+    // renegotiation cannot start: secure renegotiation is not enabled or not supported by
+    // the peer, or a handshake is in progress. The connection is not affected.
+    Renegotiation(String),
+    Io(io::Error),
+}
+
+// Why the peer's certificates were rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CertificateError {
+    // the certificate cannot be parsed, does not match a pin or the server name
+    Bad(String),
+    // the chain does not lead to a trust anchor
+    UnknownIssuer(String),
+    // a certificate of the chain is not valid at the current time
+    Expired(String),
+    // the certificate has a key this crate cannot use
+    Unsupported(String),
+    Revoked(String),
+    // the revocation status or the CT policy cannot be satisfied
+    Unknown(String),
+    // the stapled OCSP response is malformed or does not verify
+    BadStatusResponse(String),
+}
+
+impl Error {
+    // the fatal alert to send for this error, None if no alert is sent
+    pub fn alert(&self) -> Option<AlertDescription> {
+        match self {
+            Error::Decode(_) => Some(AlertDescription::DecodeError),
+            Error::UnexpectedMessage(_) => Some(AlertDescription::UnexpectedMessage),
+            Error::HandshakeFailure(_) => Some(AlertDescription::HandshakeFailure),
+            Error::IllegalParameter(_) => Some(AlertDescription::IllegalParameter),
+            Error::ProtocolVersion(_) => Some(AlertDescription::ProtocolVersion),
+            Error::UnrecognizedName(_) => Some(AlertDescription::UnrecognizedName),
+            Error::NoApplicationProtocol(_) => Some(AlertDescription::NoApplicationProtocol),
+            Error::BadRecordMac => Some(AlertDescription::BadRecordMac),
+            Error::RecordOverflow => Some(AlertDescription::RecordOverflow),
+            Error::Crypto(_) => Some(AlertDescription::DecryptError),
+            Error::Certificate(error) => Some(match error {
+                CertificateError::Bad(_) => AlertDescription::BadCertificate,
+                CertificateError::UnknownIssuer(_) => AlertDescription::UnknownCa,
+                CertificateError::Expired(_) => AlertDescription::CertificateExpired,
+                CertificateError::Unsupported(_) => AlertDescription::UnsupportedCertificate,
+                CertificateError::Revoked(_) => AlertDescription::CertificateRevoked,
+                CertificateError::Unknown(_) => AlertDescription::CertificateUnknown,
+                CertificateError::BadStatusResponse(_) => {
+                    AlertDescription::BadCertificateStatusResponse
+                }
+            }),
+            Error::Internal(_) => Some(AlertDescription::InternalError),
+            Error::AlertReceived(_) | Error::Renegotiation(_) | Error::Io(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Decode(structure) => write!(f, "malformed {}", structure),
+            Error::UnexpectedMessage(message)
+            | Error::HandshakeFailure(message)
+            | Error::IllegalParameter(message)
+            | Error::ProtocolVersion(message)
+            | Error::UnrecognizedName(message)
+            | Error::NoApplicationProtocol(message)
+            | Error::Crypto(message)
+            | Error::Internal(message)
+            | Error::Renegotiation(message) => write!(f, "{}", message),
+            Error::BadRecordMac => write!(f, "record failed to authenticate"),
+            Error::RecordOverflow => write!(f, "record too long"),
+            Error::Certificate(error) => write!(f, "{}", error),
+            Error::AlertReceived(description) => write!(f, "received alert: {:?}", description),
+            Error::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CertificateError::Bad(message)
+            | CertificateError::UnknownIssuer(message)
+            | CertificateError::Expired(message)
+            | CertificateError::Unsupported(message)
+            | CertificateError::Revoked(message)
+            | CertificateError::Unknown(message)
+            | CertificateError::BadStatusResponse(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<CertificateError> for Error {
+    fn from(error: CertificateError) -> Self {
+        Error::Certificate(error)
+    }
+}
+
+// for callers that work with io::Result, e.g. Read and Write adapters
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io(error) => error,
+            Error::AlertReceived(_) => io::Error::new(io::ErrorKind::ConnectionAborted, error),
+            error => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+// A ClientConfig or ServerConfig that cannot work, reported by the builder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    message: String,
+}
+
+impl ConfigError {
+    pub fn new(message: impl Into<String>) -> Self {
+        ConfigError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid configuration: {}", self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
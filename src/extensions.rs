@@ -0,0 +1,420 @@
+use crate::codec::{
+    encode_opaque, encode_opaque_vec, encode_vec, read_opaque, read_opaque_vec, read_vec, Codec,
+    LengthPrefix, Reader,
+};
+use crate::enums::{ECPointFormat, MaxFragmentLength, NamedGroup, SignatureScheme};
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-1-4--Hello-Extensions
+// struct {
+//     ExtensionType extension_type;
+//     opaque extension_data<0..2^16-1>;
+// } Extension;
+pub const SERVER_NAME: u16 = 0;
+pub const MAX_FRAGMENT_LENGTH: u16 = 1;
+pub const STATUS_REQUEST: u16 = 5;
+pub const SUPPORTED_GROUPS: u16 = 10;
+pub const EC_POINT_FORMATS: u16 = 11;
+pub const SIGNATURE_ALGORITHMS: u16 = 13;
+pub const APPLICATION_LAYER_PROTOCOL_NEGOTIATION: u16 = 16;
+pub const SIGNED_CERTIFICATE_TIMESTAMP: u16 = 18;
+pub const EXTENDED_MASTER_SECRET: u16 = 23;
+pub const SESSION_TICKET: u16 = 35;
+pub const RENEGOTIATION_INFO: u16 = 0xff01;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#3--Server-Name-Indication
+// enum { host_name(0), (255) } NameType;
+const HOST_NAME: u8 = 0;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#8--Certificate-Status-Request
+// enum { ocsp(1), (255) } CertificateStatusType;
+const OCSP: u8 = 1;
+
+// Extensions of ClientHello. The extension_data of known types is parsed, others are kept
+// as they are.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientExtension {
+    // host_name of the server_name extension
+    ServerName(String),
+    MaxFragmentLength(MaxFragmentLength),
+    StatusRequest(CertificateStatusRequest),
+    // supported_groups (elliptic_curves in RFC 4492)
+    NamedGroups(Vec<NamedGroup>),
+    ECPointFormats(Vec<ECPointFormat>),
+    SignatureAlgorithms(Vec<SignatureScheme>),
+    // ALPN protocol names in the client's order of preference
+    Protocols(Vec<Vec<u8>>),
+    SignedCertificateTimestampRequest,
+    ExtendedMasterSecretRequest,
+    // empty to request a new ticket
+    SessionTicket(Vec<u8>),
+    // renegotiated_connection
+    RenegotiationInfo(Vec<u8>),
+    Unknown(u16, Vec<u8>),
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#8--Certificate-Status-Request
+// struct {
+//     CertificateStatusType status_type;
+//     select (status_type) {
+//         case ocsp: OCSPStatusRequest;
+//     } request;
+// } CertificateStatusRequest;
+//
+// struct {
+//     ResponderID responder_id_list<0..2^16-1>;
+//     Extensions  request_extensions;
+// } OCSPStatusRequest;
+//
+// opaque ResponderID<1..2^16-1>;
+// opaque Extensions<0..2^16-1>;
+#[derive(Debug, Clone, PartialEq)]
+pub enum CertificateStatusRequest {
+    Ocsp {
+        responder_ids: Vec<Vec<u8>>,
+        // DER encoded request extensions
+        extensions: Vec<u8>,
+    },
+    Unknown(u8, Vec<u8>),
+}
+
+impl CertificateStatusRequest {
+    // any responder, no request extensions
+    pub fn ocsp() -> Self {
+        CertificateStatusRequest::Ocsp {
+            responder_ids: Vec::new(),
+            extensions: Vec::new(),
+        }
+    }
+}
+
+impl Codec for CertificateStatusRequest {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        match self {
+            CertificateStatusRequest::Ocsp {
+                responder_ids,
+                extensions,
+            } => {
+                bytes.push(OCSP);
+                encode_opaque_vec(bytes, LengthPrefix::U16, LengthPrefix::U16, responder_ids);
+                encode_opaque(bytes, LengthPrefix::U16, extensions);
+            }
+            CertificateStatusRequest::Unknown(status_type, request) => {
+                bytes.push(*status_type);
+                bytes.extend_from_slice(request);
+            }
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        match u8::decode(r)? {
+            OCSP => Some(CertificateStatusRequest::Ocsp {
+                responder_ids: read_opaque_vec(r, LengthPrefix::U16, LengthPrefix::U16)?,
+                extensions: read_opaque(r, LengthPrefix::U16)?.to_vec(),
+            }),
+            status_type => Some(CertificateStatusRequest::Unknown(
+                status_type,
+                r.rest().to_vec(),
+            )),
+        }
+    }
+}
+
+impl ClientExtension {
+    pub fn extension_type(&self) -> u16 {
+        match self {
+            ClientExtension::ServerName(_) => SERVER_NAME,
+            ClientExtension::MaxFragmentLength(_) => MAX_FRAGMENT_LENGTH,
+            ClientExtension::StatusRequest(_) => STATUS_REQUEST,
+            ClientExtension::NamedGroups(_) => SUPPORTED_GROUPS,
+            ClientExtension::ECPointFormats(_) => EC_POINT_FORMATS,
+            ClientExtension::SignatureAlgorithms(_) => SIGNATURE_ALGORITHMS,
+            ClientExtension::Protocols(_) => APPLICATION_LAYER_PROTOCOL_NEGOTIATION,
+            ClientExtension::SignedCertificateTimestampRequest => SIGNED_CERTIFICATE_TIMESTAMP,
+            ClientExtension::ExtendedMasterSecretRequest => EXTENDED_MASTER_SECRET,
+            ClientExtension::SessionTicket(_) => SESSION_TICKET,
+            ClientExtension::RenegotiationInfo(_) => RENEGOTIATION_INFO,
+            ClientExtension::Unknown(extension_type, _) => *extension_type,
+        }
+    }
+
+    fn encode_data(&self, bytes: &mut Vec<u8>) {
+        match self {
+            // struct {
+            //     NameType name_type;
+            //     select (name_type) {
+            //         case host_name: HostName;
+            //     } name;
+            // } ServerName;
+            //
+            // opaque HostName<1..2^16-1>;
+            //
+            // struct {
+            //     ServerName server_name_list<1..2^16-1>
+            // } ServerNameList;
+            ClientExtension::ServerName(host_name) => {
+                let mut server_name = vec![HOST_NAME];
+                encode_opaque(&mut server_name, LengthPrefix::U16, host_name.as_bytes());
+                encode_opaque(bytes, LengthPrefix::U16, &server_name);
+            }
+            ClientExtension::MaxFragmentLength(max_fragment_length) => {
+                max_fragment_length.encode_into(bytes)
+            }
+            ClientExtension::StatusRequest(request) => request.encode_into(bytes),
+            // NamedCurve named_curve_list<2..2^16-1>
+            ClientExtension::NamedGroups(groups) => encode_vec(bytes, LengthPrefix::U16, groups),
+            // ECPointFormat ec_point_format_list<1..2^8-1>
+            ClientExtension::ECPointFormats(formats) => {
+                encode_vec(bytes, LengthPrefix::U8, formats)
+            }
+            // SignatureAndHashAlgorithm supported_signature_algorithms<2..2^16-2>
+            ClientExtension::SignatureAlgorithms(schemes) => {
+                encode_vec(bytes, LengthPrefix::U16, schemes)
+            }
+            // opaque ProtocolName<1..2^8-1>;
+            // ProtocolName protocol_name_list<2..2^16-1>
+            ClientExtension::Protocols(protocols) => {
+                encode_opaque_vec(bytes, LengthPrefix::U16, LengthPrefix::U8, protocols)
+            }
+            ClientExtension::SignedCertificateTimestampRequest
+            | ClientExtension::ExtendedMasterSecretRequest => {}
+            ClientExtension::SessionTicket(ticket) => bytes.extend_from_slice(ticket),
+            // opaque renegotiated_connection<0..255>;
+            ClientExtension::RenegotiationInfo(renegotiated_connection) => {
+                encode_opaque(bytes, LengthPrefix::U8, renegotiated_connection)
+            }
+            ClientExtension::Unknown(_, data) => bytes.extend_from_slice(data),
+        }
+    }
+
+    fn decode_data(extension_type: u16, r: &mut Reader) -> Option<Self> {
+        Some(match extension_type {
+            SERVER_NAME => {
+                let mut server_names = Reader::new(read_opaque(r, LengthPrefix::U16)?);
+                let mut host_name = None;
+                while server_names.any_left() {
+                    let name_type = u8::decode(&mut server_names)?;
+                    let name = read_opaque(&mut server_names, LengthPrefix::U16)?;
+                    if name_type == HOST_NAME && host_name.is_none() {
+                        host_name = Some(String::from_utf8(name.to_vec()).ok()?);
+                    }
+                }
+                ClientExtension::ServerName(host_name?)
+            }
+            MAX_FRAGMENT_LENGTH => {
+                ClientExtension::MaxFragmentLength(MaxFragmentLength::decode(r)?)
+            }
+            STATUS_REQUEST => ClientExtension::StatusRequest(CertificateStatusRequest::decode(r)?),
+            SUPPORTED_GROUPS => ClientExtension::NamedGroups(read_vec(r, LengthPrefix::U16)?),
+            EC_POINT_FORMATS => ClientExtension::ECPointFormats(read_vec(r, LengthPrefix::U8)?),
+            SIGNATURE_ALGORITHMS => {
+                ClientExtension::SignatureAlgorithms(read_vec(r, LengthPrefix::U16)?)
+            }
+            APPLICATION_LAYER_PROTOCOL_NEGOTIATION => {
+                ClientExtension::Protocols(read_opaque_vec(r, LengthPrefix::U16, LengthPrefix::U8)?)
+            }
+            SIGNED_CERTIFICATE_TIMESTAMP => ClientExtension::SignedCertificateTimestampRequest,
+            EXTENDED_MASTER_SECRET => ClientExtension::ExtendedMasterSecretRequest,
+            SESSION_TICKET => ClientExtension::SessionTicket(r.rest().to_vec()),
+            RENEGOTIATION_INFO => {
+                ClientExtension::RenegotiationInfo(read_opaque(r, LengthPrefix::U8)?.to_vec())
+            }
+            _ => ClientExtension::Unknown(extension_type, r.rest().to_vec()),
+        })
+    }
+}
+
+impl Codec for ClientExtension {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        self.extension_type().encode_into(bytes);
+        let mut data = Vec::new();
+        self.encode_data(&mut data);
+        encode_opaque(bytes, LengthPrefix::U16, &data);
+    }
+
+    // extension_data must be used up by the extension
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let extension_type = u16::decode(r)?;
+        let mut data = Reader::new(read_opaque(r, LengthPrefix::U16)?);
+        let extension = Self::decode_data(extension_type, &mut data)?;
+        if data.any_left() {
+            return None;
+        }
+        Some(extension)
+    }
+}
+
+// Extensions of ServerHello.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerExtension {
+    // the server used the server_name, the extension_data is empty
+    ServerNameAck,
+    // the server accepted the client's max_fragment_length, it echoes the value
+    MaxFragmentLength(MaxFragmentLength),
+    ECPointFormats(Vec<ECPointFormat>),
+    // the selected ALPN protocol
+    Protocol(Vec<u8>),
+    // SerializedSCTs of a SignedCertificateTimestampList
+    SignedCertificateTimestamp(Vec<Vec<u8>>),
+    ExtendedMasterSecretAck,
+    // the server will send NewSessionTicket
+    SessionTicketAck,
+    // the server will send CertificateStatus
+    CertificateStatusAck,
+    // renegotiated_connection
+    RenegotiationInfo(Vec<u8>),
+    Unknown(u16, Vec<u8>),
+}
+
+impl ServerExtension {
+    pub fn extension_type(&self) -> u16 {
+        match self {
+            ServerExtension::ServerNameAck => SERVER_NAME,
+            ServerExtension::MaxFragmentLength(_) => MAX_FRAGMENT_LENGTH,
+            ServerExtension::ECPointFormats(_) => EC_POINT_FORMATS,
+            ServerExtension::Protocol(_) => APPLICATION_LAYER_PROTOCOL_NEGOTIATION,
+            ServerExtension::SignedCertificateTimestamp(_) => SIGNED_CERTIFICATE_TIMESTAMP,
+            ServerExtension::ExtendedMasterSecretAck => EXTENDED_MASTER_SECRET,
+            ServerExtension::SessionTicketAck => SESSION_TICKET,
+            ServerExtension::CertificateStatusAck => STATUS_REQUEST,
+            ServerExtension::RenegotiationInfo(_) => RENEGOTIATION_INFO,
+            ServerExtension::Unknown(extension_type, _) => *extension_type,
+        }
+    }
+
+    fn encode_data(&self, bytes: &mut Vec<u8>) {
+        match self {
+            ServerExtension::ServerNameAck
+            | ServerExtension::ExtendedMasterSecretAck
+            | ServerExtension::SessionTicketAck
+            | ServerExtension::CertificateStatusAck => {}
+            ServerExtension::MaxFragmentLength(max_fragment_length) => {
+                max_fragment_length.encode_into(bytes)
+            }
+            ServerExtension::ECPointFormats(formats) => {
+                encode_vec(bytes, LengthPrefix::U8, formats)
+            }
+            // the protocol_name_list contains exactly one name
+            ServerExtension::Protocol(protocol) => encode_opaque_vec(
+                bytes,
+                LengthPrefix::U16,
+                LengthPrefix::U8,
+                std::slice::from_ref(protocol),
+            ),
+            // Ref: https://tex2e.github.io/rfc-translater/html/rfc6962.html#3-3-1--TLS-Extension
+            // opaque SerializedSCT<1..2^16-1>;
+            // struct {
+            //     SerializedSCT sct_list <1..2^16-1>;
+            // } SignedCertificateTimestampList;
+            ServerExtension::SignedCertificateTimestamp(scts) => {
+                encode_opaque_vec(bytes, LengthPrefix::U16, LengthPrefix::U16, scts)
+            }
+            ServerExtension::RenegotiationInfo(renegotiated_connection) => {
+                encode_opaque(bytes, LengthPrefix::U8, renegotiated_connection)
+            }
+            ServerExtension::Unknown(_, data) => bytes.extend_from_slice(data),
+        }
+    }
+
+    fn decode_data(extension_type: u16, r: &mut Reader) -> Option<Self> {
+        Some(match extension_type {
+            SERVER_NAME => ServerExtension::ServerNameAck,
+            MAX_FRAGMENT_LENGTH => {
+                ServerExtension::MaxFragmentLength(MaxFragmentLength::decode(r)?)
+            }
+            EC_POINT_FORMATS => ServerExtension::ECPointFormats(read_vec(r, LengthPrefix::U8)?),
+            APPLICATION_LAYER_PROTOCOL_NEGOTIATION => {
+                let [protocol]: [Vec<u8>; 1] =
+                    read_opaque_vec(r, LengthPrefix::U16, LengthPrefix::U8)?
+                        .try_into()
+                        .ok()?;
+                ServerExtension::Protocol(protocol)
+            }
+            SIGNED_CERTIFICATE_TIMESTAMP => ServerExtension::SignedCertificateTimestamp(
+                read_opaque_vec(r, LengthPrefix::U16, LengthPrefix::U16)?,
+            ),
+            EXTENDED_MASTER_SECRET => ServerExtension::ExtendedMasterSecretAck,
+            SESSION_TICKET => ServerExtension::SessionTicketAck,
+            STATUS_REQUEST => ServerExtension::CertificateStatusAck,
+            RENEGOTIATION_INFO => {
+                ServerExtension::RenegotiationInfo(read_opaque(r, LengthPrefix::U8)?.to_vec())
+            }
+            _ => ServerExtension::Unknown(extension_type, r.rest().to_vec()),
+        })
+    }
+}
+
+impl Codec for ServerExtension {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        self.extension_type().encode_into(bytes);
+        let mut data = Vec::new();
+        self.encode_data(&mut data);
+        encode_opaque(bytes, LengthPrefix::U16, &data);
+    }
+
+    // extension_data must be used up by the extension, acknowledgements are empty
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let extension_type = u16::decode(r)?;
+        let mut data = Reader::new(read_opaque(r, LengthPrefix::U16)?);
+        let extension = Self::decode_data(extension_type, &mut data)?;
+        if data.any_left() {
+            return None;
+        }
+        Some(extension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::decode_all;
+
+    #[test]
+    fn extensions_round_trip() {
+        let client_extensions = vec![
+            ClientExtension::ServerName(String::from("example.com")),
+            ClientExtension::MaxFragmentLength(MaxFragmentLength::len_2048),
+            ClientExtension::StatusRequest(CertificateStatusRequest::ocsp()),
+            ClientExtension::NamedGroups(vec![NamedGroup::x25519, NamedGroup::Unknown(0x0a0a)]),
+            ClientExtension::ECPointFormats(vec![ECPointFormat::uncompressed]),
+            ClientExtension::SignatureAlgorithms(vec![SignatureScheme::ecdsa_secp256r1_sha256]),
+            ClientExtension::Protocols(vec![b"h2".to_vec(), b"http/1.1".to_vec()]),
+            ClientExtension::SignedCertificateTimestampRequest,
+            ClientExtension::ExtendedMasterSecretRequest,
+            ClientExtension::SessionTicket(vec![0x42; 16]),
+            ClientExtension::RenegotiationInfo(vec![]),
+            ClientExtension::Unknown(0x1234, vec![1, 2, 3]),
+        ];
+        let mut bytes = Vec::new();
+        encode_vec(&mut bytes, LengthPrefix::U16, &client_extensions);
+        assert_eq!(
+            read_vec(&mut Reader::new(&bytes), LengthPrefix::U16),
+            Some(client_extensions)
+        );
+
+        let server_extensions = vec![
+            ServerExtension::ServerNameAck,
+            ServerExtension::MaxFragmentLength(MaxFragmentLength::len_2048),
+            ServerExtension::Protocol(b"h2".to_vec()),
+            ServerExtension::SignedCertificateTimestamp(vec![vec![0; 4], vec![1; 8]]),
+            ServerExtension::SessionTicketAck,
+            ServerExtension::CertificateStatusAck,
+            ServerExtension::RenegotiationInfo(vec![7; 24]),
+        ];
+        let mut bytes = Vec::new();
+        encode_vec(&mut bytes, LengthPrefix::U16, &server_extensions);
+        assert_eq!(
+            read_vec(&mut Reader::new(&bytes), LengthPrefix::U16),
+            Some(server_extensions)
+        );
+
+        // an acknowledgement with extension_data, a renegotiation_info with a wrong length
+        assert_eq!(
+            decode_all::<ServerExtension>(&[0x00, 0x23, 0x00, 0x01, 0x00]),
+            None
+        );
+        assert_eq!(
+            decode_all::<ClientExtension>(&[0xff, 0x01, 0x00, 0x02, 0x02, 0x00]),
+            None
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use crate::codec::{decode_all, Codec, Reader};
+use crate::enums::HandshakeType;
+use crate::error::Error;
+use crate::handshake::HandshakePayload;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-9--Finished
+// struct {
+//     opaque verify_data[verify_data_length];
+// } Finished;
+//
+// verify_data_length is 12 for all cipher suites this crate supports.
+const VERIFY_DATA_LEN: usize = 12;
+
+#[derive(Debug)]
+pub struct Finished {
+    handshake_type: HandshakeType,
+    verify_data: Vec<u8>,
+}
+
+impl Finished {
+    pub fn new(verify_data: Vec<u8>) -> Self {
+        Finished {
+            handshake_type: HandshakeType::Finished,
+            verify_data,
+        }
+    }
+
+    pub fn read(buf: &[u8]) -> Result<Self, Error> {
+        decode_all(buf).ok_or(Error::Decode("Finished"))
+    }
+
+    pub fn verify_data(&self) -> &[u8] {
+        &self.verify_data
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        HandshakePayload::encode_message(self.handshake_type, &self.get_encoding())
+    }
+}
+
+impl Codec for Finished {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.verify_data);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(Finished::new(r.take(VERIFY_DATA_LEN)?.to_vec()))
+    }
+}
@@ -1,33 +1,102 @@
-use crate::certificate::Certificate;
-use crate::client_hello::ClientHelloPayload;
-use crate::enums;
-use crate::enums::ContentType::Handshake;
-use crate::enums::HandshakeType::ClientHello;
+use crate::cipher_suite::CipherSuiteParams;
+use crate::codec::{decode_all, Codec, Reader};
 use crate::enums::{AlertDescription, AlertLevel, HandshakeType};
-use crate::protocol_version::ProtocolVersion;
-use crate::server_hello::ServerHelloPayload;
-use crate::server_key_exchange::ServerKeyExchange;
-use crate::tls_plaintext::TLSPlaintext;
-use chrono::{DateTime, Utc};
-use rustls::internal::msgs::codec::Codec;
-use rustls::internal::msgs::handshake::ServerExtension;
-use crate::server_hello_done::ServerHelloDone;
+use crate::error::Error;
+use crate::prf::prf;
+use byteorder::{BigEndian, ByteOrder};
+use chrono::{DateTime, TimeZone, Utc};
+use rand::RngCore;
+use ring::digest;
 
 // Ref: https://github.com/rustls/rustls/blob/main/rustls/src/msgs/handshake.rs#L108-L111
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SessionId {
     pub len: usize,
     pub data: [u8; 32],
 }
 
+impl SessionId {
+    pub fn empty() -> Self {
+        SessionId {
+            len: 0,
+            data: [0; 32],
+        }
+    }
+
+    // opaque SessionID<0..32>;
+    pub fn new(bytes: &[u8]) -> Self {
+        let mut data = [0; 32];
+        data[..bytes.len()].copy_from_slice(bytes);
+        SessionId {
+            len: bytes.len(),
+            data,
+        }
+    }
+
+    // a client offering a session ticket sends a random session_id to detect resumption
+    pub fn random() -> Self {
+        let mut data = [0; 32];
+        rand::thread_rng().fill_bytes(&mut data);
+        SessionId { len: 32, data }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Codec for SessionId {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.push(self.len as u8);
+        bytes.extend_from_slice(self.as_slice());
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let len = u8::decode(r)? as usize;
+        if len > 32 {
+            return None;
+        }
+        Some(SessionId::new(r.take(len)?))
+    }
+}
+
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-2--Alert-Protocol
 // struct {
 //           AlertLevel level;
 //           AlertDescription description;
 //       } Alert;
-struct Alert {
-    level: AlertLevel,
-    description: AlertDescription,
+#[derive(Debug)]
+pub struct Alert {
+    pub level: AlertLevel,
+    pub description: AlertDescription,
+}
+
+impl Alert {
+    pub fn read(buffer: &[u8]) -> Result<Self, Error> {
+        decode_all(buffer).ok_or(Error::Decode("Alert"))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        self.get_encoding()
+    }
+}
+
+impl Codec for Alert {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.push(self.level.encode());
+        bytes.push(self.description.encode());
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(Alert {
+            level: AlertLevel::read(u8::decode(r)?),
+            description: AlertDescription::read(u8::decode(r)?),
+        })
+    }
 }
 
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4--Handshake-Protocol
@@ -48,101 +117,156 @@ struct Alert {
 //           } body;
 //       } Handshake;
 #[derive(Debug)]
-pub struct HandshakePayload {
-    msg_type: HandshakeType,
-    length: u32,
-}
+pub struct HandshakePayload;
 
 // MasterSecretの情報を格納
 // ref: https://github.com/sat0ken/go-tcpip/blob/fc2b35be0ca462df93c33c22b0081c06ee4c8788/tls_type.go#L171
 #[derive(Debug)]
 pub struct MasterSecretInfo {
-    MasterSecret: Vec<u8>,
-    PreMasterSecret: Vec<u8>,
-    ClientRandom: Vec<u8>,
-    ServerRandom: Vec<u8>,
+    pub master_secret: Vec<u8>,
+    pub pre_master_secret: Vec<u8>,
+    pub client_random: Vec<u8>,
+    pub server_random: Vec<u8>,
+    // the negotiated suite, it selects the PRF hash and the size of the key block
+    suite: &'static CipherSuiteParams,
+    // the master secret is bound to the handshake (RFC 7627)
+    extended_master_secret: bool,
 }
 
-impl HandshakePayload {
-    pub fn client_hello() -> Self {
-        let length = ClientHelloPayload::new().encode().len() as u32;
+impl MasterSecretInfo {
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#8-1--Computing-the-Master-Secret
+    // master_secret = PRF(pre_master_secret, "master secret",
+    //                     ClientHello.random + ServerHello.random)
+    //                     [0..47];
+    pub fn new(
+        suite: &'static CipherSuiteParams,
+        pre_master_secret: Vec<u8>,
+        client_random: Vec<u8>,
+        server_random: Vec<u8>,
+    ) -> Self {
+        let seed = [client_random.as_slice(), server_random.as_slice()].concat();
+        let master_secret = prf(
+            suite.prf_hash.hmac_algorithm(),
+            48,
+            &pre_master_secret,
+            &seed,
+            b"master secret",
+        )
+        .to_vec();
 
-        HandshakePayload {
-            msg_type: ClientHello,
-            length,
+        MasterSecretInfo {
+            master_secret,
+            pre_master_secret,
+            client_random,
+            server_random,
+            suite,
+            extended_master_secret: false,
         }
     }
 
-    pub fn encode(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        let client_hello_payload = ClientHelloPayload::new().encode();
-
-        // encode TLSPlainText
-        let protocol_version = ProtocolVersion::new(1, 2);
-        let tls_plaintext = TLSPlaintext::new(
-            Handshake,
-            protocol_version,
-            (&client_hello_payload.len() + 4) as u16,
-        );
-        bytes.extend(&tls_plaintext.encode());
-
-        // encode HandshakePayload
-        bytes.push(*&self.msg_type.encode());
-        // length is size 3 Vec<u8>
-        let encoded_length = self.length.to_be_bytes()[1..].to_vec();
-        bytes.extend(&encoded_length);
-        bytes.extend(&client_hello_payload);
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc7627.html#4--Extended-Master-Secret
+    // master_secret = PRF(pre_master_secret, "extended master secret",
+    //                     session_hash)
+    //                     [0..47];
+    // session_hash is the hash of the handshake messages up to and including ClientKeyExchange.
+    pub fn extended(
+        suite: &'static CipherSuiteParams,
+        pre_master_secret: Vec<u8>,
+        handshake_messages: &[u8],
+        client_random: Vec<u8>,
+        server_random: Vec<u8>,
+    ) -> Self {
+        let session_hash = digest::digest(suite.prf_hash.digest_algorithm(), handshake_messages);
+        let master_secret = prf(
+            suite.prf_hash.hmac_algorithm(),
+            48,
+            &pre_master_secret,
+            session_hash.as_ref(),
+            b"extended master secret",
+        )
+        .to_vec();
 
-        bytes
+        MasterSecretInfo {
+            master_secret,
+            pre_master_secret,
+            client_random,
+            server_random,
+            suite,
+            extended_master_secret: true,
+        }
     }
 
-    pub fn read_server_hello(buffer: Vec<u8>) -> ServerHelloPayload {
-        let len = bytes_to_u32_be(&buffer[0..2]);
-        let server_hello_payload = ServerHelloPayload::read(buffer[6..].to_vec(), len);
-
-        server_hello_payload
+    // An abbreviated handshake reuses the master secret of the resumed session with fresh randoms.
+    // extended_master_secret is that of the resumed session.
+    pub fn resume(
+        suite: &'static CipherSuiteParams,
+        master_secret: Vec<u8>,
+        extended_master_secret: bool,
+        client_random: Vec<u8>,
+        server_random: Vec<u8>,
+    ) -> Self {
+        MasterSecretInfo {
+            master_secret,
+            pre_master_secret: vec![],
+            client_random,
+            server_random,
+            suite,
+            extended_master_secret,
+        }
     }
 
-    pub fn read_certificate(buffer: Vec<u8>) -> Certificate {
-        let buffer = buffer;
-        let len = bytes_to_u32_be(&buffer[3..6]);
-        let certificate = Certificate::read(buffer[12..].to_vec(), len);
-
-        certificate
+    pub fn suite(&self) -> &'static CipherSuiteParams {
+        self.suite
     }
 
-    pub fn read_server_key_exchange(buffer: Vec<u8>) -> ServerKeyExchange {
-        let buffer = buffer;
-        let len = bytes_to_u32_be(&buffer[3..6]);
-        let server_key_exchange = ServerKeyExchange::read(buffer[6..].to_vec(), len);
-
-        server_key_exchange
+    pub fn is_extended(&self) -> bool {
+        self.extended_master_secret
     }
 
-    pub fn read_server_hello_done(buffer: Vec<u8>) -> ServerHelloDone {
-        let buffer = buffer;
-        let len = bytes_to_u32_be(&buffer[3..6]);
-        let server_hello_done = ServerHelloDone::read(buffer[12..].to_vec(), len);
-
-        server_hello_done
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#6-3--Key-Calculation
+    // key_block = PRF(SecurityParameters.master_secret,
+    //                 "key expansion",
+    //                 SecurityParameters.server_random +
+    //                 SecurityParameters.client_random);
+    pub fn key_block(&self) -> Vec<u8> {
+        let seed = [self.server_random.as_slice(), self.client_random.as_slice()].concat();
+        prf(
+            self.suite.prf_hash.hmac_algorithm(),
+            self.suite.key_block_len(),
+            &self.master_secret,
+            &seed,
+            b"key expansion",
+        )
+        .to_vec()
     }
 
-    pub fn parse_packet(data: &[u8]) -> Vec<Vec<u8>> {
-        let mut result = Vec::new();
-        // TLSPlaintext delimiter
-        let delimiter = b"\x16\x03\x03";
-
-        let mut start = 0;
-        while let Some(offset) = data[start..]
-            .windows(delimiter.len())
-            .position(|window| window == delimiter)
-        {
-            result.push(data[start..start + offset].to_vec());
-            start += offset + delimiter.len();
-        }
-        result.push(data[start..].to_vec());
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-9--Finished
+    // verify_data
+    //    PRF(master_secret, finished_label, Hash(handshake_messages))
+    //       [0..verify_data_length-1];
+    // Hash is the hash of the PRF.
+    pub fn verify_data(&self, label: &[u8], handshake_messages: &[u8]) -> Vec<u8> {
+        let prf_hash = self.suite.prf_hash;
+        let handshake_hash = digest::digest(prf_hash.digest_algorithm(), handshake_messages);
+        prf(
+            prf_hash.hmac_algorithm(),
+            12,
+            &self.master_secret,
+            handshake_hash.as_ref(),
+            label,
+        )
+        .to_vec()
+    }
+}
 
-        result
+impl HandshakePayload {
+    // encode a handshake message: msg_type(1) + uint24 length + body
+    pub fn encode_message(msg_type: HandshakeType, body: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.push(msg_type.encode());
+        bytes.extend(&(body.len() as u32).to_be_bytes()[1..]);
+        bytes.extend_from_slice(body);
+        bytes
     }
 }
 
@@ -159,8 +283,54 @@ pub fn bytes_to_u32_be(bytes: &[u8]) -> u32 {
 //              uint32 gmt_unix_time;
 //              opaque random_bytes[28];
 //          } Random;
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Random {
     pub gmt_unix_time: DateTime<Utc>,
     pub random_bytes: Vec<u8>,
 }
+
+impl Random {
+    pub fn new() -> Self {
+        let mut random_bytes = vec![0; 28];
+        rand::thread_rng().fill_bytes(&mut random_bytes);
+        Random {
+            gmt_unix_time: Utc::now(),
+            random_bytes,
+        }
+    }
+
+    pub fn read(buf: &[u8]) -> Result<Self, Error> {
+        if buf.len() != 32 {
+            return Err(Error::Decode("Random"));
+        }
+        let gmt_unix_time = BigEndian::read_u32(&buf[0..4]) as i64;
+        Ok(Random {
+            gmt_unix_time: Utc
+                .timestamp_opt(gmt_unix_time, 0)
+                .single()
+                .ok_or(Error::Decode("Random"))?,
+            random_bytes: buf[4..32].to_vec(),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        self.get_encoding()
+    }
+}
+
+impl Codec for Random {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        (self.gmt_unix_time.timestamp() as u32).encode_into(bytes);
+        bytes.extend_from_slice(&self.random_bytes);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Random::read(r.take(32)?).ok()
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,172 @@
+use crate::async_io::{connect, AsyncTlsStream};
+use crate::client::{ClientConfig, ClientConnection};
+use crate::server::{ServerConfig, ServerConnection};
+use hyper::client::connect::{Connected, Connection, HttpConnector};
+use hyper::server::accept::Accept;
+use hyper::server::conn::{AddrIncoming, AddrStream};
+use hyper::service::Service;
+use hyper::Uri;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::net::TcpStream;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc7540.html#3-3--Starting-HTTP-2-for-https-URIs
+// ALPN protocol names of HTTP/2 and HTTP/1.1, in the usual order of preference
+pub const ALPN_H2: &[u8] = b"h2";
+pub const ALPN_HTTP1: &[u8] = b"http/1.1";
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+// hyper::Client connector for https URIs. HTTP/2 is used if the server selects h2 with ALPN,
+// so config should offer ALPN_H2 and ALPN_HTTP1 to let the server choose.
+#[derive(Clone)]
+pub struct HttpsConnector {
+    http: HttpConnector,
+    config: Arc<ClientConfig>,
+}
+
+impl HttpsConnector {
+    pub fn new(config: Arc<ClientConfig>) -> Self {
+        let mut http = HttpConnector::new();
+        // the scheme is https, HttpConnector only accepts http unless told otherwise
+        http.enforce_http(false);
+        HttpsConnector { http, config }
+    }
+}
+
+impl Service<Uri> for HttpsConnector {
+    type Response = AsyncTlsStream<ClientConnection, TcpStream>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        if uri.scheme_str() != Some("https") {
+            return Box::pin(async move { Err(format!("{} is not an https URI", uri).into()) });
+        }
+        // IP literals are verified against iPAddress subjectAltNames
+        let server_name = uri
+            .host()
+            .unwrap_or_default()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let config = Arc::clone(&self.config);
+        let connecting = self.http.call(uri);
+        Box::pin(async move {
+            let tcp = connecting.await?;
+            Ok(connect(config, &server_name, tcp).await?)
+        })
+    }
+}
+
+impl Connection for AsyncTlsStream<ClientConnection, TcpStream> {
+    fn connected(&self) -> Connected {
+        let connected = self.get_ref().connected();
+        match self.connection().alpn_protocol() {
+            Some(ALPN_H2) => connected.negotiated_h2(),
+            _ => connected,
+        }
+    }
+}
+
+// hyper::Server acceptor. The handshake of a connection runs when hyper first reads from it,
+// so a slow client does not hold up the others. hyper tells HTTP/2 from HTTP/1.1 by the
+// connection preface, config offers the protocols to the client with ALPN.
+pub struct TlsAcceptor {
+    config: Arc<ServerConfig>,
+    incoming: AddrIncoming,
+}
+
+impl TlsAcceptor {
+    pub fn new(config: Arc<ServerConfig>, incoming: AddrIncoming) -> Self {
+        TlsAcceptor { config, incoming }
+    }
+}
+
+impl Accept for TlsAcceptor {
+    type Conn = AsyncTlsStream<ServerConnection, AddrStream>;
+    type Error = io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let this = self.get_mut();
+        let accepted = ready!(Pin::new(&mut this.incoming).poll_accept(cx));
+        Poll::Ready(accepted.map(|sock| {
+            sock.map(|sock| {
+                AsyncTlsStream::new(ServerConnection::new(Arc::clone(&this.config)), sock)
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::load_private_key;
+    use crate::sign::CertifiedKey;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Client, Request, Response, Server, Version};
+
+    const LEAF: &[u8] = include_bytes!("../testdata/ct/leaf.der");
+    const LEAF_KEY: &[u8] = include_bytes!("../testdata/ct/leaf.key");
+
+    // the client offers h2 and http/1.1 and the server picks the first it supports
+    async fn request_version(server_protocols: Vec<Vec<u8>>) -> Version {
+        let server_config = ServerConfig::builder()
+            .with_single_cert(CertifiedKey::new(
+                vec![LEAF.to_vec()],
+                load_private_key(LEAF_KEY).unwrap(),
+            ))
+            .with_alpn_protocols(server_protocols)
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let client_config = ClientConfig::builder()
+            .with_no_certificate_verification()
+            .with_alpn_protocols(vec![ALPN_H2.to_vec(), ALPN_HTTP1.to_vec()])
+            .build()
+            .map(Arc::new)
+            .unwrap();
+
+        let incoming = AddrIncoming::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let port = incoming.local_addr().port();
+        let service = make_service_fn(|_| async {
+            Ok::<_, io::Error>(service_fn(|request: Request<Body>| async move {
+                Ok::<_, io::Error>(Response::new(Body::from(format!(
+                    "{:?}",
+                    request.version()
+                ))))
+            }))
+        });
+        let server = Server::builder(TlsAcceptor::new(server_config, incoming)).serve(service);
+        let server = tokio::spawn(server);
+
+        let client: Client<_, Body> = Client::builder().build(HttpsConnector::new(client_config));
+        let uri = format!("https://localhost:{}/", port).parse().unwrap();
+        let response = client.get(uri).await.unwrap();
+        let version = response.version();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, format!("{:?}", version));
+        server.abort();
+        version
+    }
+
+    #[tokio::test]
+    async fn alpn_selects_http_version() {
+        let h2 = vec![ALPN_H2.to_vec(), ALPN_HTTP1.to_vec()];
+        assert_eq!(request_version(h2).await, Version::HTTP_2);
+        assert_eq!(
+            request_version(vec![ALPN_HTTP1.to_vec()]).await,
+            Version::HTTP_11
+        );
+    }
+}
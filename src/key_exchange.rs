@@ -0,0 +1,102 @@
+use crate::enums::NamedGroup;
+use ring::agreement;
+use ring::rand::SystemRandom;
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+// Named curves this crate implements, most preferred first.
+pub const SUPPORTED_GROUPS: [NamedGroup; 3] = [
+    NamedGroup::x25519,
+    NamedGroup::secp256r1,
+    NamedGroup::secp384r1,
+];
+
+// An ephemeral ECDH key pair. The shared key is the pre_master_secret.
+pub struct KeyExchange {
+    named_curve: NamedGroup,
+    private_key: PrivateKey,
+    public_key: Vec<u8>,
+}
+
+enum PrivateKey {
+    X25519(EphemeralSecret),
+    // secp256r1 and secp384r1
+    Ecdh(agreement::EphemeralPrivateKey),
+}
+
+impl KeyExchange {
+    // None if the curve is not supported
+    pub fn generate(named_curve: NamedGroup) -> Option<Self> {
+        let algorithm = match named_curve {
+            NamedGroup::x25519 => {
+                // 秘密鍵と公開鍵を作る
+                let private_key = EphemeralSecret::random();
+                let public_key = PublicKey::from(&private_key).as_bytes().to_vec();
+                return Some(KeyExchange {
+                    named_curve,
+                    private_key: PrivateKey::X25519(private_key),
+                    public_key,
+                });
+            }
+            NamedGroup::secp256r1 => &agreement::ECDH_P256,
+            NamedGroup::secp384r1 => &agreement::ECDH_P384,
+            _ => return None,
+        };
+
+        // secp256r1 and secp384r1 public keys are uncompressed ECPoints
+        let private_key =
+            agreement::EphemeralPrivateKey::generate(algorithm, &SystemRandom::new()).ok()?;
+        let public_key = private_key.compute_public_key().ok()?.as_ref().to_vec();
+        Some(KeyExchange {
+            named_curve,
+            private_key: PrivateKey::Ecdh(private_key),
+            public_key,
+        })
+    }
+
+    pub fn named_curve(&self) -> NamedGroup {
+        self.named_curve
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    // returns the shared key, None if the peer's public key is invalid
+    pub fn complete(self, peer_public_key: &[u8]) -> Option<Vec<u8>> {
+        match self.private_key {
+            PrivateKey::X25519(private_key) => {
+                let peer_public_key: [u8; 32] = peer_public_key.try_into().ok()?;
+                // 鍵交換
+                let shared_key = private_key.diffie_hellman(&PublicKey::from(peer_public_key));
+                Some(shared_key.as_bytes().to_vec())
+            }
+            PrivateKey::Ecdh(private_key) => {
+                let algorithm = private_key.algorithm();
+                agreement::agree_ephemeral(
+                    private_key,
+                    &agreement::UnparsedPublicKey::new(algorithm, peer_public_key),
+                    (),
+                    |shared_key| Ok(shared_key.to_vec()),
+                )
+                .ok()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_sides_agree() {
+        for named_curve in SUPPORTED_GROUPS {
+            let client = KeyExchange::generate(named_curve).unwrap();
+            let server = KeyExchange::generate(named_curve).unwrap();
+            let client_public_key = client.public_key().to_vec();
+            let client_shared_key = client.complete(server.public_key()).unwrap();
+            let server_shared_key = server.complete(&client_public_key).unwrap();
+            assert_eq!(client_shared_key, server_shared_key);
+        }
+    }
+}
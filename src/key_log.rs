@@ -0,0 +1,91 @@
+use std::env;
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+// Ref: https://developer.mozilla.org/en-US/docs/Mozilla/Projects/NSS/Key_Log_Format
+// Receives the secrets of each handshake so that tools such as Wireshark can decrypt a capture.
+// Only for debugging, whoever reads the log can decrypt the traffic.
+pub trait KeyLog: Send + Sync {
+    // label is "CLIENT_RANDOM" for the TLS 1.2 master secret
+    fn log(&self, label: &str, client_random: &[u8], secret: &[u8]);
+}
+
+// the default, logs nothing
+pub struct NoKeyLog;
+
+impl KeyLog for NoKeyLog {
+    fn log(&self, _label: &str, _client_random: &[u8], _secret: &[u8]) {}
+}
+
+// Appends to the file named by the SSLKEYLOGFILE environment variable, in the NSS key log format:
+// CLIENT_RANDOM <client_random> <master_secret>, both lower case hex.
+// Without the variable, or if the file cannot be opened, nothing is logged.
+pub struct KeyLogFile {
+    file: Mutex<Option<File>>,
+}
+
+impl KeyLogFile {
+    pub fn new() -> Self {
+        let file = env::var_os("SSLKEYLOGFILE").and_then(|path| Self::open_file(path).ok());
+        KeyLogFile {
+            file: Mutex::new(file),
+        }
+    }
+
+    // log to path regardless of SSLKEYLOGFILE
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(KeyLogFile {
+            file: Mutex::new(Some(Self::open_file(path)?)),
+        })
+    }
+
+    fn open_file(path: impl AsRef<Path>) -> io::Result<File> {
+        OpenOptions::new().append(true).create(true).open(path)
+    }
+}
+
+impl Default for KeyLogFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyLog for KeyLogFile {
+    fn log(&self, label: &str, client_random: &[u8], secret: &[u8]) {
+        let mut file = self.file.lock().unwrap();
+        let Some(file) = file.as_mut() else {
+            return;
+        };
+        let line = format!("{} {} {}\n", label, hex(client_random), hex(secret));
+        // a failed write must not fail the handshake
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn appends_nss_key_log_lines() {
+        let path = env::temp_dir().join(format!("key_log_test_{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let key_log = KeyLogFile::open(&path).unwrap();
+        key_log.log("CLIENT_RANDOM", &[0x01, 0xab], &[0xff; 3]);
+        key_log.log("CLIENT_RANDOM", &[0x02], &[0x00]);
+        let logged = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(logged, "CLIENT_RANDOM 01ab ffffff\nCLIENT_RANDOM 02 00\n");
+    }
+}
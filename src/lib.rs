@@ -1,11 +1,43 @@
-mod certificate;
-mod client_hello;
-mod enums;
+pub mod async_io;
+pub mod certificate;
+mod certificate_request;
+mod certificate_status;
+mod certificate_verify;
+pub mod cipher_suite;
+pub mod client;
+pub mod client_hello;
+mod client_key_exchange;
+pub mod codec;
+pub mod config;
+pub mod connection;
+pub mod crl;
+pub mod ct;
+mod der;
+pub mod enums;
+pub mod error;
+pub mod extensions;
+mod finished;
 pub mod handshake;
+pub mod https;
+mod key_exchange;
+pub mod key_log;
+pub mod loader;
+mod new_session_ticket;
+pub mod ocsp;
+pub mod pcap;
+pub mod pinning;
 mod prf;
 mod protocol_version;
+mod record_layer;
+pub mod resolver;
+pub mod server;
 mod server_hello;
 mod server_hello_done;
 mod server_key_exchange;
+pub mod session;
+pub mod sign;
+pub mod stream;
+pub mod ticket;
+mod tls_ciphertext;
 mod tls_plaintext;
-mod client_key_exchange;
+pub mod verify;
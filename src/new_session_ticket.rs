@@ -0,0 +1,56 @@
+use crate::codec::{decode_all, encode_opaque, read_opaque, Codec, LengthPrefix, Reader};
+use crate::enums::HandshakeType;
+use crate::error::Error;
+use crate::handshake::HandshakePayload;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5077.html#3-3--NewSessionTicket-Handshake-Message
+// struct {
+//     uint32 ticket_lifetime_hint;
+//     opaque ticket<0..2^16-1>;
+// } NewSessionTicket;
+#[derive(Debug)]
+pub struct NewSessionTicket {
+    handshake_type: HandshakeType,
+    ticket_lifetime_hint: u32,
+    ticket: Vec<u8>,
+}
+
+impl NewSessionTicket {
+    pub fn new(ticket_lifetime_hint: u32, ticket: Vec<u8>) -> Self {
+        NewSessionTicket {
+            handshake_type: HandshakeType::NewSessionTicket,
+            ticket_lifetime_hint,
+            ticket,
+        }
+    }
+
+    pub fn read(buf: &[u8]) -> Result<Self, Error> {
+        decode_all(buf).ok_or(Error::Decode("NewSessionTicket"))
+    }
+
+    // seconds the ticket should be kept, 0 if unspecified
+    pub fn ticket_lifetime_hint(&self) -> u32 {
+        self.ticket_lifetime_hint
+    }
+
+    pub fn ticket(&self) -> &[u8] {
+        &self.ticket
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        HandshakePayload::encode_message(self.handshake_type, &self.get_encoding())
+    }
+}
+
+impl Codec for NewSessionTicket {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        self.ticket_lifetime_hint.encode_into(bytes);
+        encode_opaque(bytes, LengthPrefix::U16, &self.ticket);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let ticket_lifetime_hint = u32::decode(r)?;
+        let ticket = read_opaque(r, LengthPrefix::U16)?.to_vec();
+        Some(NewSessionTicket::new(ticket_lifetime_hint, ticket))
+    }
+}
@@ -0,0 +1,452 @@
+use crate::certificate::{is_issued_by, verify_signed_data};
+use crate::der::{self, Reader};
+use ring::digest;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use x509_parser::prelude::*;
+
+// id-pkix-ocsp-basic 1.3.6.1.5.5.7.48.1.1
+const ID_PKIX_OCSP_BASIC: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01];
+// id-ce-ocspSctList, SignedCertificateTimestampList in singleExtensions (RFC 6962)
+const OCSP_SCT_LIST: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x05];
+// OCSPResponseStatus successful(0)
+const SUCCESSFUL: &[u8] = &[0x00];
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc6960.html#4-2-1--ASN-1-Specification-of-the-OCSP-Response
+// OCSPResponse ::= SEQUENCE {
+//    responseStatus         OCSPResponseStatus,
+//    responseBytes          [0] EXPLICIT ResponseBytes OPTIONAL }
+//
+// ResponseBytes ::=       SEQUENCE {
+//     responseType   OBJECT IDENTIFIER,
+//     response       OCTET STRING }
+//
+// BasicOCSPResponse       ::= SEQUENCE {
+//    tbsResponseData      ResponseData,
+//    signatureAlgorithm   AlgorithmIdentifier,
+//    signature            BIT STRING,
+//    certs            [0] EXPLICIT SEQUENCE OF Certificate OPTIONAL }
+//
+// ResponseData ::= SEQUENCE {
+//    version              [0] EXPLICIT Version DEFAULT v1,
+//    responderID              ResponderID,
+//    producedAt               GeneralizedTime,
+//    responses                SEQUENCE OF SingleResponse,
+//    responseExtensions   [1] EXPLICIT Extensions OPTIONAL }
+//
+// ResponderID ::= CHOICE {
+//    byName   [1] Name,
+//    byKey    [2] KeyHash }
+//
+// KeyHash ::= OCTET STRING -- SHA-1 hash of responder's public key
+//
+// Only successful responses of type id-pkix-ocsp-basic are accepted.
+#[derive(Debug)]
+pub struct OcspResponse {
+    // DER encoded ResponseData, the signed part
+    tbs_response_data: Vec<u8>,
+    signature_algorithm: Vec<u8>,
+    signature: Vec<u8>,
+    // DER encoded certificates that help to verify the signature
+    certs: Vec<Vec<u8>>,
+    responder_id: ResponderId,
+    produced_at: u64,
+    responses: Vec<SingleResponse>,
+}
+
+#[derive(Debug)]
+enum ResponderId {
+    // DER encoded Name
+    ByName(Vec<u8>),
+    ByKey(Vec<u8>),
+}
+
+// SingleResponse ::= SEQUENCE {
+//    certID                       CertID,
+//    certStatus                   CertStatus,
+//    thisUpdate                   GeneralizedTime,
+//    nextUpdate         [0]       EXPLICIT GeneralizedTime OPTIONAL,
+//    singleExtensions   [1]       EXPLICIT Extensions OPTIONAL }
+//
+// CertID          ::=     SEQUENCE {
+//     hashAlgorithm       AlgorithmIdentifier,
+//     issuerNameHash      OCTET STRING, -- Hash of issuer's DN
+//     issuerKeyHash       OCTET STRING, -- Hash of issuer's public key
+//     serialNumber        CertificateSerialNumber }
+#[derive(Debug)]
+struct SingleResponse {
+    hash_algorithm: Vec<u8>,
+    issuer_name_hash: Vec<u8>,
+    issuer_key_hash: Vec<u8>,
+    serial_number: Vec<u8>,
+    status: CertStatus,
+    this_update: u64,
+    next_update: Option<u64>,
+    // TLS encoded SignedCertificateTimestampList
+    sct_list: Option<Vec<u8>>,
+}
+
+// CertStatus ::= CHOICE {
+//     good        [0]     IMPLICIT NULL,
+//     revoked     [1]     IMPLICIT RevokedInfo,
+//     unknown     [2]     IMPLICIT UnknownInfo }
+//
+// RevokedInfo ::= SEQUENCE {
+//     revocationTime              GeneralizedTime,
+//     revocationReason    [0]     EXPLICIT CRLReason OPTIONAL }
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CertStatus {
+    Good,
+    // seconds since the UNIX epoch
+    Revoked { revocation_time: u64 },
+    Unknown,
+}
+
+impl OcspResponse {
+    // None if the response is malformed or its responseStatus is not successful
+    pub fn read(der: &[u8]) -> Option<Self> {
+        let mut outer = Reader::new(der);
+        let mut response = Reader::new(outer.read(der::SEQUENCE)?);
+        if !outer.is_empty() || response.read(der::ENUMERATED)? != SUCCESSFUL {
+            return None;
+        }
+        let mut response_bytes = Reader::new(response.read(der::context(0))?);
+        let mut response_bytes = Reader::new(response_bytes.read(der::SEQUENCE)?);
+        if response_bytes.read(der::OID)? != ID_PKIX_OCSP_BASIC {
+            return None;
+        }
+        let basic = response_bytes.read(der::OCTET_STRING)?;
+
+        let mut outer = Reader::new(basic);
+        let mut basic = Reader::new(outer.read(der::SEQUENCE)?);
+        if !outer.is_empty() {
+            return None;
+        }
+        let tbs_response_data = basic.read_raw(der::SEQUENCE)?;
+        let signature_algorithm = read_algorithm(&mut basic)?;
+        let signature = basic.read_bit_string()?.to_vec();
+        let mut certs = Vec::new();
+        if let Some(certificates) = basic.read_optional(der::context(0)) {
+            let mut certificates = Reader::new(certificates);
+            let mut certificates = Reader::new(certificates.read(der::SEQUENCE)?);
+            while !certificates.is_empty() {
+                certs.push(certificates.read_raw(der::SEQUENCE)?.to_vec());
+            }
+        }
+
+        let mut data = Reader::new(tbs_response_data);
+        let mut data = Reader::new(data.read(der::SEQUENCE)?);
+        if let Some(version) = data.read_optional(der::context(0)) {
+            // v1(0)
+            if Reader::new(version).read(der::INTEGER)? != [0x00] {
+                return None;
+            }
+        }
+        let responder_id = match data.peek_tag()? {
+            tag if tag == der::context(1) => {
+                let mut name = Reader::new(data.read(tag)?);
+                ResponderId::ByName(name.read_raw(der::SEQUENCE)?.to_vec())
+            }
+            tag if tag == der::context(2) => {
+                let mut key_hash = Reader::new(data.read(tag)?);
+                ResponderId::ByKey(key_hash.read(der::OCTET_STRING)?.to_vec())
+            }
+            _ => return None,
+        };
+        let produced_at = data.read_generalized_time()?;
+        let mut single_responses = Reader::new(data.read(der::SEQUENCE)?);
+        let mut responses = Vec::new();
+        while !single_responses.is_empty() {
+            responses.push(SingleResponse::read(single_responses.read(der::SEQUENCE)?)?);
+        }
+
+        Some(OcspResponse {
+            tbs_response_data: tbs_response_data.to_vec(),
+            signature_algorithm,
+            signature,
+            certs,
+            responder_id,
+            produced_at,
+            responses,
+        })
+    }
+
+    pub fn produced_at(&self) -> u64 {
+        self.produced_at
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc6960.html#3-2--Signed-Response-Acceptance-Requirements
+    // The status of leaf, both certificates DER encoded. The response must be signed by issuer
+    // or by a responder certificate issued by it with id-kp-OCSPSigning, and be current at now.
+    pub fn verify(&self, leaf: &[u8], issuer: &[u8], now: u64) -> Result<CertStatus, &'static str> {
+        let (Ok((_, parsed_leaf)), Ok((_, parsed_issuer))) =
+            (parse_x509_certificate(leaf), parse_x509_certificate(issuer))
+        else {
+            return Err("invalid certificate");
+        };
+        if !is_issued_by(leaf, issuer) {
+            return Err("the certificate is not issued by the issuer");
+        }
+
+        let issuer_name = parsed_issuer.subject().as_raw();
+        let issuer_key = &parsed_issuer.public_key().subject_public_key.data;
+        let response = self
+            .responses
+            .iter()
+            .find(|response| response.matches(&parsed_leaf, issuer_name, issuer_key))
+            .ok_or("the OCSP response does not cover the certificate")?;
+
+        let signer = self.signer(issuer).ok_or("unauthorized OCSP responder")?;
+        if !verify_signed_data(
+            signer,
+            &self.signature_algorithm,
+            &self.tbs_response_data,
+            &self.signature,
+        ) {
+            return Err("invalid OCSP response signature");
+        }
+
+        if !response.is_current(now) {
+            return Err("the OCSP response is not current");
+        }
+        Ok(response.status)
+    }
+
+    // the SignedCertificateTimestampList in the response for leaf, if any
+    pub fn sct_list(&self, leaf: &[u8], issuer: &[u8]) -> Option<&[u8]> {
+        let (Ok((_, leaf)), Ok((_, issuer))) =
+            (parse_x509_certificate(leaf), parse_x509_certificate(issuer))
+        else {
+            return None;
+        };
+        let issuer_key = &issuer.public_key().subject_public_key.data;
+        self.responses
+            .iter()
+            .find(|response| response.matches(&leaf, issuer.subject().as_raw(), issuer_key))?
+            .sct_list
+            .as_deref()
+    }
+
+    // every SingleResponse is within thisUpdate..nextUpdate
+    pub fn is_current(&self, now: u64) -> bool {
+        !self.responses.is_empty() && self.responses.iter().all(|r| r.is_current(now))
+    }
+
+    // the certificate whose key signs the response: issuer itself or a delegated responder
+    fn signer<'a>(&'a self, issuer: &'a [u8]) -> Option<&'a [u8]> {
+        if self.is_responder(issuer) {
+            return Some(issuer);
+        }
+        self.certs
+            .iter()
+            .find(|cert| {
+                self.is_responder(cert) && is_issued_by(cert, issuer) && is_ocsp_signer(cert)
+            })
+            .map(|cert| &cert[..])
+    }
+
+    fn is_responder(&self, cert: &[u8]) -> bool {
+        let Ok((_, cert)) = parse_x509_certificate(cert) else {
+            return false;
+        };
+        match &self.responder_id {
+            ResponderId::ByName(name) => cert.subject().as_raw() == &name[..],
+            ResponderId::ByKey(key_hash) => {
+                let key = &cert.public_key().subject_public_key.data;
+                digest::digest(&digest::SHA1_FOR_LEGACY_USE_ONLY, key).as_ref() == &key_hash[..]
+            }
+        }
+    }
+}
+
+impl SingleResponse {
+    fn read(buf: &[u8]) -> Option<Self> {
+        let mut single = Reader::new(buf);
+        let mut cert_id = Reader::new(single.read(der::SEQUENCE)?);
+        let hash_algorithm = read_algorithm(&mut cert_id)?;
+        let issuer_name_hash = cert_id.read(der::OCTET_STRING)?.to_vec();
+        let issuer_key_hash = cert_id.read(der::OCTET_STRING)?.to_vec();
+        let serial_number = cert_id.read(der::INTEGER)?.to_vec();
+
+        let status = match single.read_any()? {
+            (tag, [], _) if tag == der::context_primitive(0) => CertStatus::Good,
+            (tag, revoked_info, _) if tag == der::context(1) => CertStatus::Revoked {
+                revocation_time: Reader::new(revoked_info).read_generalized_time()?,
+            },
+            (tag, [], _) if tag == der::context_primitive(2) => CertStatus::Unknown,
+            _ => return None,
+        };
+        let this_update = single.read_generalized_time()?;
+        let next_update = match single.read_optional(der::context(0)) {
+            Some(next_update) => Some(Reader::new(next_update).read_generalized_time()?),
+            None => None,
+        };
+        // Extension ::= SEQUENCE { extnID OBJECT IDENTIFIER, critical BOOLEAN DEFAULT FALSE,
+        //                          extnValue OCTET STRING }
+        let mut sct_list = None;
+        if let Some(extensions) = single.read_optional(der::context(1)) {
+            let mut extensions = Reader::new(extensions);
+            let mut extensions = Reader::new(extensions.read(der::SEQUENCE)?);
+            while !extensions.is_empty() {
+                let mut extension = Reader::new(extensions.read(der::SEQUENCE)?);
+                let extn_id = extension.read(der::OID)?;
+                extension.read_optional(der::BOOLEAN);
+                let extn_value = extension.read(der::OCTET_STRING)?;
+                if extn_id == OCSP_SCT_LIST {
+                    sct_list = Some(Reader::new(extn_value).read(der::OCTET_STRING)?.to_vec());
+                }
+            }
+        }
+
+        Some(SingleResponse {
+            hash_algorithm,
+            issuer_name_hash,
+            issuer_key_hash,
+            serial_number,
+            status,
+            this_update,
+            next_update,
+            sct_list,
+        })
+    }
+
+    fn matches(&self, leaf: &X509Certificate, issuer_name: &[u8], issuer_key: &[u8]) -> bool {
+        let Some(algorithm) = cert_id_digest(&self.hash_algorithm) else {
+            return false;
+        };
+        self.serial_number == leaf.raw_serial()
+            && digest::digest(algorithm, issuer_name).as_ref() == &self.issuer_name_hash[..]
+            && digest::digest(algorithm, issuer_key).as_ref() == &self.issuer_key_hash[..]
+    }
+
+    fn is_current(&self, now: u64) -> bool {
+        self.this_update <= now
+            && self
+                .next_update
+                .is_none_or(|next_update| now <= next_update)
+    }
+}
+
+// An OCSP response kept in a file, e.g. written by `openssl ocsp -respout` from a cron job.
+// The file is read again whenever its modification time changes.
+pub struct OcspResponseFile {
+    path: PathBuf,
+    // (modification time, DER encoded OCSPResponse)
+    cached: Mutex<Option<(SystemTime, Vec<u8>)>>,
+}
+
+impl OcspResponseFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        OcspResponseFile {
+            path: path.into(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    // the response to staple, None if the file cannot be read, is malformed or has expired
+    pub fn response(&self) -> Option<Vec<u8>> {
+        self.response_at(crate::session::unix_time())
+    }
+
+    // like response, at the unix time now
+    pub fn response_at(&self, now: u64) -> Option<Vec<u8>> {
+        let mut cached = self.cached.lock().unwrap();
+        // keep the previous response if the file is being replaced
+        if let Ok(modified) = fs::metadata(&self.path).and_then(|m| m.modified()) {
+            if cached.as_ref().map(|(time, _)| *time) != Some(modified) {
+                if let Ok(response) = fs::read(&self.path) {
+                    *cached = Some((modified, response));
+                }
+            }
+        }
+        let (_, response) = cached.as_ref()?;
+        let current = OcspResponse::read(response)?.is_current(now);
+        current.then(|| response.clone())
+    }
+}
+
+// the id-kp-OCSPSigning extended key usage is required for delegated responders
+fn is_ocsp_signer(cert: &[u8]) -> bool {
+    let Ok((_, cert)) = parse_x509_certificate(cert) else {
+        return false;
+    };
+    matches!(cert.extended_key_usage(), Ok(Some(eku)) if eku.value.ocsp_signing)
+}
+
+// AlgorithmIdentifier ::= SEQUENCE { algorithm OBJECT IDENTIFIER, parameters ANY OPTIONAL }
+fn read_algorithm(reader: &mut Reader) -> Option<Vec<u8>> {
+    let mut algorithm = Reader::new(reader.read(der::SEQUENCE)?);
+    Some(algorithm.read(der::OID)?.to_vec())
+}
+
+fn cert_id_digest(algorithm: &[u8]) -> Option<&'static digest::Algorithm> {
+    match algorithm {
+        // id-sha1 1.3.14.3.2.26
+        [0x2b, 0x0e, 0x03, 0x02, 0x1a] => Some(&digest::SHA1_FOR_LEGACY_USE_ONLY),
+        // id-sha256 2.16.840.1.101.3.4.2.1
+        [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01] => Some(&digest::SHA256),
+        // id-sha384 2.16.840.1.101.3.4.2.2
+        [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02] => Some(&digest::SHA384),
+        // id-sha512 2.16.840.1.101.3.4.2.3
+        [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03] => Some(&digest::SHA512),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA: &[u8] = include_bytes!("../testdata/ocsp/ca.der");
+    const LEAF: &[u8] = include_bytes!("../testdata/ocsp/leaf.der");
+    const REVOKED_LEAF: &[u8] = include_bytes!("../testdata/ocsp/revoked-leaf.der");
+    const GOOD: &[u8] = include_bytes!("../testdata/ocsp/good.ocsp");
+    const REVOKED: &[u8] = include_bytes!("../testdata/ocsp/revoked.ocsp");
+    const DELEGATED: &[u8] = include_bytes!("../testdata/ocsp/delegated.ocsp");
+    // the fixtures were produced at 1792395901 and expire 7 days later
+    const NOW: u64 = 1792395901 + 60;
+    const EXPIRED: u64 = 1792395901 + 8 * 24 * 60 * 60;
+
+    #[test]
+    fn verify_fixtures() {
+        let good = OcspResponse::read(GOOD).unwrap();
+        assert_eq!(good.verify(LEAF, CA, NOW), Ok(CertStatus::Good));
+        let delegated = OcspResponse::read(DELEGATED).unwrap();
+        assert_eq!(delegated.verify(LEAF, CA, NOW), Ok(CertStatus::Good));
+        let revoked = OcspResponse::read(REVOKED).unwrap();
+        assert!(matches!(
+            revoked.verify(REVOKED_LEAF, CA, NOW),
+            Ok(CertStatus::Revoked { .. })
+        ));
+
+        // wrong certificate, expired response, issuer that did not sign the leaf
+        assert!(good.verify(REVOKED_LEAF, CA, NOW).is_err());
+        assert!(good.verify(LEAF, CA, EXPIRED).is_err());
+        assert!(good.verify(LEAF, LEAF, NOW).is_err());
+    }
+
+    #[test]
+    fn reject_tampered_response() {
+        assert!(OcspResponse::read(&GOOD[..GOOD.len() - 1]).is_none());
+        // flip a byte of producedAt, which is covered by the signature
+        let produced_at = GOOD.windows(4).position(|w| w == b"\x18\x0f20").unwrap();
+        let mut tampered = GOOD.to_vec();
+        tampered[produced_at + 15] ^= 0x01;
+        let tampered = OcspResponse::read(&tampered).unwrap();
+        assert!(tampered.verify(LEAF, CA, NOW).is_err());
+    }
+
+    #[test]
+    fn response_file_is_reloaded() {
+        let path = std::env::temp_dir().join(format!("tls-12-ocsp-{}", std::process::id()));
+        fs::write(&path, GOOD).unwrap();
+        let file = OcspResponseFile::new(&path);
+        assert_eq!(file.response_at(NOW).as_deref(), Some(GOOD));
+        assert_eq!(file.response_at(EXPIRED), None);
+        fs::remove_file(&path).unwrap();
+        // a missing file keeps the last response
+        assert_eq!(file.response_at(NOW).as_deref(), Some(GOOD));
+    }
+}
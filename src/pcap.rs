@@ -0,0 +1,275 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Ref: https://www.ietf.org/archive/id/draft-ietf-opsawg-pcap-03.html
+// Writes the TLS bytes of one connection to a pcap file as a made-up TCP stream between
+// CLIENT_ADDR and SERVER_ADDR, so the capture opens in Wireshark. Together with a key log
+// (see key_log) Wireshark decrypts it. The bytes are recorded as read_tls and write_tls
+// pass them, each call becomes one TCP segment.
+pub struct PcapRecorder {
+    out: Box<dyn Write + Send>,
+    // the recorded connection is the client side
+    is_client: bool,
+    // next TCP sequence numbers
+    client_seq: u32,
+    server_seq: u32,
+}
+
+pub const CLIENT_ADDR: ([u8; 4], u16) = ([10, 0, 0, 1], 49152);
+// port 443 makes Wireshark dissect the stream as TLS
+pub const SERVER_ADDR: ([u8; 4], u16) = ([10, 0, 0, 2], 443);
+
+// LINKTYPE_RAW, packets start with the IPv4 header
+const LINKTYPE_RAW: u32 = 101;
+const SNAPLEN: u32 = 65535;
+// payload of a single segment, larger writes are split
+const MAX_SEGMENT_LEN: usize = 32768;
+
+const TCP_FIN: u8 = 0x01;
+const TCP_SYN: u8 = 0x02;
+const TCP_PSH: u8 = 0x08;
+const TCP_ACK: u8 = 0x10;
+
+impl PcapRecorder {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+
+    // writes the pcap file header to out
+    pub fn new(out: impl Write + Send + 'static) -> io::Result<Self> {
+        let mut out: Box<dyn Write + Send> = Box::new(out);
+        let mut header = Vec::with_capacity(24);
+        header.extend(0xa1b2c3d4u32.to_le_bytes());
+        // version 2.4
+        header.extend(2u16.to_le_bytes());
+        header.extend(4u16.to_le_bytes());
+        // thiszone and sigfigs
+        header.extend([0; 8]);
+        header.extend(SNAPLEN.to_le_bytes());
+        header.extend(LINKTYPE_RAW.to_le_bytes());
+        out.write_all(&header)?;
+        out.flush()?;
+        Ok(PcapRecorder {
+            out,
+            is_client: true,
+            client_seq: 1000,
+            server_seq: 5000,
+        })
+    }
+
+    // Called when the recorder is attached to a connection: the TCP handshake, so that
+    // Wireshark sees the whole stream.
+    pub fn start(&mut self, is_client: bool) -> io::Result<()> {
+        self.is_client = is_client;
+        self.segment(true, TCP_SYN, &[])?;
+        self.segment(false, TCP_SYN | TCP_ACK, &[])?;
+        self.segment(true, TCP_ACK, &[])
+    }
+
+    // bytes written to the transport
+    pub fn sent(&mut self, data: &[u8]) -> io::Result<()> {
+        self.payload(self.is_client, data)
+    }
+
+    // bytes read from the transport
+    pub fn received(&mut self, data: &[u8]) -> io::Result<()> {
+        self.payload(!self.is_client, data)
+    }
+
+    // the transport reached EOF
+    pub fn peer_closed(&mut self) -> io::Result<()> {
+        self.segment(!self.is_client, TCP_FIN | TCP_ACK, &[])
+    }
+
+    fn payload(&mut self, from_client: bool, data: &[u8]) -> io::Result<()> {
+        for chunk in data.chunks(MAX_SEGMENT_LEN) {
+            self.segment(from_client, TCP_PSH | TCP_ACK, chunk)?;
+        }
+        Ok(())
+    }
+
+    fn segment(&mut self, from_client: bool, flags: u8, payload: &[u8]) -> io::Result<()> {
+        let ((src, src_port), (dst, dst_port)) = if from_client {
+            (CLIENT_ADDR, SERVER_ADDR)
+        } else {
+            (SERVER_ADDR, CLIENT_ADDR)
+        };
+        let (seq, ack) = if from_client {
+            (self.client_seq, self.server_seq)
+        } else {
+            (self.server_seq, self.client_seq)
+        };
+        // SYN and FIN take up a sequence number
+        let len = payload.len() as u32 + u32::from(flags & (TCP_SYN | TCP_FIN) != 0);
+        if from_client {
+            self.client_seq = self.client_seq.wrapping_add(len);
+        } else {
+            self.server_seq = self.server_seq.wrapping_add(len);
+        }
+
+        // Ref: https://tex2e.github.io/rfc-translater/html/rfc793.html#3-1--Header-Format
+        let mut tcp = Vec::with_capacity(20 + payload.len());
+        tcp.extend(src_port.to_be_bytes());
+        tcp.extend(dst_port.to_be_bytes());
+        tcp.extend(seq.to_be_bytes());
+        // the ACK number is only meaningful with the ACK flag
+        tcp.extend(if flags & TCP_ACK != 0 { ack } else { 0 }.to_be_bytes());
+        // data offset: 5 words, no options
+        tcp.push(5 << 4);
+        tcp.push(flags);
+        tcp.extend(65535u16.to_be_bytes());
+        // checksum and urgent pointer
+        tcp.extend([0; 4]);
+        tcp.extend(payload);
+        let mut pseudo_header = Vec::with_capacity(12);
+        pseudo_header.extend(src);
+        pseudo_header.extend(dst);
+        pseudo_header.extend([0, 6]);
+        pseudo_header.extend((tcp.len() as u16).to_be_bytes());
+        let checksum = internet_checksum(&[&pseudo_header, &tcp]);
+        tcp[16..18].copy_from_slice(&checksum.to_be_bytes());
+
+        // Ref: https://tex2e.github.io/rfc-translater/html/rfc791.html#3-1--Internet-Header-Format
+        let mut packet = Vec::with_capacity(20 + tcp.len());
+        packet.extend([0x45, 0]);
+        packet.extend(((20 + tcp.len()) as u16).to_be_bytes());
+        // identification, then flags and fragment offset: don't fragment
+        packet.extend([0, 0, 0x40, 0]);
+        // TTL and protocol TCP
+        packet.extend([64, 6]);
+        packet.extend([0; 2]);
+        packet.extend(src);
+        packet.extend(dst);
+        let checksum = internet_checksum(&[&packet]);
+        packet[10..12].copy_from_slice(&checksum.to_be_bytes());
+        packet.extend(tcp);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut record = Vec::with_capacity(16 + packet.len());
+        record.extend((now.as_secs() as u32).to_le_bytes());
+        record.extend(now.subsec_micros().to_le_bytes());
+        record.extend((packet.len() as u32).to_le_bytes());
+        record.extend((packet.len() as u32).to_le_bytes());
+        record.extend(packet);
+        self.out.write_all(&record)?;
+        // readable while the connection is still open
+        self.out.flush()
+    }
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc1071.html
+// one's complement of the one's complement sum of the 16-bit words
+fn internet_checksum(parts: &[&[u8]]) -> u16 {
+    let mut sum: u32 = 0;
+    for part in parts {
+        for word in part.chunks(2) {
+            let high = u32::from(word[0]) << 8;
+            sum += high | word.get(1).copied().map_or(0, u32::from);
+        }
+    }
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ClientConfig, ClientConnection};
+    use crate::connection::Connection;
+    use crate::loader::load_private_key;
+    use crate::server::{ServerConfig, ServerConnection};
+    use crate::sign::CertifiedKey;
+    use std::fs;
+    use std::sync::Arc;
+
+    const LEAF: &[u8] = include_bytes!("../testdata/ct/leaf.der");
+    const LEAF_KEY: &[u8] = include_bytes!("../testdata/ct/leaf.key");
+
+    #[test]
+    fn records_a_handshake_as_tcp_stream() {
+        let server_config = ServerConfig::builder()
+            .with_single_cert(CertifiedKey::new(
+                vec![LEAF.to_vec()],
+                load_private_key(LEAF_KEY).unwrap(),
+            ))
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let client_config = ClientConfig::builder()
+            .with_no_certificate_verification()
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("pcap_test_{}.pcap", std::process::id()));
+        let mut client = ClientConnection::new(client_config, "localhost");
+        client.set_recorder(PcapRecorder::create(&path).unwrap());
+        let mut server = ServerConnection::new(server_config);
+
+        // what the client sent and received, in order
+        let mut sent: Vec<u8> = Vec::new();
+        let mut received: Vec<u8> = Vec::new();
+        while client.is_handshaking() || server.is_handshaking() {
+            let start = sent.len();
+            while client.wants_write() {
+                client.write_tls(&mut sent).unwrap();
+            }
+            server.read_tls(&mut &sent[start..]).unwrap();
+            server.process_new_packets().unwrap();
+            let mut buf = Vec::new();
+            while server.wants_write() {
+                server.write_tls(&mut buf).unwrap();
+            }
+            let mut rd = &buf[..];
+            while !rd.is_empty() {
+                client.read_tls(&mut rd).unwrap();
+                client.process_new_packets().unwrap();
+            }
+            received.extend(buf);
+        }
+        client.read_tls(&mut &[][..]).unwrap();
+
+        let pcap = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(pcap[..4], 0xa1b2c3d4u32.to_le_bytes());
+        assert_eq!(pcap[20..24], LINKTYPE_RAW.to_le_bytes());
+        let mut packets = Vec::new();
+        let mut rest = &pcap[24..];
+        while !rest.is_empty() {
+            let len = u32::from_le_bytes(rest[8..12].try_into().unwrap()) as usize;
+            packets.push(&rest[16..16 + len]);
+            rest = &rest[16 + len..];
+        }
+        let mut client_stream: Vec<u8> = Vec::new();
+        let mut server_stream: Vec<u8> = Vec::new();
+        for packet in &packets {
+            // valid IPv4 and TCP checksums sum up to zero
+            assert_eq!(internet_checksum(&[&packet[..20]]), 0);
+            let tcp = &packet[20..];
+            let mut pseudo_header = packet[12..20].to_vec();
+            pseudo_header.extend([0, 6]);
+            pseudo_header.extend((tcp.len() as u16).to_be_bytes());
+            assert_eq!(internet_checksum(&[&pseudo_header, tcp]), 0);
+            match u16::from_be_bytes([tcp[0], tcp[1]]) {
+                443 => server_stream.extend(&tcp[20..]),
+                _ => client_stream.extend(&tcp[20..]),
+            }
+        }
+        // SYN, SYN-ACK and ACK first, FIN last
+        assert_eq!(
+            packets[..3]
+                .iter()
+                .map(|packet| packet[33])
+                .collect::<Vec<_>>(),
+            [TCP_SYN, TCP_SYN | TCP_ACK, TCP_ACK]
+        );
+        assert_eq!(packets.last().unwrap()[33], TCP_FIN | TCP_ACK);
+        assert_eq!(client_stream, sent);
+        assert_eq!(server_stream, received);
+    }
+}
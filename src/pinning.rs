@@ -0,0 +1,106 @@
+use crate::certificate::spki_sha256;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::collections::HashMap;
+use std::fmt;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc7469.html#2-4--Semantics-of-Pins
+// A pin is the SHA-256 hash of a DER encoded SubjectPublicKeyInfo, written base64 encoded
+// like the pin-sha256 directive. A connection to a pinned host is accepted when any
+// certificate of the validated path has a pinned key, so pinning the leaf key of a
+// self-signed internal service works as well as pinning a CA key. Certificates the peer
+// sent but that are not on the path do not count. Hosts without pins are not checked.
+#[derive(Debug, Clone, Default)]
+pub struct PinSet {
+    pins: HashMap<String, Vec<[u8; 32]>>,
+}
+
+impl PinSet {
+    pub fn new() -> Self {
+        PinSet::default()
+    }
+
+    pub fn add(&mut self, host: &str, pin: [u8; 32]) {
+        self.pins.entry(normalize(host)).or_default().push(pin);
+    }
+
+    // pin is base64 encoded, e.g. the output of
+    // `openssl x509 -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256 -binary | base64`
+    pub fn add_base64(&mut self, host: &str, pin: &str) -> Result<(), base64::DecodeError> {
+        let decoded = STANDARD.decode(pin)?;
+        let pin = decoded
+            .try_into()
+            .map_err(|_| base64::DecodeError::InvalidLength)?;
+        self.add(host, pin);
+        Ok(())
+    }
+
+    // path is DER encoded, leaf first, as returned by verify::verify_chain
+    pub fn check(&self, host: &str, path: &[Vec<u8>]) -> Result<(), PinError> {
+        let host = normalize(host);
+        let Some(pins) = self.pins.get(&host) else {
+            return Ok(());
+        };
+        let presented: Vec<[u8; 32]> = path.iter().filter_map(|cert| spki_sha256(cert)).collect();
+        if presented.iter().any(|pin| pins.contains(pin)) {
+            return Ok(());
+        }
+        Err(PinError {
+            host,
+            presented: presented.iter().map(|pin| STANDARD.encode(pin)).collect(),
+        })
+    }
+}
+
+// None of the keys on the path is pinned for the host.
+#[derive(Debug, PartialEq)]
+pub struct PinError {
+    pub host: String,
+    // base64 encoded SHA-256 hashes of the SubjectPublicKeyInfos on the path, leaf first
+    pub presented: Vec<String>,
+}
+
+impl fmt::Display for PinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no pinned key for {}, presented pins:", self.host)?;
+        for pin in &self.presented {
+            write!(f, " pin-sha256=\"{}\"", pin)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PinError {}
+
+// host names are compared case-insensitively and without a trailing dot
+fn normalize(host: &str) -> String {
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA: &[u8] = include_bytes!("../testdata/ocsp/ca.der");
+    const LEAF: &[u8] = include_bytes!("../testdata/ocsp/leaf.der");
+
+    #[test]
+    fn check_pins() {
+        let chain = [LEAF.to_vec(), CA.to_vec()];
+        let ca_pin = STANDARD.encode(spki_sha256(CA).unwrap());
+
+        let mut pins = PinSet::new();
+        pins.add_base64("Internal.Example.", &ca_pin).unwrap();
+        pins.add("other.example", [0; 32]);
+        assert_eq!(pins.check("internal.example", &chain), Ok(()));
+        // hosts without pins are not checked
+        assert_eq!(pins.check("unpinned.example", &chain), Ok(()));
+
+        let err = pins.check("other.example", &chain).unwrap_err();
+        assert_eq!(err.presented.len(), 2);
+        assert_eq!(err.presented[1], ca_pin);
+        assert!(err.to_string().contains(&ca_pin));
+
+        assert!(pins.add_base64("short.example", "AAAA").is_err());
+    }
+}
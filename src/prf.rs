@@ -1,20 +1,27 @@
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#5--HMAC-and-the-Pseudorandom-Function
 
-use bytes::{Bytes, BytesMut};
+use bytes::BytesMut;
 use ring::hmac;
 
 // Ref: https://github.com/rustls/rustls/blob/main/rustls/src/tls12/prf.rs#L33-L36
-pub fn prf(length: usize, secret: &[u8], seed: &[u8], label: &[u8]) -> BytesMut {
+// P_hash is P_SHA256 unless the cipher suite says otherwise (e.g. *_SHA384 suites use P_SHA384).
+pub fn prf(
+    algorithm: hmac::Algorithm,
+    length: usize,
+    secret: &[u8],
+    seed: &[u8],
+    label: &[u8],
+) -> BytesMut {
     let seed = [label, seed].concat();
-    p_hash(length, secret, &seed)
+    p_hash(algorithm, length, secret, &seed)
 }
 
 // Ref: https://github.com/rustls/rustls/blob/main/rustls/src/tls12/prf.rs#L10-L24
 // Ref: https://github.com/sat0ken/go-tcpip/blob/main/tls_prf.go
 // Ref: https://cs.opensource.google/go/go/+/refs/heads/master:src/crypto/tls/prf.go;drc=b3bc8620f89153fddc1a30ee17c1d93654ed4314;l=27
-fn p_hash(length: usize, secret: &[u8], seed: &[u8]) -> BytesMut {
+fn p_hash(algorithm: hmac::Algorithm, length: usize, secret: &[u8], seed: &[u8]) -> BytesMut {
     let mut buf = BytesMut::with_capacity(length);
-    let hmac_key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+    let hmac_key = hmac::Key::new(algorithm, secret);
     // A(0) = seed
     let mut a = hmac::sign(&hmac_key, seed);
 
@@ -42,6 +49,9 @@ mod tests {
         let label = b"test label";
         let expect_result = b"\xe3\xf2\x29\xba\x72\x7b\xe1\x7b\x8d\x12\x26\x20\x55\x7c\xd4\x53\xc2\xaa\xb2\x1d\x07\xc3\xd4\x95\x32\x9b\x52\xd4\xe6\x1e\xdb\x5a\x6b\x30\x17\x91\xe9\x0d\x35\xc9\xc9\xa4\x6b\x4e\x14\xba\xf9\xaf\x0f\xa0\x22\xf7\x07\x7d\xef\x17\xab\xfd\x37\x97\xc0\x56\x4b\xab\x4f\xbc\x91\x66\x6e\x9d\xef\x9b\x97\xfc\xe3\x4f\x79\x67\x89\xba\xa4\x80\x82\xd1\x22\xee\x42\xc5\xa7\x2e\x5a\x51\x10\xff\xf7\x01\x87\x34\x7b\x66";
 
-        assert_eq!(&prf(100, secret, seed, label)[..], &expect_result[..]);
+        assert_eq!(
+            &prf(hmac::HMAC_SHA256, 100, secret, seed, label)[..],
+            &expect_result[..]
+        );
     }
 }
@@ -1,3 +1,5 @@
+use crate::codec::{Codec, Reader};
+
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#A-1--Record-Layer
 // struct {
 //     uint8 major;
@@ -32,3 +34,18 @@ impl ProtocolVersion {
         bytes
     }
 }
+
+// the version as it is on the wire, e.g. 0x03 0x03 for TLS 1.2
+impl Codec for ProtocolVersion {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.push(self.major);
+        bytes.push(self.minor);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(ProtocolVersion {
+            major: u8::decode(r)?,
+            minor: u8::decode(r)?,
+        })
+    }
+}
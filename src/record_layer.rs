@@ -0,0 +1,117 @@
+use crate::enums::ContentType;
+use crate::error::Error;
+use crate::protocol_version::ProtocolVersion;
+use crate::tls_ciphertext::GenericAEADCipher;
+use crate::tls_plaintext::{TLSPlaintext, MAX_CIPHERTEXT_LEN, MAX_FRAGMENT_LEN, RECORD_HEADER_LEN};
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#6--The-TLS-Record-Protocol
+// Reads and writes TLS records. Until ChangeCipherSpec is processed records are sent as TLSPlaintext,
+// after that each direction is protected by its own GenericAEADCipher and sequence number.
+// It does no I/O, records are taken from and appended to byte buffers.
+pub struct RecordLayer {
+    encrypter: Option<GenericAEADCipher>,
+    decrypter: Option<GenericAEADCipher>,
+    write_seq: u64,
+    read_seq: u64,
+    // largest plaintext fragment in either direction, lowered by max_fragment_length (RFC 6066)
+    max_fragment_len: usize,
+}
+
+impl RecordLayer {
+    pub fn new() -> Self {
+        RecordLayer {
+            encrypter: None,
+            decrypter: None,
+            write_seq: 0,
+            read_seq: 0,
+            max_fragment_len: MAX_FRAGMENT_LEN,
+        }
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#4--Maximum-Fragment-Length-Negotiation
+    // applies to every record after the ServerHello that negotiated it
+    pub fn set_max_fragment_len(&mut self, max_fragment_len: usize) {
+        self.max_fragment_len = max_fragment_len;
+    }
+
+    // called after sending ChangeCipherSpec
+    pub fn set_encrypter(&mut self, cipher: GenericAEADCipher) {
+        self.encrypter = Some(cipher);
+        self.write_seq = 0;
+    }
+
+    // called after receiving ChangeCipherSpec
+    pub fn set_decrypter(&mut self, cipher: GenericAEADCipher) {
+        self.decrypter = Some(cipher);
+        self.read_seq = 0;
+    }
+
+    // removes the first record from buf. None if buf does not hold a complete record yet.
+    pub fn read_record(
+        &mut self,
+        buf: &mut Vec<u8>,
+    ) -> Result<Option<(ContentType, Vec<u8>)>, Error> {
+        if buf.len() < RECORD_HEADER_LEN {
+            return Ok(None);
+        }
+        let plaintext = TLSPlaintext::read(buf[..RECORD_HEADER_LEN].to_vec())?;
+        let len = plaintext.length() as usize;
+        if len > MAX_CIPHERTEXT_LEN {
+            return Err(Error::RecordOverflow);
+        }
+        if buf.len() < RECORD_HEADER_LEN + len {
+            return Ok(None);
+        }
+        let fragment = buf[RECORD_HEADER_LEN..RECORD_HEADER_LEN + len].to_vec();
+        buf.drain(..RECORD_HEADER_LEN + len);
+
+        let content_type = plaintext.content_type();
+        match &self.decrypter {
+            Some(decrypter) => {
+                let fragment = decrypter
+                    .decrypt(self.read_seq, content_type, &fragment)
+                    .ok_or(Error::BadRecordMac)?;
+                if fragment.len() > self.max_fragment_len {
+                    return Err(Error::RecordOverflow);
+                }
+                self.read_seq += 1;
+                Ok(Some((content_type, fragment)))
+            }
+            None if fragment.len() > self.max_fragment_len => Err(Error::RecordOverflow),
+            None => Ok(Some((content_type, fragment))),
+        }
+    }
+
+    // appends data to out as one or more records
+    pub fn write_record(&mut self, out: &mut Vec<u8>, content_type: ContentType, data: &[u8]) {
+        // an empty message (e.g. zero length application data) is still sent as one record
+        let chunks: Vec<&[u8]> = if data.is_empty() {
+            vec![data]
+        } else {
+            data.chunks(self.max_fragment_len).collect()
+        };
+        for chunk in chunks {
+            let fragment = match &self.encrypter {
+                Some(encrypter) => {
+                    let fragment = encrypter.encrypt(self.write_seq, content_type, chunk);
+                    self.write_seq += 1;
+                    fragment
+                }
+                None => chunk.to_vec(),
+            };
+            let header = TLSPlaintext::new(
+                content_type,
+                ProtocolVersion::new(1, 2),
+                fragment.len() as u16,
+            );
+            out.extend(header.encode());
+            out.extend(fragment);
+        }
+    }
+}
+
+impl Default for RecordLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,137 @@
+use crate::cipher_suite::DEFAULT_CIPHER_SUITES;
+use crate::client_hello::ClientHelloPayload;
+use crate::connection::choose_cipher_suite;
+use crate::sign::CertifiedKey;
+use std::collections::HashMap;
+
+// Chooses the certificate chain and signing key for a full handshake.
+// Implement this to load certificates on demand or from a database.
+pub trait ResolvesServerCert: Send + Sync {
+    // None aborts the handshake with an unrecognized_name alert
+    fn resolve(&self, client_hello: &ClientHelloPayload) -> Option<CertifiedKey>;
+}
+
+// A server with a single certificate uses it for every ClientHello.
+impl ResolvesServerCert for CertifiedKey {
+    fn resolve(&self, _client_hello: &ClientHelloPayload) -> Option<CertifiedKey> {
+        Some(self.clone())
+    }
+}
+
+// Certificates keyed by host name. A name like "*.example.com" matches exactly one
+// label in place of the asterisk, e.g. "www.example.com" but not "example.com" or
+// "a.b.example.com".
+//
+// Several certificates can be added for the same name, e.g. an RSA and an ECDSA one.
+// The first whose cipher suite the client offers and whose key can sign with one of the
+// client's signature_algorithms is used.
+#[derive(Default)]
+pub struct SniResolver {
+    by_name: HashMap<String, Vec<CertifiedKey>>,
+    // used when the client sends no server_name or no name matches
+    default: Vec<CertifiedKey>,
+}
+
+impl SniResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: &str, certified_key: CertifiedKey) {
+        self.by_name
+            .entry(normalize(name))
+            .or_default()
+            .push(certified_key);
+    }
+
+    pub fn add_default(&mut self, certified_key: CertifiedKey) {
+        self.default.push(certified_key);
+    }
+
+    fn candidates(&self, server_name: Option<&str>) -> &[CertifiedKey] {
+        let Some(server_name) = server_name.map(normalize) else {
+            return &self.default;
+        };
+        if let Some(certified_keys) = self.by_name.get(&server_name) {
+            return certified_keys;
+        }
+        let wildcard = server_name
+            .split_once('.')
+            .and_then(|(_, parent)| self.by_name.get(&format!("*.{}", parent)));
+        match wildcard {
+            Some(certified_keys) => certified_keys,
+            None => &self.default,
+        }
+    }
+}
+
+impl ResolvesServerCert for SniResolver {
+    fn resolve(&self, client_hello: &ClientHelloPayload) -> Option<CertifiedKey> {
+        let cipher_suites = client_hello.cipher_suites();
+        let signature_algorithms = client_hello.signature_algorithms();
+        self.candidates(client_hello.server_name().as_deref())
+            .iter()
+            .find(|certified_key| {
+                choose_cipher_suite(
+                    &DEFAULT_CIPHER_SUITES,
+                    certified_key.key.certificate_type(),
+                    cipher_suites,
+                )
+                .is_some()
+                    && certified_key
+                        .key
+                        .choose_scheme(&signature_algorithms)
+                        .is_some()
+            })
+            .cloned()
+    }
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#3--Server-Name-Indication
+// host names are compared case-insensitively and without a trailing dot
+fn normalize(name: &str) -> String {
+    name.trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::SignatureScheme;
+    use crate::sign::SigningKey;
+    use std::sync::Arc;
+
+    struct TestKey;
+
+    impl SigningKey for TestKey {
+        fn schemes(&self) -> Vec<SignatureScheme> {
+            vec![SignatureScheme::rsa_pkcs1_sha256]
+        }
+
+        fn certificate_type(&self) -> u8 {
+            crate::sign::RSA_SIGN
+        }
+
+        fn sign(&self, _scheme: SignatureScheme, _message: &[u8]) -> Option<Vec<u8>> {
+            None
+        }
+    }
+
+    fn certified_key(name: &str) -> CertifiedKey {
+        CertifiedKey::new(vec![name.as_bytes().to_vec()], Arc::new(TestKey))
+    }
+
+    #[test]
+    fn exact_then_wildcard_then_default() {
+        let mut resolver = SniResolver::new();
+        resolver.add("example.com", certified_key("example.com"));
+        resolver.add("*.Example.com", certified_key("wildcard"));
+        resolver.add_default(certified_key("default"));
+
+        let resolve = |name: Option<&str>| resolver.candidates(name)[0].cert_chain[0].clone();
+        assert_eq!(resolve(Some("EXAMPLE.com.")), b"example.com");
+        assert_eq!(resolve(Some("www.example.com")), b"wildcard");
+        assert_eq!(resolve(Some("a.b.example.com")), b"default");
+        assert_eq!(resolve(Some("example.org")), b"default");
+        assert_eq!(resolve(None), b"default");
+    }
+}
@@ -0,0 +1,934 @@
+use crate::certificate::Certificate;
+use crate::certificate_request::CertificateRequest;
+use crate::certificate_status::CertificateStatus;
+use crate::certificate_verify::CertificateVerify;
+use crate::cipher_suite::{CipherSuite, CipherSuiteParams, DEFAULT_CIPHER_SUITES};
+use crate::client_hello::ClientHelloPayload;
+use crate::client_key_exchange::ClientKeyExchange;
+use crate::codec::Codec;
+use crate::config;
+use crate::connection::{
+    choose_cipher_suite, key_expansion, Connection, ConnectionCommon, Message,
+};
+use crate::enums::{AlertDescription, AlertLevel, HandshakeType, NamedGroup, SignatureScheme};
+use crate::error::{CertificateError, ConfigError, Error};
+use crate::extensions::ServerExtension;
+use crate::handshake::{HandshakePayload, MasterSecretInfo, Random, SessionId};
+use crate::key_exchange::{KeyExchange, SUPPORTED_GROUPS};
+use crate::key_log::{KeyLog, NoKeyLog};
+use crate::pcap::PcapRecorder;
+use crate::resolver::ResolvesServerCert;
+use crate::server_hello::ServerHelloPayload;
+use crate::server_hello_done::ServerHelloDone;
+use crate::server_key_exchange::{signed_params, ServerKeyExchange};
+use crate::session::{unix_time, ServerSessionCache, ServerSessionValue};
+use crate::sign::{
+    verify_signature, CertifiedKey, ECDSA_SIGN, RSA_SIGN, SUPPORTED_SIGNATURE_SCHEMES,
+};
+use crate::ticket::Ticketer;
+use crate::tls_plaintext::MAX_FRAGMENT_LEN;
+use crate::verify::{verify_chain, RootCertStore};
+use std::mem;
+use std::sync::Arc;
+
+// TLS 1.2 server side of a connection, driven through the Connection trait.
+//
+// Full handshake:
+// ClientHello -> ServerHello, Certificate, CertificateStatus**, ServerKeyExchange,
+// CertificateRequest***, ServerHelloDone
+// -> Certificate***, ClientKeyExchange, CertificateVerify***, [ChangeCipherSpec], Finished
+// -> NewSessionTicket*, [ChangeCipherSpec], Finished
+//
+// Abbreviated handshake, when the client offers a cached session_id or a valid ticket:
+// ClientHello -> ServerHello, NewSessionTicket*, [ChangeCipherSpec], Finished
+// -> [ChangeCipherSpec], Finished
+//
+// * only when a Ticketer is set and the client sent the session_ticket extension
+// ** only when the certificate has a current OCSP response and the client sent status_request
+// *** only when client authentication is configured. CertificateVerify is only sent
+// by a client with a certificate.
+//
+// Renegotiation is refused with a no_renegotiation warning alert, unless it is enabled with
+// ServerConfigBuilder::with_secure_renegotiation and the client supports RFC 5746.
+pub struct ServerConnection {
+    common: ConnectionCommon,
+    config: Arc<ServerConfig>,
+    state: ServerState,
+    // host_name from the client's server_name extension
+    server_name: Option<String>,
+    // certificates of an authenticated client, the leaf comes first
+    peer_certificates: Vec<Vec<u8>>,
+    // protocol selected with ALPN
+    alpn_protocol: Option<Vec<u8>>,
+    resumed: bool,
+    // RFC 5746: the client sent renegotiation_info or the SCSV in the initial ClientHello
+    secure_renegotiation: bool,
+    // verify_data of the Finished messages of the last handshake
+    client_verify_data: Vec<u8>,
+    server_verify_data: Vec<u8>,
+}
+
+// The message the server waits for. The states after ClientHello carry what was negotiated.
+enum ServerState {
+    ExpectClientHello,
+    // only when client authentication is configured
+    ExpectCertificate(Box<ServerHandshake>, KeyExchange),
+    ExpectClientKeyExchange(Box<ServerHandshake>, KeyExchange),
+    // only when the client sent a certificate
+    ExpectCertificateVerify(Box<ServerHandshake>, MasterSecretInfo),
+    ExpectChangeCipherSpec(Box<ServerHandshake>, MasterSecretInfo),
+    ExpectFinished(Box<ServerHandshake>, MasterSecretInfo),
+    Connected,
+}
+
+impl ServerState {
+    fn expected(&self) -> &'static str {
+        match self {
+            ServerState::ExpectClientHello => "ClientHello",
+            ServerState::ExpectCertificate(..) => "Certificate",
+            ServerState::ExpectClientKeyExchange(..) => "ClientKeyExchange",
+            ServerState::ExpectCertificateVerify(..) => "CertificateVerify",
+            ServerState::ExpectChangeCipherSpec(..) => "ChangeCipherSpec",
+            ServerState::ExpectFinished(..) => "Finished",
+            ServerState::Connected => "no handshake message",
+        }
+    }
+}
+
+// What ClientHello negotiated, completed by the following messages.
+struct ServerHandshake {
+    suite: &'static CipherSuiteParams,
+    // session_id from ServerHello, the key of the session cache
+    session_id: SessionId,
+    client_random: Vec<u8>,
+    server_random: Vec<u8>,
+    send_ticket: bool,
+    extended_master_secret: bool,
+    // subjectPublicKey of the client's certificate, it signs CertificateVerify
+    client_public_key: Option<Vec<u8>>,
+}
+
+// Whether the server asks for a client certificate, and the trust anchors for it.
+enum ClientAuth {
+    None,
+    // a client without a certificate is accepted
+    Optional(RootCertStore),
+    Required(RootCertStore),
+}
+
+// Settings of server connections, validated by ServerConfigBuilder::build.
+// It is immutable, so one Arc<ServerConfig> can be shared by many connections.
+pub struct ServerConfig {
+    // in the server's order of preference, which wins over the client's
+    cipher_suites: Vec<CipherSuite>,
+    named_groups: Vec<NamedGroup>,
+    signature_schemes: Vec<SignatureScheme>,
+    cert_resolver: Arc<dyn ResolvesServerCert>,
+    session_cache: Option<Arc<ServerSessionCache>>,
+    ticketer: Option<Arc<Ticketer>>,
+    // in order of preference, empty to ignore ALPN
+    alpn_protocols: Vec<Vec<u8>>,
+    client_auth: ClientAuth,
+    // abort handshakes without the extended master secret (RFC 7627)
+    require_ems: bool,
+    // renegotiate with clients that support RFC 5746
+    secure_renegotiation: bool,
+    key_log: Arc<dyn KeyLog>,
+    time_provider: fn() -> u64,
+}
+
+impl ServerConfig {
+    pub fn builder() -> ServerConfigBuilder {
+        ServerConfigBuilder::new()
+    }
+
+    pub fn cipher_suites(&self) -> &[CipherSuite] {
+        &self.cipher_suites
+    }
+
+    pub fn alpn_protocols(&self) -> &[Vec<u8>] {
+        &self.alpn_protocols
+    }
+}
+
+pub struct ServerConfigBuilder {
+    cipher_suites: Vec<CipherSuite>,
+    named_groups: Vec<NamedGroup>,
+    signature_schemes: Vec<SignatureScheme>,
+    cert_resolver: Option<Arc<dyn ResolvesServerCert>>,
+    session_cache: Option<Arc<ServerSessionCache>>,
+    ticketer: Option<Arc<Ticketer>>,
+    alpn_protocols: Vec<Vec<u8>>,
+    client_auth: ClientAuth,
+    require_ems: bool,
+    secure_renegotiation: bool,
+    key_log: Arc<dyn KeyLog>,
+    time_provider: fn() -> u64,
+}
+
+impl ServerConfigBuilder {
+    // the crate's defaults, without a certificate
+    pub fn new() -> Self {
+        ServerConfigBuilder {
+            cipher_suites: DEFAULT_CIPHER_SUITES.to_vec(),
+            named_groups: SUPPORTED_GROUPS.to_vec(),
+            signature_schemes: SUPPORTED_SIGNATURE_SCHEMES.to_vec(),
+            cert_resolver: None,
+            session_cache: None,
+            ticketer: None,
+            alpn_protocols: Vec::new(),
+            client_auth: ClientAuth::None,
+            require_ems: false,
+            secure_renegotiation: false,
+            key_log: Arc::new(NoKeyLog),
+            time_provider: unix_time,
+        }
+    }
+
+    // enabled cipher suites, most preferred first
+    pub fn with_cipher_suites(mut self, cipher_suites: Vec<CipherSuite>) -> Self {
+        self.cipher_suites = cipher_suites;
+        self
+    }
+
+    // enabled named groups, most preferred first
+    pub fn with_named_groups(mut self, named_groups: Vec<NamedGroup>) -> Self {
+        self.named_groups = named_groups;
+        self
+    }
+
+    // schemes for ServerKeyExchange and the client's CertificateVerify
+    pub fn with_signature_schemes(mut self, signature_schemes: Vec<SignatureScheme>) -> Self {
+        self.signature_schemes = signature_schemes;
+        self
+    }
+
+    // serve the same certificate to every client
+    pub fn with_single_cert(self, certified_key: CertifiedKey) -> Self {
+        self.with_cert_resolver(Arc::new(certified_key))
+    }
+
+    // choose the certificate per ClientHello, e.g. by server name
+    pub fn with_cert_resolver(mut self, cert_resolver: Arc<dyn ResolvesServerCert>) -> Self {
+        self.cert_resolver = Some(cert_resolver);
+        self
+    }
+
+    // resume sessions by session_id
+    pub fn with_session_cache(mut self, session_cache: Arc<ServerSessionCache>) -> Self {
+        self.session_cache = Some(session_cache);
+        self
+    }
+
+    // issue and accept session tickets (RFC 5077)
+    pub fn with_ticketer(mut self, ticketer: Arc<Ticketer>) -> Self {
+        self.ticketer = Some(ticketer);
+        self
+    }
+
+    // ALPN protocol names, most preferred first. A client that offers ALPN but none
+    // of these is refused with a no_application_protocol alert.
+    pub fn with_alpn_protocols(mut self, alpn_protocols: Vec<Vec<u8>>) -> Self {
+        self.alpn_protocols = alpn_protocols;
+        self
+    }
+
+    // require a client certificate that leads to one of roots
+    pub fn with_client_auth(mut self, roots: RootCertStore) -> Self {
+        self.client_auth = ClientAuth::Required(roots);
+        self
+    }
+
+    // ask for a client certificate that leads to one of roots, but accept clients without one
+    pub fn with_optional_client_auth(mut self, roots: RootCertStore) -> Self {
+        self.client_auth = ClientAuth::Optional(roots);
+        self
+    }
+
+    // refuse clients that do not support the extended master secret (RFC 7627)
+    pub fn with_require_ems(mut self, require_ems: bool) -> Self {
+        self.require_ems = require_ems;
+        self
+    }
+
+    // Renegotiate when a client that supports RFC 5746 sends a ClientHello after the handshake,
+    // and allow ServerConnection::request_renegotiation. Off by default.
+    pub fn with_secure_renegotiation(mut self, secure_renegotiation: bool) -> Self {
+        self.secure_renegotiation = secure_renegotiation;
+        self
+    }
+
+    // receives the master secret of every handshake, e.g. a KeyLogFile
+    pub fn with_key_log(mut self, key_log: Arc<dyn KeyLog>) -> Self {
+        self.key_log = key_log;
+        self
+    }
+
+    // the current time for client certificate checks and stapled OCSP responses,
+    // session::unix_time by default. Tests set the time their fixtures are valid at.
+    pub fn with_time_provider(mut self, time_provider: fn() -> u64) -> Self {
+        self.time_provider = time_provider;
+        self
+    }
+
+    pub fn build(self) -> Result<ServerConfig, ConfigError> {
+        config::check_signature_schemes(&self.signature_schemes)?;
+        config::check_cipher_suites(&self.cipher_suites, &self.signature_schemes)?;
+        config::check_named_groups(&self.named_groups)?;
+        config::check_alpn_protocols(&self.alpn_protocols)?;
+        let Some(cert_resolver) = self.cert_resolver else {
+            return Err(ConfigError::new("no server certificate"));
+        };
+        if let ClientAuth::Optional(roots) | ClientAuth::Required(roots) = &self.client_auth {
+            if roots.is_empty() {
+                return Err(ConfigError::new(
+                    "client authentication without root certificates",
+                ));
+            }
+        }
+        Ok(ServerConfig {
+            cipher_suites: self.cipher_suites,
+            named_groups: self.named_groups,
+            signature_schemes: self.signature_schemes,
+            cert_resolver,
+            session_cache: self.session_cache,
+            ticketer: self.ticketer,
+            alpn_protocols: self.alpn_protocols,
+            client_auth: self.client_auth,
+            require_ems: self.require_ems,
+            secure_renegotiation: self.secure_renegotiation,
+            key_log: self.key_log,
+            time_provider: self.time_provider,
+        })
+    }
+}
+
+impl Default for ServerConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServerConnection {
+    pub fn new(config: Arc<ServerConfig>) -> Self {
+        ServerConnection {
+            common: ConnectionCommon::new(),
+            config,
+            state: ServerState::ExpectClientHello,
+            server_name: None,
+            peer_certificates: Vec::new(),
+            alpn_protocol: None,
+            resumed: false,
+            secure_renegotiation: false,
+            client_verify_data: Vec::new(),
+            server_verify_data: Vec::new(),
+        }
+    }
+
+    // the server name the client asked for (SNI), if any
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    // DER encoded certificates of the client, the leaf comes first.
+    // Empty unless the client authenticated.
+    pub fn peer_certificates(&self) -> &[Vec<u8>] {
+        &self.peer_certificates
+    }
+
+    // the protocol selected with ALPN
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    // true if the handshake resumed a session
+    pub fn is_resumed(&self) -> bool {
+        self.resumed
+    }
+
+    // capture the connection to a pcap file, see PcapRecorder
+    pub fn set_recorder(&mut self, recorder: PcapRecorder) {
+        self.common.set_recorder(recorder, false);
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-1-1--Hello-Request
+    // Ask the client for a new handshake with HelloRequest. The client may refuse with a
+    // no_renegotiation warning, the connection then goes on as before.
+    pub fn request_renegotiation(&mut self) -> Result<(), Error> {
+        if !self.config.secure_renegotiation || !self.secure_renegotiation {
+            return Err(Error::Renegotiation(String::from(
+                "secure renegotiation is not enabled or not supported by the client",
+            )));
+        }
+        if self.common.is_handshaking() {
+            return Err(Error::Renegotiation(String::from(
+                "a handshake is in progress",
+            )));
+        }
+        // send_handshake adds HelloRequest to the transcript of the last handshake, which
+        // start_peer_handshake clears when the ClientHello arrives, so it is never hashed
+        self.common.send_handshake(HandshakePayload::encode_message(
+            HandshakeType::HelloRequest,
+            &[],
+        ));
+        Ok(())
+    }
+
+    fn process_messages(&mut self) -> Result<(), Error> {
+        while let Some(message) = self.common.next_message()? {
+            let state = mem::replace(&mut self.state, ServerState::Connected);
+            self.state = match message {
+                Message::Handshake(msg_type, body) => {
+                    self.handle_handshake(state, msg_type, body)?
+                }
+                Message::ChangeCipherSpec => self.handle_change_cipher_spec(state)?,
+            };
+        }
+        Ok(())
+    }
+
+    fn handle_handshake(
+        &mut self,
+        state: ServerState,
+        msg_type: HandshakeType,
+        body: Vec<u8>,
+    ) -> Result<ServerState, Error> {
+        match (state, msg_type) {
+            (ServerState::ExpectClientHello, HandshakeType::ClientHello) => {
+                self.handle_client_hello(body)
+            }
+            (ServerState::Connected, HandshakeType::ClientHello) => {
+                if !self.config.secure_renegotiation || !self.secure_renegotiation {
+                    self.refuse_renegotiation();
+                    return Ok(ServerState::Connected);
+                }
+                let message = HandshakePayload::encode_message(HandshakeType::ClientHello, &body);
+                self.common.start_peer_handshake(&message);
+                self.handle_client_hello(body)
+            }
+            (
+                ServerState::ExpectCertificate(mut handshake, key_exchange),
+                HandshakeType::Certificate,
+            ) => {
+                handshake.client_public_key = self.read_client_certificate(body)?;
+                Ok(ServerState::ExpectClientKeyExchange(
+                    handshake,
+                    key_exchange,
+                ))
+            }
+            (
+                ServerState::ExpectClientKeyExchange(handshake, key_exchange),
+                HandshakeType::ClientKeyExchange,
+            ) => self.handle_client_key_exchange(handshake, key_exchange, body),
+            (
+                ServerState::ExpectCertificateVerify(handshake, secrets),
+                HandshakeType::CertificateVerify,
+            ) => {
+                if let Some(public_key) = &handshake.client_public_key {
+                    self.verify_client_signature(public_key, body)?;
+                }
+                Ok(ServerState::ExpectChangeCipherSpec(handshake, secrets))
+            }
+            (ServerState::ExpectFinished(handshake, secrets), HandshakeType::Finished) => {
+                self.handle_finished(handshake, secrets, body)
+            }
+            (state, msg_type) => Err(Error::UnexpectedMessage(format!(
+                "expected {}, got {:?}",
+                state.expected(),
+                msg_type
+            ))),
+        }
+    }
+
+    fn handle_change_cipher_spec(&mut self, state: ServerState) -> Result<ServerState, Error> {
+        match state {
+            ServerState::ExpectChangeCipherSpec(handshake, secrets) => {
+                let (client_cipher, _) = key_expansion(&secrets);
+                self.common.set_decrypter(client_cipher);
+                Ok(ServerState::ExpectFinished(handshake, secrets))
+            }
+            state => Err(Error::UnexpectedMessage(format!(
+                "expected {}, got ChangeCipherSpec",
+                state.expected()
+            ))),
+        }
+    }
+
+    fn handle_client_hello(&mut self, body: Vec<u8>) -> Result<ServerState, Error> {
+        let client_hello = ClientHelloPayload::read(&body)?;
+
+        // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#E-1--Compatibility-with-TLS-1-0-1-1-and-SSL-3-0
+        let version = client_hello.client_version();
+        if (version.major, version.minor) < (0x03, 0x03) {
+            return Err(Error::ProtocolVersion(String::from(
+                "client does not support TLS 1.2",
+            )));
+        }
+        if !client_hello.compression_methods().contains(&0x00) {
+            return Err(Error::IllegalParameter(String::from(
+                "client did not offer the null compression method",
+            )));
+        }
+        let config = Arc::clone(&self.config);
+        self.server_name = client_hello.server_name();
+        let Some(certified_key) = config.cert_resolver.resolve(&client_hello) else {
+            return Err(Error::UnrecognizedName(String::from(
+                "no certificate for the requested server name",
+            )));
+        };
+        // the certificate determines the cipher suite
+        let Some(suite) = choose_cipher_suite(
+            &config.cipher_suites,
+            certified_key.key.certificate_type(),
+            client_hello.cipher_suites(),
+        )
+        .and_then(CipherSuite::params) else {
+            return Err(Error::HandshakeFailure(String::from(
+                "no shared cipher suite",
+            )));
+        };
+
+        let mut extensions = Vec::new();
+        // Ref: https://tex2e.github.io/rfc-translater/html/rfc5746.html#3-6--Server-Behavior--Initial-Handshake
+        // Ref: https://tex2e.github.io/rfc-translater/html/rfc5746.html#3-7--Server-Behavior--Secure-Renegotiation
+        let renegotiated_connection = client_hello.renegotiation_info();
+        if !self.common.is_established() {
+            match renegotiated_connection {
+                Some(info) if !info.is_empty() => {
+                    return Err(Error::HandshakeFailure(String::from(
+                        "renegotiated_connection must be empty on the initial handshake",
+                    )))
+                }
+                Some(_) => extensions.push(ServerExtension::RenegotiationInfo(vec![])),
+                None => {}
+            }
+            self.secure_renegotiation = renegotiated_connection.is_some();
+        } else {
+            // the SCSV counts as an empty renegotiation_info and does not match either
+            if renegotiated_connection.as_ref() != Some(&self.client_verify_data) {
+                return Err(Error::HandshakeFailure(String::from(
+                    "renegotiation_info does not match the previous handshake",
+                )));
+            }
+            extensions.push(ServerExtension::RenegotiationInfo(
+                [
+                    self.client_verify_data.as_slice(),
+                    self.server_verify_data.as_slice(),
+                ]
+                .concat(),
+            ));
+        }
+        let send_ticket = config.ticketer.is_some() && client_hello.session_ticket().is_some();
+        if send_ticket {
+            extensions.push(ServerExtension::SessionTicketAck);
+        }
+        // Ref: https://tex2e.github.io/rfc-translater/html/rfc7627.html#5-2--Client-and-Server-Behavior--Full-Handshake
+        let extended_master_secret = client_hello.extended_master_secret();
+        if extended_master_secret {
+            extensions.push(ServerExtension::ExtendedMasterSecretAck);
+        } else if config.require_ems {
+            return Err(Error::HandshakeFailure(String::from(
+                "client does not support the extended master secret",
+            )));
+        }
+        self.alpn_protocol = self.choose_alpn_protocol(&client_hello)?;
+        if let Some(protocol) = &self.alpn_protocol {
+            extensions.push(ServerExtension::Protocol(protocol.clone()));
+        }
+        // Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#4--Maximum-Fragment-Length-Negotiation
+        let max_fragment_len = match client_hello.max_fragment_length() {
+            Some(max_fragment_length) => {
+                let Some(len) = max_fragment_length.fragment_len() else {
+                    return Err(Error::IllegalParameter(String::from(
+                        "invalid max_fragment_length",
+                    )));
+                };
+                extensions.push(ServerExtension::MaxFragmentLength(max_fragment_length));
+                len
+            }
+            None => MAX_FRAGMENT_LEN,
+        };
+
+        let client_random = client_hello.random().encode();
+        let server_random = Random::new();
+        match self.find_session(&client_hello) {
+            Some((session, suite)) => {
+                self.resumed = true;
+                // echo the session_id to tell the client the session is resumed
+                let server_hello = ServerHelloPayload::new(
+                    server_random,
+                    client_hello.session_id().clone(),
+                    suite.suite,
+                    extensions,
+                );
+                self.common.send_handshake(server_hello.encode());
+                self.common.set_max_fragment_len(max_fragment_len);
+                self.peer_certificates = session.peer_certificates;
+                let handshake = Box::new(ServerHandshake {
+                    suite,
+                    session_id: server_hello.session_id().clone(),
+                    client_random,
+                    server_random: server_hello.random().encode(),
+                    send_ticket,
+                    extended_master_secret,
+                    client_public_key: None,
+                });
+                let secrets = MasterSecretInfo::resume(
+                    suite,
+                    session.master_secret,
+                    session.extended_master_secret,
+                    handshake.client_random.clone(),
+                    handshake.server_random.clone(),
+                );
+                self.log_master_secret(&secrets);
+                // the server finishes first in an abbreviated handshake
+                self.send_server_finished(&handshake, &secrets);
+                Ok(ServerState::ExpectChangeCipherSpec(handshake, secrets))
+            }
+            None => {
+                self.resumed = false;
+                self.peer_certificates = Vec::new();
+                // Ref: https://tex2e.github.io/rfc-translater/html/rfc6066.html#8--Certificate-Status-Request
+                let ocsp_response = match &certified_key.ocsp {
+                    Some(ocsp) if client_hello.status_request() => {
+                        ocsp.response_at((self.config.time_provider)())
+                    }
+                    _ => None,
+                };
+                if ocsp_response.is_some() {
+                    extensions.push(ServerExtension::CertificateStatusAck);
+                }
+                // without a session cache there is nothing to resume by session_id
+                let session_id = if config.session_cache.is_some() {
+                    SessionId::random()
+                } else {
+                    SessionId::empty()
+                };
+                let server_hello =
+                    ServerHelloPayload::new(server_random, session_id, suite.suite, extensions);
+                self.common.send_handshake(server_hello.encode());
+                self.common.set_max_fragment_len(max_fragment_len);
+                let handshake = Box::new(ServerHandshake {
+                    suite,
+                    session_id: server_hello.session_id().clone(),
+                    client_random,
+                    server_random: server_hello.random().encode(),
+                    send_ticket,
+                    extended_master_secret,
+                    client_public_key: None,
+                });
+                self.send_server_flight(handshake, &client_hello, &certified_key, ocsp_response)
+            }
+        }
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc7301.html#3-2--Protocol-Selection
+    // the first of the server's protocols that the client offered
+    fn choose_alpn_protocol(
+        &self,
+        client_hello: &ClientHelloPayload,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let offered = client_hello.alpn_protocols();
+        if offered.is_empty() || self.config.alpn_protocols.is_empty() {
+            return Ok(None);
+        }
+        match self
+            .config
+            .alpn_protocols
+            .iter()
+            .find(|protocol| offered.contains(protocol))
+        {
+            Some(protocol) => Ok(Some(protocol.clone())),
+            None => Err(Error::NoApplicationProtocol(String::from(
+                "no shared ALPN protocol",
+            ))),
+        }
+    }
+
+    // a session offered in the session ticket, or cached under the offered session_id.
+    // A ticket is only accepted together with a session_id, which the client needs
+    // to recognize the resumption. The session must have been established with the
+    // extended master secret if and only if the client offers it now (RFC 7627).
+    // Returns the session and its cipher suite.
+    fn find_session(
+        &self,
+        client_hello: &ClientHelloPayload,
+    ) -> Option<(ServerSessionValue, &'static CipherSuiteParams)> {
+        if client_hello.session_id().is_empty() {
+            return None;
+        }
+        let from_ticket = match (&self.config.ticketer, client_hello.session_ticket()) {
+            (Some(ticketer), Some(ticket)) if !ticket.is_empty() => ticketer.decrypt(ticket),
+            _ => None,
+        };
+        let session = from_ticket.or_else(|| {
+            self.config
+                .session_cache
+                .as_ref()?
+                .get(client_hello.session_id())
+        })?;
+        if session.extended_master_secret != client_hello.extended_master_secret() {
+            return None;
+        }
+        // the client must offer the cipher suite of the session again
+        let suite = self
+            .config
+            .cipher_suites
+            .iter()
+            .copied()
+            .find(|suite| session.cipher_suite == suite.get_encoding())?;
+        if !client_hello.cipher_suites().contains(&suite) {
+            return None;
+        }
+        Some((session, suite.params()?))
+    }
+
+    // Certificate, CertificateStatus, ServerKeyExchange, CertificateRequest and ServerHelloDone
+    // of a full handshake
+    fn send_server_flight(
+        &mut self,
+        handshake: Box<ServerHandshake>,
+        client_hello: &ClientHelloPayload,
+        certified_key: &CertifiedKey,
+        ocsp_response: Option<Vec<u8>>,
+    ) -> Result<ServerState, Error> {
+        let config = Arc::clone(&self.config);
+        let client_groups = client_hello.named_groups();
+        let Some(key_exchange) = config
+            .named_groups
+            .iter()
+            .copied()
+            .find(|group| client_groups.contains(group))
+            .and_then(KeyExchange::generate)
+        else {
+            return Err(Error::HandshakeFailure(String::from(
+                "no shared named group",
+            )));
+        };
+        let client_schemes: Vec<SignatureScheme> = client_hello
+            .signature_algorithms()
+            .into_iter()
+            .filter(|scheme| config.signature_schemes.contains(scheme))
+            .collect();
+        let Some(scheme) = certified_key.key.choose_scheme(&client_schemes) else {
+            return Err(Error::HandshakeFailure(String::from(
+                "no shared signature algorithm",
+            )));
+        };
+
+        self.common
+            .send_handshake(Certificate::new(&certified_key.cert_chain).encode());
+        if let Some(ocsp_response) = ocsp_response {
+            self.common
+                .send_handshake(CertificateStatus::new(ocsp_response).encode());
+        }
+
+        let message = signed_params(
+            &handshake.client_random,
+            &handshake.server_random,
+            key_exchange.named_curve(),
+            key_exchange.public_key(),
+        );
+        let Some(signature) = certified_key.key.sign(scheme, &message) else {
+            return Err(Error::Internal(String::from(
+                "failed to sign ServerKeyExchange",
+            )));
+        };
+        let server_key_exchange = ServerKeyExchange::new(
+            key_exchange.named_curve(),
+            key_exchange.public_key().to_vec(),
+            scheme,
+            signature,
+        );
+        self.common.send_handshake(server_key_exchange.encode());
+        // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-4--Certificate-Request
+        let state = match &config.client_auth {
+            ClientAuth::None => ServerState::ExpectClientKeyExchange(handshake, key_exchange),
+            ClientAuth::Optional(roots) | ClientAuth::Required(roots) => {
+                let request = CertificateRequest::new(
+                    vec![RSA_SIGN, ECDSA_SIGN],
+                    config.signature_schemes.clone(),
+                    roots.subjects(),
+                );
+                self.common.send_handshake(request.encode());
+                ServerState::ExpectCertificate(handshake, key_exchange)
+            }
+        };
+        self.common.send_handshake(ServerHelloDone::new().encode());
+        Ok(state)
+    }
+
+    // the client's Certificate. Returns the public key of its leaf, None if it sent no certificate.
+    fn read_client_certificate(&mut self, body: Vec<u8>) -> Result<Option<Vec<u8>>, Error> {
+        let certificate = Certificate::read(&body)?;
+        let chain = certificate.certificates();
+        let roots = match &self.config.client_auth {
+            ClientAuth::Optional(roots) | ClientAuth::Required(roots) => roots,
+            ClientAuth::None => {
+                return Err(Error::UnexpectedMessage(String::from(
+                    "no certificate was requested",
+                )))
+            }
+        };
+        if chain.is_empty() {
+            if let ClientAuth::Required(_) = self.config.client_auth {
+                return Err(Error::HandshakeFailure(String::from(
+                    "client did not send a certificate",
+                )));
+            }
+            return Ok(None);
+        }
+        certificate.verify()?;
+        verify_chain(&chain, roots, (self.config.time_provider)())?;
+        let Some(public_key) = certificate.public_key() else {
+            return Err(Error::Certificate(CertificateError::Unsupported(
+                String::from("cannot read the certificate public key"),
+            )));
+        };
+        self.peer_certificates = chain;
+        Ok(Some(public_key))
+    }
+
+    fn handle_client_key_exchange(
+        &mut self,
+        handshake: Box<ServerHandshake>,
+        key_exchange: KeyExchange,
+        body: Vec<u8>,
+    ) -> Result<ServerState, Error> {
+        let client_key_exchange = ClientKeyExchange::read(&body)?;
+        let Some(pre_master_secret) = key_exchange.complete(client_key_exchange.pubkey()) else {
+            return Err(Error::IllegalParameter(String::from(
+                "invalid client public key",
+            )));
+        };
+        // the session hash includes ClientKeyExchange
+        let secrets = if handshake.extended_master_secret {
+            MasterSecretInfo::extended(
+                handshake.suite,
+                pre_master_secret,
+                self.common.handshake_messages(),
+                handshake.client_random.clone(),
+                handshake.server_random.clone(),
+            )
+        } else {
+            MasterSecretInfo::new(
+                handshake.suite,
+                pre_master_secret,
+                handshake.client_random.clone(),
+                handshake.server_random.clone(),
+            )
+        };
+        self.log_master_secret(&secrets);
+        if handshake.client_public_key.is_some() {
+            return Ok(ServerState::ExpectCertificateVerify(handshake, secrets));
+        }
+        Ok(ServerState::ExpectChangeCipherSpec(handshake, secrets))
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-8--Certificate-Verify
+    // the signature covers the handshake messages before CertificateVerify
+    fn verify_client_signature(&mut self, public_key: &[u8], body: Vec<u8>) -> Result<(), Error> {
+        let certificate_verify = CertificateVerify::read(&body)?;
+        if !self
+            .config
+            .signature_schemes
+            .contains(&certificate_verify.algorithm())
+        {
+            return Err(Error::IllegalParameter(String::from(
+                "client used a signature scheme that was not requested",
+            )));
+        }
+        if !verify_signature(
+            certificate_verify.algorithm(),
+            public_key,
+            self.common.handshake_messages_before_last(),
+            certificate_verify.signature(),
+        ) {
+            return Err(Error::Crypto(String::from(
+                "invalid CertificateVerify signature",
+            )));
+        }
+        Ok(())
+    }
+
+    // The client's Finished completes an abbreviated handshake. In a full handshake
+    // the server's NewSessionTicket, ChangeCipherSpec and Finished follow.
+    fn handle_finished(
+        &mut self,
+        handshake: Box<ServerHandshake>,
+        secrets: MasterSecretInfo,
+        body: Vec<u8>,
+    ) -> Result<ServerState, Error> {
+        self.client_verify_data = self
+            .common
+            .check_finished(&secrets, b"client finished", body)?;
+        if !self.resumed {
+            self.send_server_finished(&handshake, &secrets);
+            if let Some(session_cache) = &self.config.session_cache {
+                session_cache.put(
+                    &handshake.session_id,
+                    session_value(&secrets, &self.peer_certificates),
+                );
+            }
+        }
+        self.common.handshake_done();
+        Ok(ServerState::Connected)
+    }
+
+    fn log_master_secret(&self, secrets: &MasterSecretInfo) {
+        self.config.key_log.log(
+            "CLIENT_RANDOM",
+            &secrets.client_random,
+            &secrets.master_secret,
+        );
+    }
+
+    // NewSessionTicket, ChangeCipherSpec and Finished
+    fn send_server_finished(&mut self, handshake: &ServerHandshake, secrets: &MasterSecretInfo) {
+        match &self.config.ticketer {
+            Some(ticketer) if handshake.send_ticket => {
+                let message =
+                    ticketer.new_session_ticket(&session_value(secrets, &self.peer_certificates));
+                self.common.send_handshake(message);
+            }
+            _ => {}
+        }
+        let (_, server_cipher) = key_expansion(secrets);
+        self.common.send_change_cipher_spec(server_cipher);
+        self.server_verify_data = self.common.send_finished(secrets, b"server finished");
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc5746.html#4-4--Legacy-Servers
+    // A ClientHello after the handshake asks for renegotiation, which is refused unless secure
+    // renegotiation is enabled and the client supports it.
+    fn refuse_renegotiation(&mut self) {
+        self.common
+            .send_alert(AlertLevel::Warning, AlertDescription::NoRenegotiation);
+    }
+}
+
+impl Connection for ServerConnection {
+    fn common(&self) -> &ConnectionCommon {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut ConnectionCommon {
+        &mut self.common
+    }
+
+    fn process_new_packets(&mut self) -> Result<(), Error> {
+        self.common.check_failed()?;
+        let result = self.process_messages();
+        self.common.abort_on_error(result)
+    }
+}
+
+fn session_value(secrets: &MasterSecretInfo, peer_certificates: &[Vec<u8>]) -> ServerSessionValue {
+    ServerSessionValue {
+        cipher_suite: secrets.suite().suite.get_encoding(),
+        master_secret: secrets.master_secret.clone(),
+        creation_time: unix_time(),
+        extended_master_secret: secrets.is_extended(),
+        peer_certificates: peer_certificates.to_vec(),
+    }
+}
@@ -1,9 +1,10 @@
+use crate::cipher_suite::CipherSuite;
+use crate::codec::{decode_all, encode_vec, read_vec, Codec, LengthPrefix, Reader};
 use crate::enums::HandshakeType;
-use crate::handshake::{Random, SessionId};
+use crate::error::Error;
+use crate::extensions::ServerExtension;
+use crate::handshake::{HandshakePayload, Random, SessionId};
 use crate::protocol_version::ProtocolVersion;
-use byteorder::{BigEndian, ByteOrder};
-use chrono::{TimeZone, Utc};
-use rustls::internal::msgs::handshake::ServerExtension;
 
 // https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-1-3--Server-Hello
 // struct {
@@ -21,48 +22,156 @@ use rustls::internal::msgs::handshake::ServerExtension;
 // } ServerHello;
 #[derive(Debug)]
 pub struct ServerHelloPayload {
-    handshake_type: HandshakeType,
-    length: u32,
     protocol_version: ProtocolVersion,
     random: Random,
     session_id: SessionId,
-    cipher_suite: Vec<u8>,
+    cipher_suite: CipherSuite,
     compression_method: Vec<u8>,
     extensions: Vec<ServerExtension>,
 }
 
 impl ServerHelloPayload {
-    pub fn read(buf: Vec<u8>, len: u32) -> Self {
-        let handshake_type = HandshakeType::ServerHello;
-        let length = len;
-        let protocol_version = ProtocolVersion {
-            major: buf[0],
-            minor: buf[1],
-        };
-        let gmt_unix_time = BigEndian::read_u32(&buf[2..6]) as i64;
-        let random = Random {
-            gmt_unix_time: Utc.timestamp_opt(gmt_unix_time, 0).unwrap(),
-            random_bytes: buf[6..34].to_vec(),
-        };
-        let session_id_len = buf[34] as usize;
-        let session_id_end = 34 + session_id_len;
-        let session_id = SessionId {
-            len: session_id_len,
-            data: buf[34..session_id_end].try_into().unwrap(),
+    // TLS 1.2 ServerHello without compression
+    pub fn new(
+        random: Random,
+        session_id: SessionId,
+        cipher_suite: CipherSuite,
+        extensions: Vec<ServerExtension>,
+    ) -> Self {
+        ServerHelloPayload {
+            protocol_version: ProtocolVersion {
+                major: 0x03,
+                minor: 0x03,
+            },
+            random,
+            session_id,
+            cipher_suite,
+            compression_method: vec![0x00],
+            extensions,
+        }
+    }
+
+    pub fn read(buf: &[u8]) -> Result<Self, Error> {
+        decode_all(buf).ok_or(Error::Decode("ServerHello"))
+    }
+
+    pub fn protocol_version(&self) -> &ProtocolVersion {
+        &self.protocol_version
+    }
+
+    pub fn compression_method(&self) -> &[u8] {
+        &self.compression_method
+    }
+
+    pub fn random(&self) -> &Random {
+        &self.random
+    }
+
+    pub fn session_id(&self) -> &SessionId {
+        &self.session_id
+    }
+
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
+    pub fn extensions(&self) -> &[ServerExtension] {
+        &self.extensions
+    }
+
+    // encode ServerHello handshake message (with header)
+    pub fn encode(&self) -> Vec<u8> {
+        HandshakePayload::encode_message(HandshakeType::ServerHello, &self.get_encoding())
+    }
+}
+
+impl Codec for ServerHelloPayload {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        self.protocol_version.encode_into(bytes);
+        self.random.encode_into(bytes);
+        self.session_id.encode_into(bytes);
+        self.cipher_suite.encode_into(bytes);
+        bytes.extend_from_slice(&self.compression_method);
+        // the extensions block is omitted when there are no extensions
+        if !self.extensions.is_empty() {
+            encode_vec(bytes, LengthPrefix::U16, &self.extensions);
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let protocol_version = ProtocolVersion::decode(r)?;
+        let random = Random::decode(r)?;
+        let session_id = SessionId::decode(r)?;
+        let cipher_suite = CipherSuite::decode(r)?;
+        let compression_method = r.take(1)?.to_vec();
+        // extensions are present only if there are bytes left after compression_method
+        let extensions = if r.any_left() {
+            read_vec(r, LengthPrefix::U16)?
+        } else {
+            vec![]
         };
-        let cipher_suite = buf[session_id_end..session_id_end + 2].to_vec();
-        let compression_method = buf[session_id_end + 2..session_id_end + 3].to_vec();
-        let extensions = vec![];
 
-        ServerHelloPayload {
-            handshake_type,
-            length,
+        Some(ServerHelloPayload {
             protocol_version,
             random,
             session_id,
             cipher_suite,
             compression_method,
             extensions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_session_id() {
+        let mut buf = vec![0x03, 0x03];
+        buf.extend([0x11; 32]);
+        buf.push(32);
+        buf.extend([0xab; 32]);
+        buf.extend([0xc0, 0x30, 0x00]);
+        // extensions: renegotiation_info (empty)
+        buf.extend([0x00, 0x05, 0xff, 0x01, 0x00, 0x01, 0x00]);
+
+        let server_hello = ServerHelloPayload::read(&buf).unwrap();
+        assert_eq!(server_hello.session_id().as_slice(), &[0xab; 32]);
+        assert_eq!(
+            server_hello.cipher_suite(),
+            CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384
+        );
+        assert_eq!(server_hello.extensions().len(), 1);
+
+        for len in 0..buf.len() {
+            // a ServerHello may end right after compression_method
+            if len != 35 + 32 + 3 {
+                assert!(ServerHelloPayload::read(&buf[..len]).is_err());
+            }
         }
+        let mut oversized = buf.clone();
+        oversized[34] = 33;
+        assert!(ServerHelloPayload::read(&oversized).is_err());
+    }
+
+    #[test]
+    fn encode_round_trip() {
+        let server_hello = ServerHelloPayload::new(
+            Random::new(),
+            SessionId::random(),
+            CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+            vec![ServerExtension::SessionTicketAck],
+        );
+        let message = server_hello.encode();
+        let body = message[4..].to_vec();
+        let decoded = ServerHelloPayload::read(&body).unwrap();
+        assert_eq!(decoded.session_id(), server_hello.session_id());
+        assert_eq!(decoded.random().encode(), server_hello.random().encode());
+        assert_eq!(
+            decoded.cipher_suite(),
+            CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384
+        );
+        assert_eq!(decoded.extensions().len(), 1);
     }
 }
@@ -1,21 +1,33 @@
+use crate::codec::{decode_all, Codec, Reader};
 use crate::enums::HandshakeType;
+use crate::error::Error;
+use crate::handshake::HandshakePayload;
 
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-5--Server-Hello-Done
 // struct { } ServerHelloDone;
 #[derive(Debug)]
-pub struct ServerHelloDone {
-    handshake_type: HandshakeType,
-    length: u32,
-}
+pub struct ServerHelloDone;
 
 impl ServerHelloDone {
-    pub fn read(buf: Vec<u8>, len: u32) -> Self {
-        let handshake_type = HandshakeType::ServerHelloDone;
-        let length = len;
+    pub fn new() -> Self {
+        ServerHelloDone
+    }
+
+    // the body must be empty
+    pub fn read(buf: &[u8]) -> Result<Self, Error> {
+        decode_all(buf).ok_or(Error::Decode("ServerHelloDone"))
+    }
+
+    // encode ServerHelloDone handshake message (with header)
+    pub fn encode(&self) -> Vec<u8> {
+        HandshakePayload::encode_message(HandshakeType::ServerHelloDone, &self.get_encoding())
+    }
+}
+
+impl Codec for ServerHelloDone {
+    fn encode_into(&self, _bytes: &mut Vec<u8>) {}
 
-        ServerHelloDone {
-            handshake_type,
-            length,
-        }
+    fn decode(_r: &mut Reader) -> Option<Self> {
+        Some(ServerHelloDone::new())
     }
 }
@@ -30,74 +30,220 @@
 //     };
 // } ServerKeyExchange;
 
-use crate::enums::HandshakeType;
-use crate::handshake::bytes_to_u32_be;
-use x25519_dalek::{EphemeralSecret, PublicKey};
+use crate::codec::{decode_all, encode_opaque, read_opaque, Codec, LengthPrefix, Reader};
+use crate::enums::{ECCurveType, HandshakeType, NamedGroup, SignatureScheme};
+use crate::error::Error;
+use crate::handshake::HandshakePayload;
+use crate::key_exchange::KeyExchange;
+use crate::sign::verify_signature;
 
 #[derive(Debug)]
 pub struct ServerKeyExchange {
-    handshake_type: HandshakeType,
-    length: u32,
     ecdh_server_params: ECDiffieHellmanParam,
 }
 
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc8422.html#5-4--Server-Key-Exchange
+// struct {
+//     ECCurveType    curve_type;
+//     select (curve_type) {
+//         case named_curve:
+//             NamedCurve namedcurve;
+//     };
+// } ECParameters;
+//
+// opaque point <1..2^8-1>;
 #[derive(Debug)]
 struct ECDiffieHellmanParam {
-    curve_type: Vec<u8>,
-    named_curve: Vec<u8>,
-    pubkey_len: Vec<u8>,
+    curve_type: ECCurveType,
+    named_curve: NamedGroup,
     pubkey: Vec<u8>,
-    signature_algorithm: Vec<u8>,
-    signature_len: Vec<u8>,
+    signature_algorithm: SignatureScheme,
     signature: Vec<u8>,
 }
 
 impl ServerKeyExchange {
-    pub fn read(buffer: Vec<u8>, len: u32) -> ServerKeyExchange {
-        let curve_type = buffer[0..1].to_vec();
-        let named_curve = buffer[1..3].to_vec();
-        let pubkey_len = buffer[3..4].to_vec();
-        let pubkey_offset = 4 + bytes_to_u32_be(&pubkey_len) as usize;
-        let pubkey = buffer[4..pubkey_offset].to_vec();
-        let signature_algorithm = buffer[pubkey_offset..pubkey_offset + 2].to_vec();
-        let signature_len = buffer[pubkey_offset + 2..pubkey_offset + 4].to_vec();
-        let signature = buffer[pubkey_offset + 4..].to_vec();
-
+    // ECDHE parameters signed with signature_algorithm, see signed_params
+    pub fn new(
+        named_curve: NamedGroup,
+        pubkey: Vec<u8>,
+        signature_algorithm: SignatureScheme,
+        signature: Vec<u8>,
+    ) -> ServerKeyExchange {
         let ecdh_server_params = ECDiffieHellmanParam {
-            curve_type,
+            curve_type: ECCurveType::named_curve,
             named_curve,
-            pubkey_len,
             pubkey,
             signature_algorithm,
-            signature_len,
             signature,
         };
 
-        ServerKeyExchange {
-            handshake_type: HandshakeType::ServerKeyExchange,
-            length: len,
-            ecdh_server_params,
-        }
+        ServerKeyExchange { ecdh_server_params }
+    }
+
+    // only named_curve parameters are supported (RFC 8422 5.4)
+    pub fn read(buf: &[u8]) -> Result<ServerKeyExchange, Error> {
+        decode_all(buf).ok_or(Error::Decode("ServerKeyExchange"))
+    }
+
+    pub fn named_curve(&self) -> NamedGroup {
+        self.ecdh_server_params.named_curve
+    }
+
+    pub fn signature_algorithm(&self) -> SignatureScheme {
+        self.ecdh_server_params.signature_algorithm
+    }
+
+    // check the signature over signed_params.
+    // public_key is the subjectPublicKey of the server certificate.
+    pub fn verify(&self, client_random: &[u8], server_random: &[u8], public_key: &[u8]) -> bool {
+        let params = &self.ecdh_server_params;
+        let message = signed_params(
+            client_random,
+            server_random,
+            params.named_curve,
+            &params.pubkey,
+        );
+
+        verify_signature(
+            params.signature_algorithm,
+            public_key,
+            &message,
+            &params.signature,
+        )
     }
 
     // generate ecdh sharedkey
+    // returns (client public key, shared key). The shared key is the pre_master_secret.
+    // None if the curve is not supported or the server's public key is invalid.
     // ref: https://zenn.dev/satoken/articles/golang-tls1_2_2
-    pub fn generate_shared_key(&self) {
-        // クライアントの秘密鍵を作る
-        let client_secret_key = EphemeralSecret::random();
-        // クライアントの公開鍵を作る
-        let client_public_key = PublicKey::from(&client_secret_key);
-        // サーバーの公開鍵を取得
-        println!("ecdh_server_params: {:?}", self.ecdh_server_params);
-        println!("pubkey size: {:?}", self.ecdh_server_params.pubkey.len());
-        println!("client_public_key size: {:?}", client_public_key.as_bytes().len());
-        let server_public_key_bytes: [u8; 32] = self.ecdh_server_params.pubkey[..].try_into().unwrap();
-        let server_public_key = PublicKey::from(server_public_key_bytes);
-
-        // 鍵交換
-        let client_shared_key = client_secret_key.diffie_hellman(&server_public_key);
-
-        println!("client_public_key: {:?}", client_public_key);
-        println!("client_shared_key: {:?}", client_shared_key.as_bytes());
+    pub fn generate_shared_key(&self) -> Option<(Vec<u8>, Vec<u8>)> {
+        let key_exchange = KeyExchange::generate(self.ecdh_server_params.named_curve)?;
+        let client_public_key = key_exchange.public_key().to_vec();
+        let shared_key = key_exchange.complete(&self.ecdh_server_params.pubkey)?;
+        Some((client_public_key, shared_key))
+    }
+
+    // encode ServerKeyExchange handshake message (with header)
+    pub fn encode(&self) -> Vec<u8> {
+        HandshakePayload::encode_message(HandshakeType::ServerKeyExchange, &self.get_encoding())
+    }
+}
+
+// only named_curve parameters are supported (RFC 8422 5.4)
+impl Codec for ServerKeyExchange {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        self.ecdh_server_params.encode_into(bytes);
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        Some(ServerKeyExchange {
+            ecdh_server_params: ECDiffieHellmanParam::decode(r)?,
+        })
+    }
+}
+
+impl Codec for ECDiffieHellmanParam {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        self.curve_type.encode_into(bytes);
+        self.named_curve.encode_into(bytes);
+        encode_opaque(bytes, LengthPrefix::U8, &self.pubkey);
+        self.signature_algorithm.encode_into(bytes);
+        encode_opaque(bytes, LengthPrefix::U16, &self.signature);
+    }
+
+    // explicit curves have a different layout and are not supported
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let curve_type = ECCurveType::decode(r)?;
+        if curve_type != ECCurveType::named_curve {
+            return None;
+        }
+        Some(ECDiffieHellmanParam {
+            curve_type,
+            named_curve: NamedGroup::decode(r)?,
+            pubkey: read_opaque(r, LengthPrefix::U8)?.to_vec(),
+            signature_algorithm: SignatureScheme::decode(r)?,
+            signature: read_opaque(r, LengthPrefix::U16)?.to_vec(),
+        })
+    }
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc8422.html#5-4--Server-Key-Exchange
+// digitally-signed struct {
+//     opaque client_random[32];
+//     opaque server_random[32];
+//     ServerECDHParams params;
+// } signed_params;
+//
+// struct {
+//     ECParameters curve_params;
+//     ECPoint public;
+// } ServerECDHParams;
+pub fn signed_params(
+    client_random: &[u8],
+    server_random: &[u8],
+    named_curve: NamedGroup,
+    pubkey: &[u8],
+) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(client_random);
+    message.extend_from_slice(server_random);
+    ECCurveType::named_curve.encode_into(&mut message);
+    named_curve.encode_into(&mut message);
+    encode_opaque(&mut message, LengthPrefix::U8, pubkey);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sign::{EcdsaSigningKey, Ed25519SigningKey, SigningKey};
+    use ring::rand::SystemRandom;
+    use ring::signature::{self, EcdsaKeyPair, Ed25519KeyPair, KeyPair};
+
+    fn sign_and_verify(key: &dyn SigningKey, public_key: &[u8]) {
+        let client_random = [0x01; 32];
+        let server_random = [0x02; 32];
+        let pubkey = vec![0x03; 32];
+        let scheme = key.schemes()[0];
+        let message = signed_params(&client_random, &server_random, NamedGroup::x25519, &pubkey);
+        let signature = key.sign(scheme, &message).unwrap();
+
+        let encoded =
+            ServerKeyExchange::new(NamedGroup::x25519, pubkey, scheme, signature).encode();
+        let body = encoded[4..].to_vec();
+        let server_key_exchange = ServerKeyExchange::read(&body).unwrap();
+        assert!(server_key_exchange.verify(&client_random, &server_random, public_key));
+        assert!(!server_key_exchange.verify(&server_random, &client_random, public_key));
+        for len in 0..body.len() {
+            assert!(ServerKeyExchange::read(&body[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn ecdsa_and_ed25519_signatures() {
+        let rng = SystemRandom::new();
+        for algorithm in [
+            &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+            &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+        ] {
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(algorithm, &rng).unwrap();
+            let public_key = EcdsaKeyPair::from_pkcs8(algorithm, pkcs8.as_ref())
+                .unwrap()
+                .public_key()
+                .as_ref()
+                .to_vec();
+            sign_and_verify(&EcdsaSigningKey::new(pkcs8.as_ref()).unwrap(), &public_key);
+        }
+
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let public_key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+            .unwrap()
+            .public_key()
+            .as_ref()
+            .to_vec();
+        sign_and_verify(
+            &Ed25519SigningKey::new(pkcs8.as_ref()).unwrap(),
+            &public_key,
+        );
     }
 }
@@ -0,0 +1,225 @@
+use crate::codec::{
+    decode_all, encode_opaque, encode_opaque_vec, read_opaque, read_opaque_vec, Codec,
+    LengthPrefix, Reader,
+};
+use crate::handshake::SessionId;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-1-2--Client-Hello
+// The state a client needs to resume a session with an abbreviated handshake.
+#[derive(Debug, Clone)]
+pub struct ClientSessionValue {
+    pub session_id: SessionId,
+    pub master_secret: Vec<u8>,
+    pub cipher_suite: Vec<u8>,
+    pub peer_certificates: Vec<Vec<u8>>,
+    // opaque session ticket issued by the server (RFC 5077), empty if none
+    pub ticket: Vec<u8>,
+    // the master secret was derived with the extended master secret (RFC 7627)
+    pub extended_master_secret: bool,
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#F-1-4--Resuming-Sessions
+// upper limit of a client session's lifetime
+const MAX_CLIENT_SESSION_LIFETIME: u64 = 24 * 60 * 60;
+
+// In-memory session cache keyed by server name.
+// It is shared between connections, so wrap it in an Arc.
+#[derive(Debug, Default)]
+pub struct ClientSessionCache {
+    // (expiry in seconds since UNIX epoch, session)
+    sessions: Mutex<HashMap<String, (u64, ClientSessionValue)>>,
+}
+
+impl ClientSessionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // None for unknown or expired sessions
+    pub fn get(&self, server_name: &str) -> Option<ClientSessionValue> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let (expiry, value) = sessions.get(server_name)?.clone();
+        if expiry < unix_time() {
+            sessions.remove(server_name);
+            return None;
+        }
+        Some(value)
+    }
+
+    // lifetime_hint is the ticket_lifetime_hint of NewSessionTicket in seconds, 0 if there is
+    // none. The session expires after it, and after MAX_CLIENT_SESSION_LIFETIME at the latest.
+    pub fn put(&self, server_name: &str, value: ClientSessionValue, lifetime_hint: u32) {
+        let lifetime = match u64::from(lifetime_hint) {
+            0 => MAX_CLIENT_SESSION_LIFETIME,
+            hint => hint.min(MAX_CLIENT_SESSION_LIFETIME),
+        };
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(server_name.to_string(), (unix_time() + lifetime, value));
+    }
+
+    pub fn remove(&self, server_name: &str) {
+        self.sessions.lock().unwrap().remove(server_name);
+    }
+}
+
+// The state a server needs to resume a session. It is stored in the session cache
+// or sent to the client encrypted inside a session ticket (RFC 5077).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerSessionValue {
+    pub cipher_suite: Vec<u8>,
+    pub master_secret: Vec<u8>,
+    // seconds since UNIX epoch
+    pub creation_time: u64,
+    // the master secret was derived with the extended master secret (RFC 7627)
+    pub extended_master_secret: bool,
+    // DER encoded certificates of an authenticated client, empty if none
+    pub peer_certificates: Vec<Vec<u8>>,
+}
+
+impl ServerSessionValue {
+    pub fn encode(&self) -> Vec<u8> {
+        self.get_encoding()
+    }
+
+    pub fn read(buf: &[u8]) -> Option<Self> {
+        decode_all(buf)
+    }
+}
+
+// cipher_suite<1..2^8-1> + master_secret<1..2^8-1> + uint64 creation_time +
+// uint8 extended_master_secret + ASN.1Cert peer_certificates<0..2^24-1>
+impl Codec for ServerSessionValue {
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        encode_opaque(bytes, LengthPrefix::U8, &self.cipher_suite);
+        encode_opaque(bytes, LengthPrefix::U8, &self.master_secret);
+        self.creation_time.encode_into(bytes);
+        (self.extended_master_secret as u8).encode_into(bytes);
+        encode_opaque_vec(
+            bytes,
+            LengthPrefix::U24,
+            LengthPrefix::U24,
+            &self.peer_certificates,
+        );
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let cipher_suite = read_opaque(r, LengthPrefix::U8)?.to_vec();
+        let master_secret = read_opaque(r, LengthPrefix::U8)?.to_vec();
+        let creation_time = u64::decode(r)?;
+        let extended_master_secret = match u8::decode(r)? {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        let peer_certificates = read_opaque_vec(r, LengthPrefix::U24, LengthPrefix::U24)?;
+        Some(ServerSessionValue {
+            cipher_suite,
+            master_secret,
+            creation_time,
+            extended_master_secret,
+            peer_certificates,
+        })
+    }
+}
+
+// In-memory session cache keyed by session_id, used by servers for session-ID resumption.
+// When the cache is full an arbitrary session is evicted.
+#[derive(Debug)]
+pub struct ServerSessionCache {
+    capacity: usize,
+    // seconds a session can be resumed for
+    lifetime: u64,
+    sessions: Mutex<HashMap<Vec<u8>, ServerSessionValue>>,
+}
+
+impl ServerSessionCache {
+    pub fn new(capacity: usize, lifetime: u64) -> Self {
+        ServerSessionCache {
+            capacity,
+            lifetime,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // None for unknown or expired sessions
+    pub fn get(&self, session_id: &SessionId) -> Option<ServerSessionValue> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let value = sessions.get(session_id.as_slice())?.clone();
+        // a creation_time that overflows is bogus, treat it as expired
+        let expiry = value.creation_time.checked_add(self.lifetime);
+        if expiry.is_none_or(|expiry| expiry < unix_time()) {
+            sessions.remove(session_id.as_slice());
+            return None;
+        }
+        Some(value)
+    }
+
+    pub fn put(&self, session_id: &SessionId, value: ServerSessionValue) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.len() >= self.capacity && !sessions.contains_key(session_id.as_slice()) {
+            let Some(evicted) = sessions.keys().next().cloned() else {
+                return;
+            };
+            sessions.remove(&evicted);
+        }
+        sessions.insert(session_id.as_slice().to_vec(), value);
+    }
+
+    pub fn remove(&self, session_id: &SessionId) {
+        self.sessions.lock().unwrap().remove(session_id.as_slice());
+    }
+}
+
+// seconds since UNIX epoch
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> ClientSessionValue {
+        ClientSessionValue {
+            session_id: SessionId::empty(),
+            master_secret: vec![0; 48],
+            cipher_suite: vec![0xc0, 0x2f],
+            peer_certificates: Vec::new(),
+            ticket: vec![1, 2, 3],
+            extended_master_secret: true,
+        }
+    }
+
+    #[test]
+    fn client_sessions_expire() {
+        let cache = ClientSessionCache::new();
+        cache.put("example.com", session(), 60);
+        cache.put("example.net", session(), u32::MAX);
+        assert!(cache.get("example.com").is_some());
+        {
+            let sessions = cache.sessions.lock().unwrap();
+            let now = unix_time();
+            assert!(sessions["example.com"].0 <= now + 60);
+            // the hint cannot extend the lifetime beyond the upper limit
+            assert!(sessions["example.net"].0 <= now + MAX_CLIENT_SESSION_LIFETIME);
+        }
+
+        cache
+            .sessions
+            .lock()
+            .unwrap()
+            .get_mut("example.com")
+            .unwrap()
+            .0 = unix_time() - 1;
+        assert!(cache.get("example.com").is_none());
+        assert!(!cache.sessions.lock().unwrap().contains_key("example.com"));
+    }
+}
@@ -0,0 +1,296 @@
+use crate::enums::SignatureScheme;
+use crate::ocsp::OcspResponseFile;
+use ring::rand::SystemRandom;
+use ring::signature::{self, EcdsaKeyPair, Ed25519KeyPair, RsaKeyPair};
+use std::sync::Arc;
+
+// SignatureScheme values this crate can verify, most preferred first. They are offered in
+// ClientHello's signature_algorithms and CertificateRequest.
+// Ref: https://github.com/rustls/rustls/blob/main/rustls/src/verify.rs#L420
+pub const SUPPORTED_SIGNATURE_SCHEMES: [SignatureScheme; 9] = [
+    SignatureScheme::ecdsa_secp384r1_sha384,
+    SignatureScheme::ecdsa_secp256r1_sha256,
+    SignatureScheme::ed25519,
+    SignatureScheme::rsa_pss_rsae_sha512,
+    SignatureScheme::rsa_pss_rsae_sha384,
+    SignatureScheme::rsa_pss_rsae_sha256,
+    SignatureScheme::rsa_pkcs1_sha512,
+    SignatureScheme::rsa_pkcs1_sha384,
+    SignatureScheme::rsa_pkcs1_sha256,
+];
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#4-7--Cryptographic-Attributes
+// check a digitally-signed struct. public_key is the subjectPublicKey of the signer's certificate.
+pub fn verify_signature(
+    scheme: SignatureScheme,
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> bool {
+    verification_algorithms(scheme)
+        .into_iter()
+        .any(|algorithm| {
+            signature::UnparsedPublicKey::new(algorithm, public_key)
+                .verify(message, signature)
+                .is_ok()
+        })
+}
+
+// In TLS 1.2 an ECDSA value only names the hash, {sha256, ecdsa} may be signed with a P-384
+// key, so each hash is tried with both curves.
+fn verification_algorithms(
+    scheme: SignatureScheme,
+) -> Vec<&'static dyn signature::VerificationAlgorithm> {
+    match scheme {
+        SignatureScheme::rsa_pkcs1_sha256 => vec![&signature::RSA_PKCS1_2048_8192_SHA256],
+        SignatureScheme::rsa_pkcs1_sha384 => vec![&signature::RSA_PKCS1_2048_8192_SHA384],
+        SignatureScheme::rsa_pkcs1_sha512 => vec![&signature::RSA_PKCS1_2048_8192_SHA512],
+        SignatureScheme::rsa_pss_rsae_sha256 => vec![&signature::RSA_PSS_2048_8192_SHA256],
+        SignatureScheme::rsa_pss_rsae_sha384 => vec![&signature::RSA_PSS_2048_8192_SHA384],
+        SignatureScheme::rsa_pss_rsae_sha512 => vec![&signature::RSA_PSS_2048_8192_SHA512],
+        SignatureScheme::ecdsa_secp256r1_sha256 => vec![
+            &signature::ECDSA_P256_SHA256_ASN1,
+            &signature::ECDSA_P384_SHA256_ASN1,
+        ],
+        SignatureScheme::ecdsa_secp384r1_sha384 => vec![
+            &signature::ECDSA_P384_SHA384_ASN1,
+            &signature::ECDSA_P256_SHA384_ASN1,
+        ],
+        SignatureScheme::ed25519 => vec![&signature::ED25519],
+        _ => vec![],
+    }
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#7-4-4--Certificate-Request
+// enum {
+//     rsa_sign(1), dss_sign(2), rsa_fixed_dh(3), dss_fixed_dh(4),
+//     rsa_ephemeral_dh_RESERVED(5), dss_ephemeral_dh_RESERVED(6),
+//     fortezza_dms_RESERVED(20), ecdsa_sign(64) /* RFC 8422 */, (255)
+// } ClientCertificateType;
+pub const RSA_SIGN: u8 = 1;
+pub const ECDSA_SIGN: u8 = 64;
+
+// A private key that can produce digitally-signed structs.
+pub trait SigningKey: Send + Sync {
+    // SignatureScheme values this key can sign with, most preferred first
+    fn schemes(&self) -> Vec<SignatureScheme>;
+
+    // ClientCertificateType of the certificate that holds this key
+    fn certificate_type(&self) -> u8;
+
+    fn sign(&self, scheme: SignatureScheme, message: &[u8]) -> Option<Vec<u8>>;
+
+    // the first of our schemes that the peer offered
+    fn choose_scheme(&self, offered: &[SignatureScheme]) -> Option<SignatureScheme> {
+        self.schemes()
+            .into_iter()
+            .find(|scheme| offered.contains(scheme))
+    }
+}
+
+pub struct RsaSigningKey {
+    key_pair: RsaKeyPair,
+}
+
+impl RsaSigningKey {
+    // der is either a PKCS#8 PrivateKeyInfo or a PKCS#1 RSAPrivateKey
+    pub fn new(der: &[u8]) -> Option<Self> {
+        let key_pair = RsaKeyPair::from_pkcs8(der)
+            .or_else(|_| RsaKeyPair::from_der(der))
+            .ok()?;
+        Some(RsaSigningKey { key_pair })
+    }
+}
+
+impl SigningKey for RsaSigningKey {
+    fn schemes(&self) -> Vec<SignatureScheme> {
+        vec![
+            SignatureScheme::rsa_pss_rsae_sha512,
+            SignatureScheme::rsa_pss_rsae_sha384,
+            SignatureScheme::rsa_pss_rsae_sha256,
+            SignatureScheme::rsa_pkcs1_sha512,
+            SignatureScheme::rsa_pkcs1_sha384,
+            SignatureScheme::rsa_pkcs1_sha256,
+        ]
+    }
+
+    fn certificate_type(&self) -> u8 {
+        RSA_SIGN
+    }
+
+    fn sign(&self, scheme: SignatureScheme, message: &[u8]) -> Option<Vec<u8>> {
+        let padding: &'static dyn signature::RsaEncoding = match scheme {
+            SignatureScheme::rsa_pkcs1_sha256 => &signature::RSA_PKCS1_SHA256,
+            SignatureScheme::rsa_pkcs1_sha384 => &signature::RSA_PKCS1_SHA384,
+            SignatureScheme::rsa_pkcs1_sha512 => &signature::RSA_PKCS1_SHA512,
+            SignatureScheme::rsa_pss_rsae_sha256 => &signature::RSA_PSS_SHA256,
+            SignatureScheme::rsa_pss_rsae_sha384 => &signature::RSA_PSS_SHA384,
+            SignatureScheme::rsa_pss_rsae_sha512 => &signature::RSA_PSS_SHA512,
+            _ => return None,
+        };
+        let mut signature = vec![0; self.key_pair.public_modulus_len()];
+        self.key_pair
+            .sign(padding, &SystemRandom::new(), message, &mut signature)
+            .ok()?;
+        Some(signature)
+    }
+}
+
+pub struct EcdsaSigningKey {
+    key_pair: EcdsaKeyPair,
+    scheme: SignatureScheme,
+}
+
+impl EcdsaSigningKey {
+    // der is a PKCS#8 PrivateKeyInfo for a P-256 or P-384 key
+    pub fn new(der: &[u8]) -> Option<Self> {
+        if let Ok(key_pair) =
+            EcdsaKeyPair::from_pkcs8(&signature::ECDSA_P256_SHA256_ASN1_SIGNING, der)
+        {
+            return Some(EcdsaSigningKey {
+                key_pair,
+                scheme: SignatureScheme::ecdsa_secp256r1_sha256,
+            });
+        }
+        let key_pair =
+            EcdsaKeyPair::from_pkcs8(&signature::ECDSA_P384_SHA384_ASN1_SIGNING, der).ok()?;
+        Some(EcdsaSigningKey {
+            key_pair,
+            scheme: SignatureScheme::ecdsa_secp384r1_sha384,
+        })
+    }
+
+    // der is a SEC1 ECPrivateKey, as written by `openssl ecparam -genkey`.
+    // ring only reads PKCS#8, so the key is wrapped in a PrivateKeyInfo for each curve
+    // until one matches.
+    pub fn from_sec1(der: &[u8]) -> Option<Self> {
+        [SECP256R1_OID, SECP384R1_OID]
+            .into_iter()
+            .find_map(|curve| Self::new(&sec1_to_pkcs8(curve, der)))
+    }
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5208.html#5--Private-key-information-syntax
+// PrivateKeyInfo ::= SEQUENCE {
+//     version                   Version,
+//     privateKeyAlgorithm       PrivateKeyAlgorithmIdentifier,
+//     privateKey                PrivateKey }
+//
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5480.html#2-1-1--Unrestricted-Algorithm-Identifier-and-Parameters
+// AlgorithmIdentifier ::= SEQUENCE { algorithm id-ecPublicKey, parameters namedCurve }
+const EC_PUBLIC_KEY_OID: &[u8] = &[0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const SECP256R1_OID: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const SECP384R1_OID: &[u8] = &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x22];
+
+fn sec1_to_pkcs8(curve: &[u8], sec1: &[u8]) -> Vec<u8> {
+    let version = [0x02, 0x01, 0x00];
+    let algorithm = der_tlv(0x30, &[EC_PUBLIC_KEY_OID, curve].concat());
+    let private_key = der_tlv(0x04, sec1);
+    der_tlv(0x30, &[&version[..], &algorithm, &private_key].concat())
+}
+
+// DER tag, definite length and contents
+fn der_tlv(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut tlv = vec![tag];
+    let len = contents.len();
+    if len < 0x80 {
+        tlv.push(len as u8);
+    } else {
+        let len_bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        tlv.push(0x80 | len_bytes.len() as u8);
+        tlv.extend(len_bytes);
+    }
+    tlv.extend_from_slice(contents);
+    tlv
+}
+
+impl SigningKey for EcdsaSigningKey {
+    // In TLS 1.2 the curve is not bound to the hash, but ring only pairs P-256 with SHA-256
+    // and P-384 with SHA-384.
+    fn schemes(&self) -> Vec<SignatureScheme> {
+        vec![self.scheme]
+    }
+
+    fn certificate_type(&self) -> u8 {
+        ECDSA_SIGN
+    }
+
+    fn sign(&self, scheme: SignatureScheme, message: &[u8]) -> Option<Vec<u8>> {
+        if scheme != self.scheme {
+            return None;
+        }
+        let signature = self.key_pair.sign(&SystemRandom::new(), message).ok()?;
+        Some(signature.as_ref().to_vec())
+    }
+}
+
+pub struct Ed25519SigningKey {
+    key_pair: Ed25519KeyPair,
+}
+
+impl Ed25519SigningKey {
+    // der is a PKCS#8 v1 or v2 PrivateKeyInfo
+    pub fn new(der: &[u8]) -> Option<Self> {
+        let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der).ok()?;
+        Some(Ed25519SigningKey { key_pair })
+    }
+}
+
+impl SigningKey for Ed25519SigningKey {
+    fn schemes(&self) -> Vec<SignatureScheme> {
+        vec![SignatureScheme::ed25519]
+    }
+
+    // Ref: https://tex2e.github.io/rfc-translater/html/rfc8422.html#5-5--Certificate-Request
+    // Ed25519 certificates are requested with ecdsa_sign.
+    fn certificate_type(&self) -> u8 {
+        ECDSA_SIGN
+    }
+
+    fn sign(&self, scheme: SignatureScheme, message: &[u8]) -> Option<Vec<u8>> {
+        if scheme != SignatureScheme::ed25519 {
+            return None;
+        }
+        Some(self.key_pair.sign(message).as_ref().to_vec())
+    }
+}
+
+// der is a PKCS#8 PrivateKeyInfo holding an RSA, ECDSA P-256/P-384 or Ed25519 key
+pub fn any_pkcs8_key(der: &[u8]) -> Option<Arc<dyn SigningKey>> {
+    if let Some(key) = EcdsaSigningKey::new(der) {
+        return Some(Arc::new(key));
+    }
+    if let Some(key) = Ed25519SigningKey::new(der) {
+        return Some(Arc::new(key));
+    }
+    let key = RsaSigningKey::new(der)?;
+    Some(Arc::new(key))
+}
+
+// A certificate chain (leaf first, DER encoded) and the private key of the leaf.
+#[derive(Clone)]
+pub struct CertifiedKey {
+    pub cert_chain: Vec<Vec<u8>>,
+    pub key: Arc<dyn SigningKey>,
+    // OCSP response stapled for the leaf when the client sends status_request
+    pub ocsp: Option<Arc<OcspResponseFile>>,
+}
+
+impl CertifiedKey {
+    pub fn new(cert_chain: Vec<Vec<u8>>, key: Arc<dyn SigningKey>) -> Self {
+        CertifiedKey {
+            cert_chain,
+            key,
+            ocsp: None,
+        }
+    }
+
+    pub fn with_ocsp(mut self, ocsp: Arc<OcspResponseFile>) -> Self {
+        self.ocsp = Some(ocsp);
+        self
+    }
+}
@@ -0,0 +1,193 @@
+use crate::client::{ClientConfig, ClientConnection};
+use crate::connection::Connection;
+use crate::server::{ServerConfig, ServerConnection};
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+// A connection and its blocking transport, used like the transport itself.
+// The handshake runs on the first read, write or flush.
+//
+// read returns Ok(0) once the peer sent close_notify, which is answered with a close_notify.
+// EOF of the transport before close_notify is reported as an UnexpectedEof error: the peer or an
+// attacker may have truncated the data (RFC 5246 7.2.1).
+pub struct TlsStream<C: Connection, S: Read + Write> {
+    conn: C,
+    sock: S,
+    // close_notify was queued by shutdown or in reply to the peer's
+    closed: bool,
+}
+
+impl<S: Read + Write> TlsStream<ClientConnection, S> {
+    pub fn connect(config: Arc<ClientConfig>, server_name: &str, sock: S) -> Self {
+        Self::new(ClientConnection::new(config, server_name), sock)
+    }
+}
+
+impl<S: Read + Write> TlsStream<ServerConnection, S> {
+    pub fn accept(config: Arc<ServerConfig>, sock: S) -> Self {
+        Self::new(ServerConnection::new(config), sock)
+    }
+}
+
+impl<C: Connection, S: Read + Write> TlsStream<C, S> {
+    pub fn new(conn: C, sock: S) -> Self {
+        TlsStream {
+            conn,
+            sock,
+            closed: false,
+        }
+    }
+
+    // e.g. for alpn_protocol or peer_certificates after the handshake
+    pub fn connection(&self) -> &C {
+        &self.conn
+    }
+
+    pub fn connection_mut(&mut self) -> &mut C {
+        &mut self.conn
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.sock
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sock
+    }
+
+    pub fn into_inner(self) -> (C, S) {
+        (self.conn, self.sock)
+    }
+
+    // run the handshake now instead of on first use
+    pub fn handshake(&mut self) -> io::Result<()> {
+        self.complete_prior_io()
+    }
+
+    // Send close_notify. Nothing can be written afterwards, reading is still possible
+    // until the peer's close_notify.
+    pub fn shutdown(&mut self) -> io::Result<()> {
+        self.complete_prior_io()?;
+        if !self.closed {
+            self.closed = true;
+            self.conn.send_close_notify();
+        }
+        self.conn.complete_io(&mut self.sock)?;
+        Ok(())
+    }
+
+    // finish the handshake and write what is queued
+    fn complete_prior_io(&mut self) -> io::Result<()> {
+        if self.conn.is_handshaking() {
+            self.conn.complete_io(&mut self.sock)?;
+        }
+        if self.conn.wants_write() {
+            self.conn.complete_io(&mut self.sock)?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: Connection, S: Read + Write> Read for TlsStream<C, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.complete_prior_io()?;
+        loop {
+            match self.conn.reader().read(buf) {
+                Ok(0) if !buf.is_empty() => {
+                    // the peer sent close_notify
+                    if !self.closed {
+                        let _ = self.shutdown();
+                    }
+                    return Ok(0);
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    self.conn.complete_io(&mut self.sock)?;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<C: Connection, S: Read + Write> Write for TlsStream<C, S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.closed {
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "close_notify was already sent",
+            ));
+        }
+        self.complete_prior_io()?;
+        let n = self.conn.writer().write(buf)?;
+        // the data is consumed even if the transport fails now, the error shows up on the next call
+        let _ = self.conn.complete_io(&mut self.sock);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.complete_prior_io()?;
+        self.sock.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::load_private_key;
+    use crate::sign::CertifiedKey;
+    use std::net::{Shutdown, TcpListener, TcpStream};
+    use std::thread;
+
+    const LEAF: &[u8] = include_bytes!("../testdata/ct/leaf.der");
+    const LEAF_KEY: &[u8] = include_bytes!("../testdata/ct/leaf.key");
+
+    #[test]
+    fn echo_and_truncation_over_tcp() {
+        let server_config = ServerConfig::builder()
+            .with_single_cert(CertifiedKey::new(
+                vec![LEAF.to_vec()],
+                load_private_key(LEAF_KEY).unwrap(),
+            ))
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let client_config = ClientConfig::builder()
+            .with_no_certificate_verification()
+            .build()
+            .map(Arc::new)
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            // echo one message and close properly
+            let (sock, _) = listener.accept().unwrap();
+            let mut stream = TlsStream::accept(Arc::clone(&server_config), sock);
+            let mut buf = [0; 5];
+            stream.read_exact(&mut buf).unwrap();
+            stream.write_all(&buf).unwrap();
+            stream.shutdown().unwrap();
+            assert_eq!(stream.read(&mut buf).unwrap(), 0);
+
+            // close the transport without close_notify
+            let (sock, _) = listener.accept().unwrap();
+            let mut stream = TlsStream::accept(server_config, sock);
+            stream.handshake().unwrap();
+            stream.get_ref().shutdown(Shutdown::Both).unwrap();
+        });
+
+        let sock = TcpStream::connect(addr).unwrap();
+        let mut stream = TlsStream::connect(Arc::clone(&client_config), "localhost", sock);
+        stream.write_all(b"hello").unwrap();
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).unwrap();
+        assert_eq!(received, b"hello");
+        assert!(stream.write_all(b"late").is_err());
+
+        let sock = TcpStream::connect(addr).unwrap();
+        let mut stream = TlsStream::connect(client_config, "localhost", sock);
+        let error = stream.read_to_end(&mut received).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        server.join().unwrap();
+    }
+}
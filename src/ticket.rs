@@ -0,0 +1,216 @@
+use crate::new_session_ticket::NewSessionTicket;
+use crate::session::{unix_time, ServerSessionValue};
+use rand::RngCore;
+use ring::aead;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5077.html#4--Recommended-Ticket-Construction
+// The RFC recommends AES-128-CBC + HMAC-SHA-256. This crate uses AES-256-GCM instead:
+// struct {
+//     opaque key_name[16];
+//     opaque nonce[12];
+//     opaque encrypted_state<0..2^16-1>; /* ServerSessionValue + GCM tag */
+// } ticket;
+const KEY_NAME_LEN: usize = 16;
+
+#[derive(Clone)]
+pub struct TicketKey {
+    name: [u8; KEY_NAME_LEN],
+    secret: [u8; 32],
+}
+
+impl TicketKey {
+    // Servers that share the same keys can resume each other's tickets.
+    pub fn new(name: [u8; KEY_NAME_LEN], secret: [u8; 32]) -> Self {
+        TicketKey { name, secret }
+    }
+
+    pub fn generate() -> Self {
+        let mut name = [0; KEY_NAME_LEN];
+        let mut secret = [0; 32];
+        rand::thread_rng().fill_bytes(&mut name);
+        rand::thread_rng().fill_bytes(&mut secret);
+        TicketKey { name, secret }
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    fn aead_key(&self) -> aead::LessSafeKey {
+        aead::LessSafeKey::new(aead::UnboundKey::new(&aead::AES_256_GCM, &self.secret).unwrap())
+    }
+}
+
+// Source of ticket keys. Implement this to share keys between server processes,
+// e.g. by loading them from a file or a key management service.
+pub trait TicketKeyProvider: Send + Sync {
+    // key used to encrypt new tickets
+    fn encryption_key(&self) -> TicketKey;
+
+    // key used to decrypt a ticket that was encrypted under key_name.
+    // Return None for unknown or retired keys, the client then falls back to a full handshake.
+    fn decryption_key(&self, key_name: &[u8]) -> Option<TicketKey>;
+}
+
+// Generates a random key every rotation_interval. Tickets encrypted under the previous key
+// can still be decrypted until the next rotation.
+pub struct RotatingTicketKeys {
+    rotation_interval: Duration,
+    keys: Mutex<RotatingKeys>,
+}
+
+struct RotatingKeys {
+    current: TicketKey,
+    previous: Option<TicketKey>,
+    rotated_at: Instant,
+}
+
+impl RotatingTicketKeys {
+    pub fn new(rotation_interval: Duration) -> Self {
+        RotatingTicketKeys {
+            rotation_interval,
+            keys: Mutex::new(RotatingKeys {
+                current: TicketKey::generate(),
+                previous: None,
+                rotated_at: Instant::now(),
+            }),
+        }
+    }
+
+    fn rotate_if_needed(&self, keys: &mut RotatingKeys) {
+        if keys.rotated_at.elapsed() >= self.rotation_interval {
+            keys.previous = Some(std::mem::replace(&mut keys.current, TicketKey::generate()));
+            keys.rotated_at = Instant::now();
+        }
+    }
+}
+
+impl TicketKeyProvider for RotatingTicketKeys {
+    fn encryption_key(&self) -> TicketKey {
+        let mut keys = self.keys.lock().unwrap();
+        self.rotate_if_needed(&mut keys);
+        keys.current.clone()
+    }
+
+    fn decryption_key(&self, key_name: &[u8]) -> Option<TicketKey> {
+        let mut keys = self.keys.lock().unwrap();
+        self.rotate_if_needed(&mut keys);
+        let key = [Some(&keys.current), keys.previous.as_ref()]
+            .into_iter()
+            .flatten()
+            .find(|key| key.name() == key_name)
+            .cloned();
+        key
+    }
+}
+
+// Encrypts and decrypts session tickets with keys from a TicketKeyProvider.
+pub struct Ticketer {
+    provider: Arc<dyn TicketKeyProvider>,
+    // seconds, sent to the client as ticket_lifetime_hint
+    lifetime: u32,
+}
+
+impl Ticketer {
+    pub fn new(provider: Arc<dyn TicketKeyProvider>, lifetime: u32) -> Self {
+        Ticketer { provider, lifetime }
+    }
+
+    pub fn lifetime(&self) -> u32 {
+        self.lifetime
+    }
+
+    // encoded NewSessionTicket handshake message carrying a ticket for value
+    pub fn new_session_ticket(&self, value: &ServerSessionValue) -> Vec<u8> {
+        NewSessionTicket::new(self.lifetime, self.encrypt(value)).encode()
+    }
+
+    pub fn encrypt(&self, value: &ServerSessionValue) -> Vec<u8> {
+        let key = self.provider.encryption_key();
+        let mut nonce = [0; aead::NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let mut encrypted_state = value.encode();
+        key.aead_key()
+            .seal_in_place_append_tag(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(key.name()),
+                &mut encrypted_state,
+            )
+            .unwrap();
+
+        let mut ticket = key.name().to_vec();
+        ticket.extend(nonce);
+        ticket.extend(encrypted_state);
+        ticket
+    }
+
+    // returns None if the ticket is malformed, encrypted under an unknown key or expired
+    pub fn decrypt(&self, ticket: &[u8]) -> Option<ServerSessionValue> {
+        if ticket.len() < KEY_NAME_LEN + aead::NONCE_LEN {
+            return None;
+        }
+        let (key_name, rest) = ticket.split_at(KEY_NAME_LEN);
+        let (nonce, encrypted_state) = rest.split_at(aead::NONCE_LEN);
+        let key = self.provider.decryption_key(key_name)?;
+
+        let mut in_out = encrypted_state.to_vec();
+        let state = key
+            .aead_key()
+            .open_in_place(
+                aead::Nonce::try_assume_unique_for_key(nonce).ok()?,
+                aead::Aad::from(key_name),
+                &mut in_out,
+            )
+            .ok()?;
+        let value = ServerSessionValue::read(state)?;
+
+        // a creation_time that overflows is bogus, treat it as expired
+        let expiry = value.creation_time.checked_add(u64::from(self.lifetime));
+        if expiry.is_none_or(|expiry| expiry < unix_time()) {
+            return None;
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> ServerSessionValue {
+        ServerSessionValue {
+            cipher_suite: vec![0xc0, 0x30],
+            master_secret: vec![0x42; 48],
+            creation_time: unix_time(),
+            extended_master_secret: true,
+            peer_certificates: vec![vec![0x30, 0x00]],
+        }
+    }
+
+    #[test]
+    fn ticket_round_trip() {
+        let provider = Arc::new(RotatingTicketKeys::new(Duration::from_secs(3600)));
+        let ticketer = Ticketer::new(provider, 3600);
+        let ticket = ticketer.encrypt(&session());
+        assert_eq!(ticketer.decrypt(&ticket), Some(session()));
+
+        let mut tampered = ticket.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(ticketer.decrypt(&tampered), None);
+    }
+
+    #[test]
+    fn previous_key_still_decrypts() {
+        let provider = Arc::new(RotatingTicketKeys::new(Duration::ZERO));
+        let ticketer = Ticketer::new(provider, 3600);
+        // every call rotates, so the ticket is decrypted with the previous key
+        let ticket = ticketer.encrypt(&session());
+        assert_eq!(ticketer.decrypt(&ticket), Some(session()));
+        // rotated twice since encryption
+        ticketer.encrypt(&session());
+        assert_eq!(ticketer.decrypt(&ticket), None);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::enums::ContentType;
+use ring::aead;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#6-2-3-3--AEAD-Ciphers
+// struct {
+//     ContentType type;
+//     ProtocolVersion version;
+//     uint16 length;
+//     select (SecurityParameters.cipher_type) {
+//         case stream: GenericStreamCipher;
+//         case block:  GenericBlockCipher;
+//         case aead:   GenericAEADCipher;
+//     } fragment;
+// } TLSCiphertext;
+//
+// struct {
+//    opaque nonce_explicit[SecurityParameters.record_iv_length];
+//    aead-ciphered struct {
+//        opaque content[TLSCompressed.length];
+//    };
+// } GenericAEADCipher;
+//
+// additional_data = seq_num + TLSCompressed.type +
+//                   TLSCompressed.version + TLSCompressed.length;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5288.html#3--AES-GCM-Cipher-Suites
+// struct {
+//    opaque salt[4];
+//    opaque nonce_explicit[8];
+// } GCMNonce;
+const EXPLICIT_NONCE_LEN: usize = 8;
+
+pub struct GenericAEADCipher {
+    key: aead::LessSafeKey,
+    salt: Vec<u8>,
+}
+
+impl GenericAEADCipher {
+    pub fn new(algorithm: &'static aead::Algorithm, key: &[u8], salt: &[u8]) -> Self {
+        GenericAEADCipher {
+            key: aead::LessSafeKey::new(aead::UnboundKey::new(algorithm, key).unwrap()),
+            salt: salt.to_vec(),
+        }
+    }
+
+    // the sequence number is used as nonce_explicit, it is unique for each record under one key
+    pub fn encrypt(&self, seq_num: u64, content_type: ContentType, plaintext: &[u8]) -> Vec<u8> {
+        let nonce_explicit = seq_num.to_be_bytes();
+        let nonce = self.nonce(&nonce_explicit);
+        let aad = additional_data(seq_num, content_type, plaintext.len());
+
+        let mut in_out = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(nonce, aead::Aad::from(aad), &mut in_out)
+            .unwrap();
+
+        let mut fragment = nonce_explicit.to_vec();
+        fragment.extend(in_out);
+        fragment
+    }
+
+    // returns None when the record fails to authenticate (bad_record_mac)
+    pub fn decrypt(
+        &self,
+        seq_num: u64,
+        content_type: ContentType,
+        fragment: &[u8],
+    ) -> Option<Vec<u8>> {
+        let tag_len = self.key.algorithm().tag_len();
+        if fragment.len() < EXPLICIT_NONCE_LEN + tag_len {
+            return None;
+        }
+        let nonce = self.nonce(&fragment[..EXPLICIT_NONCE_LEN]);
+        let plaintext_len = fragment.len() - EXPLICIT_NONCE_LEN - tag_len;
+        let aad = additional_data(seq_num, content_type, plaintext_len);
+
+        let mut in_out = fragment[EXPLICIT_NONCE_LEN..].to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, aead::Aad::from(aad), &mut in_out)
+            .ok()?;
+        Some(plaintext.to_vec())
+    }
+
+    fn nonce(&self, nonce_explicit: &[u8]) -> aead::Nonce {
+        let mut nonce = [0; aead::NONCE_LEN];
+        nonce[..4].copy_from_slice(&self.salt);
+        nonce[4..].copy_from_slice(nonce_explicit);
+        aead::Nonce::assume_unique_for_key(nonce)
+    }
+}
+
+fn additional_data(seq_num: u64, content_type: ContentType, length: usize) -> Vec<u8> {
+    let mut aad = seq_num.to_be_bytes().to_vec();
+    aad.push(content_type.encode());
+    // TLS 1.2
+    aad.extend([0x03, 0x03]);
+    aad.extend((length as u16).to_be_bytes());
+    aad
+}
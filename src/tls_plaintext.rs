@@ -1,5 +1,15 @@
 use crate::enums::ContentType;
+use crate::error::Error;
 use crate::protocol_version::ProtocolVersion;
+use byteorder::{BigEndian, ByteOrder};
+
+// Record header size: ContentType(1) + ProtocolVersion(2) + length(2)
+pub const RECORD_HEADER_LEN: usize = 5;
+// The record layer fragments information blocks into TLSPlaintext records carrying data in chunks of 2^14 bytes or less.
+pub const MAX_FRAGMENT_LEN: usize = 16384;
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#6-2-3--Record-Payload-Protection
+// The length of TLSCiphertext.fragment MUST NOT exceed 2^14 + 2048.
+pub const MAX_CIPHERTEXT_LEN: usize = MAX_FRAGMENT_LEN + 2048;
 
 // Ref: https://tex2e.github.io/rfc-translater/html/rfc5246.html#A-1--Record-Layer
 // struct {
@@ -31,7 +41,26 @@ impl TLSPlaintext {
         bytes
     }
 
-    pub fn read(buffer: Vec<u8>) -> Self {
-        todo!()
+    // read TLSPlaintext header (5 bytes). version is kept as it is on the wire.
+    pub fn read(buffer: Vec<u8>) -> Result<Self, Error> {
+        if buffer.len() != RECORD_HEADER_LEN {
+            return Err(Error::Decode("TLSPlaintext"));
+        }
+        Ok(TLSPlaintext {
+            content_type: ContentType::read(buffer[0]),
+            version: ProtocolVersion {
+                major: buffer[1],
+                minor: buffer[2],
+            },
+            length: BigEndian::read_u16(&buffer[3..5]),
+        })
+    }
+
+    pub fn content_type(&self) -> ContentType {
+        self.content_type
+    }
+
+    pub fn length(&self) -> u16 {
+        self.length
     }
 }
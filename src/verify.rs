@@ -0,0 +1,194 @@
+use crate::certificate::{is_issued_by, CertificateInfo, SubjectAltName};
+use crate::error::CertificateError;
+use crate::loader::load_certificates;
+use std::fs;
+use std::io;
+use std::net::IpAddr;
+use std::path::Path;
+
+// Trust anchors for the peer's certificate chain, DER encoded CA certificates.
+// A chain is trusted if it leads to one of them, see verify_chain.
+#[derive(Debug, Clone, Default)]
+pub struct RootCertStore {
+    roots: Vec<Vec<u8>>,
+}
+
+impl RootCertStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // der is a DER encoded certificate
+    pub fn add(&mut self, der: &[u8]) -> io::Result<()> {
+        if CertificateInfo::parse(der).is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a DER encoded certificate",
+            ));
+        }
+        self.roots.push(der.to_vec());
+        Ok(())
+    }
+
+    // every certificate of a PEM file, or a single DER encoded certificate
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        for certificate in load_certificates(&fs::read(path)?)? {
+            self.add(&certificate)?;
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.roots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    // DER encoded subject names, e.g. the certificate_authorities of CertificateRequest
+    pub fn subjects(&self) -> Vec<Vec<u8>> {
+        self.roots
+            .iter()
+            .filter_map(|root| {
+                let (_, cert) = x509_parser::parse_x509_certificate(root).ok()?;
+                Some(cert.subject().as_raw().to_vec())
+            })
+            .collect()
+    }
+}
+
+// longest path from the leaf to a trust anchor, a bound for chains with loops
+const MAX_PATH_LEN: usize = 8;
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc5280.html#6-1--Basic-Path-Validation
+// chain is the peer's certificate_list, leaf first. Intermediates may come in any order and
+// unused ones are ignored. Every certificate on the path must be valid at now (seconds since
+// the UNIX epoch) and every intermediate must be a CA. The trust anchor itself is not checked.
+// Returns the path that was built, leaf first and ending with the trust anchor. Checks that
+// depend on the issuers, such as revocation and pinning, must use it instead of chain.
+pub fn verify_chain(
+    chain: &[Vec<u8>],
+    roots: &RootCertStore,
+    now: u64,
+) -> Result<Vec<Vec<u8>>, CertificateError> {
+    let Some(leaf) = chain.first() else {
+        return Err(CertificateError::Bad(String::from("no certificate")));
+    };
+    let mut path = vec![leaf.clone()];
+    let mut current = leaf;
+    for _ in 0..MAX_PATH_LEN {
+        check_validity(current, now)?;
+        if roots.roots.contains(current) {
+            return Ok(path);
+        }
+        if let Some(root) = roots.roots.iter().find(|root| is_issued_by(current, root)) {
+            path.push(root.clone());
+            return Ok(path);
+        }
+        let issuer = chain[1..].iter().find(|candidate| {
+            *candidate != current
+                && CertificateInfo::parse(candidate).is_some_and(|info| info.is_ca)
+                && is_issued_by(current, candidate)
+        });
+        match issuer {
+            Some(issuer) => {
+                path.push(issuer.clone());
+                current = issuer;
+            }
+            None => break,
+        }
+    }
+    Err(CertificateError::UnknownIssuer(String::from(
+        "the certificate chain does not lead to a trust anchor",
+    )))
+}
+
+fn check_validity(certificate: &[u8], now: u64) -> Result<(), CertificateError> {
+    let Some(info) = CertificateInfo::parse(certificate) else {
+        return Err(CertificateError::Bad(String::from("invalid certificate")));
+    };
+    let now = now as i64;
+    if now < info.not_before || now > info.not_after {
+        return Err(CertificateError::Expired(format!(
+            "certificate \"{}\" is not valid now",
+            info.subject
+        )));
+    }
+    Ok(())
+}
+
+// Ref: https://tex2e.github.io/rfc-translater/html/rfc6125.html#6-4--Matching-the-DNS-Domain-Name-Portion
+// server_name must be one of the leaf's subjectAltNames: a dNSName, where "*" may stand for
+// the whole leftmost label, or an iPAddress for an IP literal. The subject CN is not used.
+pub fn verify_server_name(leaf: &[u8], server_name: &str) -> Result<(), CertificateError> {
+    let Some(info) = CertificateInfo::parse(leaf) else {
+        return Err(CertificateError::Bad(String::from("invalid certificate")));
+    };
+    let ip_address = server_name.parse::<IpAddr>().ok();
+    let server_name = normalize(server_name);
+    let matches = info.subject_alt_names.iter().any(|name| match name {
+        SubjectAltName::Dns(pattern) => match ip_address {
+            Some(_) => false,
+            None => dns_name_matches(&normalize(pattern), &server_name),
+        },
+        SubjectAltName::Ip(address) => ip_address == Some(*address),
+        _ => false,
+    });
+    if !matches {
+        return Err(CertificateError::Bad(format!(
+            "the certificate is not valid for {}",
+            server_name
+        )));
+    }
+    Ok(())
+}
+
+fn dns_name_matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(parent) => name
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest == parent),
+        None => pattern == name,
+    }
+}
+
+// host names are compared case-insensitively and without a trailing dot
+fn normalize(name: &str) -> String {
+    name.trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA: &[u8] = include_bytes!("../testdata/ocsp/ca.der");
+    const LEAF: &[u8] = include_bytes!("../testdata/ct/leaf.der");
+    // 2026-10-20 00:00:00 UTC, the leaf is valid for a year from 2026-10-19
+    const NOW: u64 = 1792454400;
+
+    #[test]
+    fn verify_chain_and_name() {
+        let mut roots = RootCertStore::new();
+        roots.add(CA).unwrap();
+        let chain = [LEAF.to_vec(), CA.to_vec()];
+        assert_eq!(verify_chain(&chain, &roots, NOW), Ok(chain.to_vec()));
+        // the trust anchor completes the path
+        assert_eq!(verify_chain(&chain[..1], &roots, NOW), Ok(chain.to_vec()));
+        assert!(matches!(
+            verify_chain(&chain, &RootCertStore::new(), NOW),
+            Err(CertificateError::UnknownIssuer(_))
+        ));
+        assert!(matches!(
+            verify_chain(&chain, &roots, NOW + 366 * 86400),
+            Err(CertificateError::Expired(_))
+        ));
+
+        assert_eq!(verify_server_name(LEAF, "LOCALHOST."), Ok(()));
+        assert!(verify_server_name(LEAF, "example.com").is_err());
+        assert!(verify_server_name(LEAF, "127.0.0.1").is_err());
+        assert!(dns_name_matches("*.example.com", "www.example.com"));
+        assert!(!dns_name_matches("*.example.com", "a.b.example.com"));
+        assert!(!dns_name_matches("*.example.com", "example.com"));
+    }
+}